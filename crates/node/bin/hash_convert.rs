@@ -21,6 +21,6 @@ fn main() {
 
     // Print both representations
     println!("Hash representations:");
-    println!("  Base32-NoPadding:    {}", hash.to_string());
+    println!("  Base32-NoPadding:    {}", hash);
     println!("  Hex: {}", hash.to_hex());
 }
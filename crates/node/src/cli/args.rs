@@ -2,14 +2,23 @@ use std::error::Error;
 
 use clap::Subcommand;
 
+use super::ops::Acl as AclOp;
+use super::ops::Blobs as BlobsOp;
+use super::ops::Doctor as DoctorOp;
+use super::ops::Fetch as FetchOp;
+use super::ops::Get as GetOp;
 use super::ops::Init as InitOp;
 use super::ops::List as ListOp;
 use super::ops::Node as NodeOp;
+use super::ops::Peer as PeerOp;
 use super::ops::Pools as PoolsOp;
 use super::ops::Probe as ProbeOp;
 use super::ops::Query as QueryOp;
+use super::ops::Rm as RmOp;
 use super::ops::Share as ShareOp;
+use super::ops::Stats as StatsOp;
 use super::ops::Status as StatusOp;
+use super::ops::Transfers as TransfersOp;
 
 pub use clap::Parser;
 
@@ -82,6 +91,15 @@ command_enum! {
     (Share, ShareOp),
     (Probe, ProbeOp),
     (Pools, PoolsOp),
+    (Peer, PeerOp),
+    (Blobs, BlobsOp),
+    (Acl, AclOp),
+    (Rm, RmOp),
+    (Get, GetOp),
+    (Fetch, FetchOp),
+    (Transfers, TransfersOp),
+    (Stats, StatsOp),
+    (Doctor, DoctorOp),
 }
 
 impl fmt::Display for OpOutput {
@@ -99,6 +117,15 @@ impl fmt::Display for OpOutput {
             OpOutput::Share(output) => write!(f, "{}", output),
             OpOutput::Probe(output) => write!(f, "{}", output),
             OpOutput::Pools(output) => write!(f, "{}", output),
+            OpOutput::Peer(output) => write!(f, "{}", output),
+            OpOutput::Blobs(output) => write!(f, "{}", output),
+            OpOutput::Acl(output) => write!(f, "{}", output),
+            OpOutput::Rm(output) => write!(f, "{}", output),
+            OpOutput::Get(output) => write!(f, "{}", output),
+            OpOutput::Fetch(output) => write!(f, "{}", output),
+            OpOutput::Transfers(output) => write!(f, "{}", output),
+            OpOutput::Stats(output) => write!(f, "{}", output),
+            OpOutput::Doctor(output) => write!(f, "{}", output),
         }
     }
 }
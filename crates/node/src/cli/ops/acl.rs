@@ -0,0 +1,142 @@
+use std::fmt;
+
+use async_trait::async_trait;
+use clap::Subcommand;
+use iroh::NodeId;
+use iroh_blobs::Hash;
+
+use jax::config::{Config, ConfigError};
+
+use super::api_client::api_requests::AclEntry;
+use super::api_client::{api_requests, ApiClient, ApiError};
+use crate::cli::args::Op;
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct Acl {
+    #[command(subcommand)]
+    command: Option<AclCommand>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum AclCommand {
+    /// Show every ACL (the default)
+    List,
+    /// Show who may fetch a hash
+    Show {
+        #[clap(value_parser)]
+        hash: Hash,
+    },
+    /// Let nodes fetch a hash, making it private if it wasn't already
+    Allow {
+        #[clap(value_parser)]
+        hash: Hash,
+        #[clap(value_parser, required = true)]
+        nodes: Vec<NodeId>,
+    },
+    /// Stop nodes fetching a hash
+    Revoke {
+        #[clap(value_parser)]
+        hash: Hash,
+        #[clap(value_parser, required = true)]
+        nodes: Vec<NodeId>,
+    },
+    /// Let anyone fetch a hash, keeping its node list
+    Public {
+        #[clap(value_parser)]
+        hash: Hash,
+    },
+    /// Let only the nodes on its list fetch a hash
+    Private {
+        #[clap(value_parser)]
+        hash: Hash,
+    },
+    /// Drop a hash's ACL, leaving it to the serving policy
+    Clear {
+        #[clap(value_parser)]
+        hash: Hash,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AclError {
+    #[error("api error: {0}")]
+    Api(#[from] ApiError),
+    #[error("config error: {0}")]
+    Config(#[from] ConfigError),
+}
+
+#[derive(Debug)]
+pub enum AclOutput {
+    Listed(Vec<AclEntry>),
+    Entry(AclEntry),
+    Cleared(Hash),
+}
+
+fn write_entry(f: &mut fmt::Formatter<'_>, entry: &AclEntry) -> fmt::Result {
+    let access = if entry.public { "public" } else { "private" };
+    writeln!(f, "{}  {}", entry.hash, access)?;
+    for node_id in &entry.nodes {
+        writeln!(f, "  allow: {}", node_id)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for AclOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AclOutput::Listed(entries) if entries.is_empty() => write!(f, "No ACLs"),
+            AclOutput::Listed(entries) => {
+                for entry in entries {
+                    write_entry(f, entry)?;
+                }
+                Ok(())
+            }
+            AclOutput::Entry(entry) => write_entry(f, entry),
+            AclOutput::Cleared(hash) => write!(f, "cleared ACL for {}", hash),
+        }
+    }
+}
+
+#[async_trait]
+impl Op for Acl {
+    type Error = AclError;
+    type Output = AclOutput;
+
+    async fn execute(&self) -> Result<Self::Output, Self::Error> {
+        let config = Config::from_env_or_disk()?;
+        let client = ApiClient::new(config.remote_url().as_ref())?;
+
+        let update =
+            |hash: &Hash, public, allow: &[NodeId], revoke: &[NodeId]| api_requests::UpdateAcl {
+                hash: *hash,
+                public,
+                allow: allow.to_vec(),
+                revoke: revoke.to_vec(),
+            };
+        let output = match &self.command {
+            None | Some(AclCommand::List) => {
+                AclOutput::Listed(client.call(api_requests::Acls {}).await?)
+            }
+            Some(AclCommand::Show { hash }) => {
+                AclOutput::Entry(client.call(api_requests::GetAcl { hash: *hash }).await?)
+            }
+            Some(AclCommand::Allow { hash, nodes }) => {
+                AclOutput::Entry(client.call(update(hash, None, nodes, &[])).await?)
+            }
+            Some(AclCommand::Revoke { hash, nodes }) => {
+                AclOutput::Entry(client.call(update(hash, None, &[], nodes)).await?)
+            }
+            Some(AclCommand::Public { hash }) => {
+                AclOutput::Entry(client.call(update(hash, Some(true), &[], &[])).await?)
+            }
+            Some(AclCommand::Private { hash }) => {
+                AclOutput::Entry(client.call(update(hash, Some(false), &[], &[])).await?)
+            }
+            Some(AclCommand::Clear { hash }) => {
+                let response = client.call(api_requests::DeleteAcl { hash: *hash }).await?;
+                AclOutput::Cleared(response.hash)
+            }
+        };
+        Ok(output)
+    }
+}
@@ -0,0 +1,78 @@
+use iroh::NodeId;
+use iroh_blobs::Hash;
+use reqwest::{Client, RequestBuilder, Url};
+use serde::{Deserialize, Serialize};
+
+use super::ApiRequest;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AclEntry {
+    pub hash: Hash,
+    pub public: bool,
+    pub nodes: Vec<NodeId>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Acls {}
+
+impl ApiRequest for Acls {
+    type Response = Vec<AclEntry>;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url.join("api/v0/acl").expect("Failed to join URL");
+        client.get(url)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GetAcl {
+    pub hash: Hash,
+}
+
+impl ApiRequest for GetAcl {
+    type Response = AclEntry;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join(&format!("api/v0/acl/{}", self.hash))
+            .expect("Failed to join URL");
+        client.get(url)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateAcl {
+    #[serde(skip)]
+    pub hash: Hash,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public: Option<bool>,
+    pub allow: Vec<NodeId>,
+    pub revoke: Vec<NodeId>,
+}
+
+impl ApiRequest for UpdateAcl {
+    type Response = AclEntry;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join(&format!("api/v0/acl/{}", self.hash))
+            .expect("Failed to join URL");
+        client.patch(url).json(&self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeleteAcl {
+    pub hash: Hash,
+}
+
+impl ApiRequest for DeleteAcl {
+    type Response = AclEntry;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join(&format!("api/v0/acl/{}", self.hash))
+            .expect("Failed to join URL");
+        client.delete(url)
+    }
+}
@@ -0,0 +1,71 @@
+use iroh_blobs::{BlobFormat, Hash};
+use reqwest::{Client, RequestBuilder, Url};
+use serde::{Deserialize, Serialize};
+
+use super::ApiRequest;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Pin {
+    pub hash: Hash,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PinResponse {
+    pub hash: Hash,
+    pub format: BlobFormat,
+    pub message: String,
+}
+
+impl ApiRequest for Pin {
+    type Response = PinResponse;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join("api/v0/blobs/pin")
+            .expect("Failed to join URL");
+        client.post(url).json(&self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Unpin {
+    pub hash: Hash,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UnpinResponse {
+    pub hash: Hash,
+    pub message: String,
+}
+
+impl ApiRequest for Unpin {
+    type Response = UnpinResponse;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join("api/v0/blobs/unpin")
+            .expect("Failed to join URL");
+        client.post(url).json(&self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Gc {}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GcResponse {
+    pub evicted: Vec<Hash>,
+    pub bytes_reclaimed: u64,
+    pub message: String,
+}
+
+impl ApiRequest for Gc {
+    type Response = GcResponse;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join("api/v0/blobs/gc")
+            .expect("Failed to join URL");
+        client.post(url)
+    }
+}
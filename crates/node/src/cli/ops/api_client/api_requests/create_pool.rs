@@ -1,3 +1,4 @@
+use iroh_blobs::BlobFormat;
 use reqwest::{Client, RequestBuilder, Url};
 use serde::{Deserialize, Serialize};
 
@@ -6,6 +7,7 @@ use super::ApiRequest;
 #[derive(Debug, Clone, Serialize)]
 pub struct CreatePool {
     pub hash: String,
+    pub format: BlobFormat,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -0,0 +1,32 @@
+use alloy::primitives::Address;
+use iroh_blobs::Hash;
+use reqwest::{Client, RequestBuilder, Url};
+use serde::{Deserialize, Serialize};
+
+use super::ApiRequest;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeleteBlob {
+    #[serde(skip)]
+    pub hash: Hash,
+    pub force: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeleteBlobResponse {
+    pub hash: Hash,
+    pub left_pools: Vec<Address>,
+    pub bytes_reclaimed: u64,
+    pub message: String,
+}
+
+impl ApiRequest for DeleteBlob {
+    type Response = DeleteBlobResponse;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join(&format!("api/v0/blob/{}", self.hash))
+            .expect("Failed to join URL");
+        client.delete(url).query(&self)
+    }
+}
@@ -0,0 +1,75 @@
+use std::net::SocketAddr;
+
+use iroh::NodeId;
+use jax::node::{AddrSource, NatType, PathKind};
+use reqwest::{Client, RequestBuilder, Url};
+use serde::{Deserialize, Serialize};
+
+use super::ApiRequest;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DirectAddress {
+    pub addr: SocketAddr,
+    pub source: AddrSource,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostics {}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SelfDiagnostics {
+    pub node_id: NodeId,
+    pub bound_addrs: Vec<SocketAddr>,
+    pub direct_addrs: Vec<DirectAddress>,
+    pub relay_url: Option<String>,
+    pub nat: NatType,
+}
+
+impl ApiRequest for Diagnostics {
+    type Response = SelfDiagnostics;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join("api/v0/diagnostics")
+            .expect("Failed to join URL");
+        client.get(url)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiscoveryRecord {
+    pub at: u64,
+    pub provenance: Option<String>,
+    pub relay_url: Option<String>,
+    pub direct_addrs: Vec<SocketAddr>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerDiagnostics {
+    pub node_id: NodeId,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PeerDiagnosticsResponse {
+    pub node_id: NodeId,
+    pub reachable: bool,
+    pub error: Option<String>,
+    pub path: PathKind,
+    pub addr: Option<SocketAddr>,
+    pub relay_url: Option<String>,
+    pub rtt_ms: Option<f64>,
+    pub discovery: Option<DiscoveryRecord>,
+    pub last_resolved: Option<DiscoveryRecord>,
+}
+
+impl ApiRequest for PeerDiagnostics {
+    type Response = PeerDiagnosticsResponse;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join(&format!("api/v0/diagnostics/{}", self.node_id))
+            .expect("Failed to join URL");
+        client.get(url)
+    }
+}
@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use iroh_blobs::{BlobFormat, Hash};
+use jax::node::{ContentKey, ExportLink};
+use reqwest::{Client, RequestBuilder, Url};
+use serde::{Deserialize, Serialize};
+
+use super::ApiRequest;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Export {
+    /// A hash or a ticket
+    pub content: String,
+    /// Relative to the node's export directory
+    pub output: PathBuf,
+    pub link: ExportLink,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<ContentKey>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportResponse {
+    pub hash: Hash,
+    pub format: BlobFormat,
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl ApiRequest for Export {
+    type Response = ExportResponse;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url.join("api/v0/export").expect("Failed to join URL");
+        client.post(url).json(&self)
+    }
+}
@@ -0,0 +1,60 @@
+use alloy::primitives::{Address, U256};
+use iroh_blobs::{BlobFormat, Hash};
+use reqwest::{Client, RequestBuilder, Url};
+use serde::{Deserialize, Serialize};
+
+use super::ApiRequest;
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchPool {
+    #[default]
+    None,
+    Join,
+    Create,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Fetch {
+    pub ticket: String,
+    pub pool: FetchPool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_address: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_value: Option<U256>,
+    pub pin: bool,
+    pub progress: bool,
+}
+
+/// Sent as `progress` events while a fetch runs
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FetchProgress {
+    Connected,
+    FoundLocal { hash: Hash, size: u64 },
+    Found { id: u64, hash: Hash, size: u64 },
+    FoundHashSeq { hash: Hash, children: u64 },
+    Progress { id: u64, offset: u64 },
+    Done { id: u64 },
+    AllDone {},
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FetchResponse {
+    pub hash: Hash,
+    pub format: BlobFormat,
+    pub bytes_read: u64,
+    pub elapsed_ms: u64,
+    pub joined_pool: Option<Address>,
+    pub created_pool: bool,
+    pub message: String,
+}
+
+impl ApiRequest for Fetch {
+    type Response = FetchResponse;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url.join("api/v0/fetch").expect("Failed to join URL");
+        client.post(url).json(&self)
+    }
+}
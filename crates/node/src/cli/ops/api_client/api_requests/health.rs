@@ -1,3 +1,5 @@
+use std::net::SocketAddr;
+
 use alloy::primitives::Address;
 use iroh::NodeId;
 use reqwest::{Client, RequestBuilder, Url};
@@ -23,6 +25,10 @@ impl LivenessResponse {
 pub struct ReadinessResponse {
     pub node_id: NodeId,
     pub eth_address: Address,
+    #[serde(default)]
+    pub bound_addrs: Vec<SocketAddr>,
+    #[serde(default)]
+    pub direct_addrs: Vec<SocketAddr>,
 }
 
 impl ApiRequest for Liveness {
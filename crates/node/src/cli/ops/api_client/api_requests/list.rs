@@ -1,19 +1,52 @@
+use alloy::primitives::Address;
+use iroh_blobs::Hash;
 use reqwest::{Client, RequestBuilder, Url};
 use serde::{Deserialize, Serialize};
 
 use super::ApiRequest;
 
-#[derive(Debug, Clone)]
-pub struct List;
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct List {
+    pub offset: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// "complete" or "partial"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pooled: Option<bool>,
+}
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlobTag {
+    pub kind: String,
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoolMembership {
+    pub address: Address,
+    pub rank: Option<usize>,
+    pub peers: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct BlobInfo {
-    pub hash: String,
+    pub hash: Hash,
+    pub size: Option<u64>,
+    pub complete: bool,
+    pub tags: Vec<BlobTag>,
+    pub created: Option<u64>,
+    pub pools: Vec<PoolMembership>,
+    pub pinned: bool,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ListResponse {
     pub blobs: Vec<BlobInfo>,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
 }
 
 impl ApiRequest for List {
@@ -21,6 +54,6 @@ impl ApiRequest for List {
 
     fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
         let url = base_url.join("api/v0/list").expect("valid URL");
-        client.get(url)
+        client.get(url).query(&self)
     }
 }
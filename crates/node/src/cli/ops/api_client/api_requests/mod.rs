@@ -1,18 +1,42 @@
+mod acl;
+mod blobs;
 mod create_pool;
+mod delete;
+mod diagnostics;
+mod export;
+mod fetch;
 mod health;
 mod list;
+mod peer;
 mod pools;
 mod probe;
+mod pull;
 mod query;
 mod share;
+mod stats;
+mod transfers;
+mod upload;
 
+pub use acl::{AclEntry, Acls, DeleteAcl, GetAcl, UpdateAcl};
+pub use blobs::{Gc, Pin, Unpin};
 pub use create_pool::{CreatePool, CreatePoolResponse};
+pub use delete::DeleteBlob;
+pub use diagnostics::{
+    Diagnostics, DiscoveryRecord, PeerDiagnostics, PeerDiagnosticsResponse, SelfDiagnostics,
+};
+pub use export::Export;
+pub use fetch::{Fetch, FetchPool, FetchProgress, FetchResponse};
 pub use health::{Liveness, Readiness};
-pub use list::{List, ListResponse};
-pub use pools::{Pools, PoolsResponse};
+pub use list::{BlobInfo, List, ListResponse};
+pub use peer::{Peer, PeerResponse};
+pub use pools::{PoolEntry, Pools, PoolsResponse};
 pub use probe::{Probe, ProbeStats};
+pub use pull::{Pull, PullResponse};
 pub use query::Query;
-pub use share::Share;
+pub use share::{Share, ShareResponse};
+pub use stats::{Counters, Stats, StatsResponse};
+pub use transfers::{CancelTransfer, TransferInfo, Transfers};
+pub use upload::{CreateUpload, FinalizeUpload, PatchUpload, UploadStatus};
 
 use reqwest::{Client, RequestBuilder, Url};
 use serde::de::DeserializeOwned;
@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+use alloy::primitives::Address;
+use iroh::NodeId;
+use iroh_blobs::{BlobFormat, Hash};
+use reqwest::{Client, RequestBuilder, Url};
+use serde::{Deserialize, Serialize};
+
+use super::ApiRequest;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Peer {
+    pub node_id: NodeId,
+    pub pool: Option<Address>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PeerIdentity {
+    pub node_id: NodeId,
+    pub beneficiary: Address,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PeerPool {
+    pub address: Address,
+    pub hash: Hash,
+    pub format: BlobFormat,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PeerResponse {
+    pub identity: PeerIdentity,
+    pub verified: bool,
+    pub pools: Vec<PeerPool>,
+    pub trust_row: Option<HashMap<NodeId, f64>>,
+}
+
+impl ApiRequest for Peer {
+    type Response = PeerResponse;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let mut url = base_url
+            .join(&format!("api/v0/peer/{}", self.node_id))
+            .expect("Failed to join URL");
+        if let Some(pool) = self.pool {
+            url.query_pairs_mut().append_pair("pool", &pool.to_string());
+        }
+        client.get(url)
+    }
+}
@@ -1,4 +1,4 @@
-use alloy::primitives::Address;
+use alloy::primitives::{Address, U256};
 use iroh::NodeId;
 use iroh_blobs::Hash;
 use reqwest::{Client, RequestBuilder, Url};
@@ -9,9 +9,12 @@ use super::ApiRequest;
 #[derive(Debug, Clone, Serialize)]
 pub struct Pools {}
 
+/// (pool address, hash, balance, peers sorted by trust)
+pub type PoolEntry = (Address, Hash, U256, Vec<(NodeId, f64)>);
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct PoolsResponse {
-    pub pools: Vec<(Address, Hash, Vec<(NodeId, f64)>)>,
+    pub pools: Vec<PoolEntry>,
     pub message: String,
 }
 
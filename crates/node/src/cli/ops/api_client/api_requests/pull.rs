@@ -0,0 +1,37 @@
+use iroh_blobs::{BlobFormat, Hash};
+use reqwest::{Client, RequestBuilder, Url};
+use serde::Deserialize;
+
+use super::ApiRequest;
+
+/// Answered with the blob itself, or for a collection with a
+///  [`PullResponse`] listing its entries
+#[derive(Debug, Clone)]
+pub struct Pull {
+    pub hash: Hash,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CollectionEntry {
+    pub name: String,
+    pub hash: Hash,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PullResponse {
+    pub hash: Hash,
+    pub format: BlobFormat,
+    pub entries: Vec<CollectionEntry>,
+    pub message: String,
+}
+
+impl ApiRequest for Pull {
+    type Response = PullResponse;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join(&format!("api/v0/pull/{}", self.hash))
+            .expect("Failed to join URL");
+        client.get(url)
+    }
+}
@@ -1,3 +1,5 @@
+use iroh_blobs::BlobFormat;
+use jax::node::ImportMode;
 use reqwest::{Client, RequestBuilder, Url};
 use serde::{Deserialize, Serialize};
 
@@ -6,12 +8,15 @@ use super::ApiRequest;
 #[derive(Debug, Clone, Serialize)]
 pub struct Share {
     pub path: String,
+    pub mode: ImportMode,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ShareResponse {
     pub ticket: String,
     pub hash: String,
+    #[serde(default)]
+    pub format: BlobFormat,
     pub message: String,
 }
 
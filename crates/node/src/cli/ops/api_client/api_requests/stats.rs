@@ -0,0 +1,52 @@
+use alloy::primitives::Address;
+use iroh::NodeId;
+use iroh_blobs::Hash;
+use reqwest::{Client, RequestBuilder, Url};
+use serde::{Deserialize, Serialize};
+
+use super::ApiRequest;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Counters {
+    pub served_bytes: u64,
+    pub served_requests: u64,
+    pub serve_failures: u64,
+    pub fetched_bytes: u64,
+    pub fetch_requests: u64,
+    pub fetch_failures: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PeerStats {
+    pub node_id: NodeId,
+    #[serde(flatten)]
+    pub counters: Counters,
+}
+
+/// Pools backed by the same content share its traffic, and come as one
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoolStats {
+    pub hash: Hash,
+    pub addresses: Vec<Address>,
+    #[serde(flatten)]
+    pub counters: Counters,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Stats {}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatsResponse {
+    pub totals: Counters,
+    pub peers: Vec<PeerStats>,
+    pub pools: Vec<PoolStats>,
+}
+
+impl ApiRequest for Stats {
+    type Response = StatsResponse;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url.join("api/v0/stats").expect("Failed to join URL");
+        client.get(url)
+    }
+}
@@ -0,0 +1,67 @@
+use iroh::NodeId;
+use iroh_blobs::{BlobFormat, Hash};
+use reqwest::{Client, RequestBuilder, Url};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::ApiRequest;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransferInfo {
+    pub id: Uuid,
+    pub hash: Hash,
+    pub format: BlobFormat,
+    pub sources: Vec<NodeId>,
+    /// "running", "complete", "failed" or "cancelled"
+    pub state: String,
+    /// Why a failed transfer failed
+    pub reason: Option<String>,
+    pub started: u64,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub chunks_done: u64,
+    pub chunks_total: u64,
+    pub rate: f64,
+    pub eta: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Transfers {}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransfersResponse {
+    pub transfers: Vec<TransferInfo>,
+}
+
+impl ApiRequest for Transfers {
+    type Response = TransfersResponse;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join("api/v0/transfers")
+            .expect("Failed to join URL");
+        client.get(url)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CancelTransfer {
+    pub id: Uuid,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CancelTransferResponse {
+    pub id: Uuid,
+    pub message: String,
+}
+
+impl ApiRequest for CancelTransfer {
+    type Response = CancelTransferResponse;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join(&format!("api/v0/transfers/{}", self.id))
+            .expect("Failed to join URL");
+        client.delete(url)
+    }
+}
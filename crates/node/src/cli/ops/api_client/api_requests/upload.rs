@@ -0,0 +1,79 @@
+use bytes::Bytes;
+use reqwest::{Client, RequestBuilder, Url};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::share::ShareResponse;
+use super::ApiRequest;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateUpload {
+    pub length: u64,
+}
+
+pub struct UploadStatus {
+    pub id: Uuid,
+}
+
+pub struct PatchUpload {
+    pub id: Uuid,
+    pub offset: u64,
+    pub data: Bytes,
+}
+
+pub struct FinalizeUpload {
+    pub id: Uuid,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct UploadInfo {
+    pub id: Uuid,
+    pub offset: u64,
+    pub length: u64,
+}
+
+impl ApiRequest for CreateUpload {
+    type Response = UploadInfo;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url.join("api/v0/uploads").expect("Failed to join URL");
+        client.post(url).json(&self)
+    }
+}
+
+impl ApiRequest for UploadStatus {
+    type Response = UploadInfo;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join(&format!("api/v0/uploads/{}", self.id))
+            .expect("Failed to join URL");
+        client.get(url)
+    }
+}
+
+impl ApiRequest for PatchUpload {
+    type Response = UploadInfo;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join(&format!("api/v0/uploads/{}", self.id))
+            .expect("Failed to join URL");
+        client
+            .patch(url)
+            .header("Content-Type", "application/offset+octet-stream")
+            .header("Upload-Offset", self.offset)
+            .body(self.data)
+    }
+}
+
+impl ApiRequest for FinalizeUpload {
+    type Response = ShareResponse;
+
+    fn build_request(self, base_url: &Url, client: &Client) -> RequestBuilder {
+        let url = base_url
+            .join(&format!("api/v0/uploads/{}/finalize", self.id))
+            .expect("Failed to join URL");
+        client.post(url)
+    }
+}
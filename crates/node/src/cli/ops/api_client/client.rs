@@ -6,6 +6,7 @@ use std::fmt::Debug;
 
 use super::api_requests::ApiRequest;
 use super::error::ApiError;
+use super::events::EventStream;
 
 #[derive(Debug, Clone)]
 /// ApiClient for interacting with our API
@@ -50,4 +51,39 @@ impl ApiClient {
             ))
         }
     }
+
+    /// Call a method whose body is read as it arrives -- raw content
+    ///  rather than JSON, say. The caller decides what to make of it.
+    pub async fn call_stream<T: ApiRequest>(
+        &self,
+        request: T,
+    ) -> Result<reqwest::Response, ApiError> {
+        let request_builder = request.build_request(&self.remote, &self.client);
+        let response = request_builder.send().await?;
+
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(ApiError::HttpStatus(
+                response.status(),
+                response.text().await?,
+            ))
+        }
+    }
+
+    /// Call a method that answers with Server-Sent Events, rather than
+    ///  a single JSON body
+    pub async fn call_events<T: ApiRequest>(&self, request: T) -> Result<EventStream, ApiError> {
+        let request_builder = request.build_request(&self.remote, &self.client);
+        let response = request_builder.send().await?;
+
+        if response.status().is_success() {
+            Ok(EventStream::new(response))
+        } else {
+            Err(ApiError::HttpStatus(
+                response.status(),
+                response.text().await?,
+            ))
+        }
+    }
 }
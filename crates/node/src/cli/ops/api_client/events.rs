@@ -0,0 +1,120 @@
+use reqwest::Response;
+
+use super::error::ApiError;
+
+/// One Server-Sent Event -- just the parts our API uses
+#[derive(Debug, Clone, Default)]
+pub struct ServerEvent {
+    pub event: String,
+    pub data: String,
+}
+
+/// Reads Server-Sent Events off a streaming response as they arrive
+pub struct EventStream {
+    response: Response,
+    // raw bytes -- a chunk can end partway through a character
+    buffer: Vec<u8>,
+}
+
+impl EventStream {
+    pub fn new(response: Response) -> Self {
+        Self {
+            response,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// The next complete event, or `None` once the server hangs up
+    pub async fn next_event(&mut self) -> Result<Option<ServerEvent>, ApiError> {
+        loop {
+            if let Some(block) = next_block(&mut self.buffer) {
+                if let Some(event) = parse_event(&block) {
+                    return Ok(Some(event));
+                }
+                continue;
+            }
+            match self.response.chunk().await? {
+                Some(chunk) => self.buffer.extend_from_slice(&chunk),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Take the first complete event block off the front of `buffer`,
+///  decoded once it's all there. Blocks end at a blank line.
+fn next_block(buffer: &mut Vec<u8>) -> Option<String> {
+    let (end, len) = [&b"\n\n"[..], b"\r\n\r\n"]
+        .iter()
+        .filter_map(|sep| {
+            buffer
+                .windows(sep.len())
+                .position(|window| window == *sep)
+                .map(|end| (end, sep.len()))
+        })
+        .min()?;
+    let block = String::from_utf8_lossy(&buffer[..end]).into_owned();
+    buffer.drain(..end + len);
+    Some(block)
+}
+
+/// Comments and keep-alives carry no data and are skipped
+fn parse_event(block: &str) -> Option<ServerEvent> {
+    let mut event = ServerEvent {
+        event: "message".to_string(),
+        data: String::new(),
+    };
+    let mut has_data = false;
+    for line in block.lines() {
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => event.event = value.to_string(),
+            "data" => {
+                if has_data {
+                    event.data.push('\n');
+                }
+                event.data.push_str(value);
+                has_data = true;
+            }
+            _ => {}
+        }
+    }
+    has_data.then_some(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event() {
+        let event = parse_event("event: progress\ndata: {\"a\":1}").unwrap();
+        assert_eq!(event.event, "progress");
+        assert_eq!(event.data, "{\"a\":1}");
+
+        let event = parse_event("data: one\ndata: two").unwrap();
+        assert_eq!(event.event, "message");
+        assert_eq!(event.data, "one\ntwo");
+
+        assert!(parse_event(": keep-alive").is_none());
+    }
+
+    #[test]
+    fn test_next_block_split_character() {
+        let data = "data: caf\u{e9}\n\n".as_bytes();
+        // split inside the two bytes of the é
+        let split = data.iter().position(|b| *b == 0xc3).unwrap() + 1;
+        let mut buffer = data[..split].to_vec();
+        assert!(next_block(&mut buffer).is_none());
+        buffer.extend_from_slice(&data[split..]);
+        let block = next_block(&mut buffer).unwrap();
+        assert_eq!(parse_event(&block).unwrap().data, "caf\u{e9}");
+        assert!(buffer.is_empty());
+
+        let mut buffer = b"data: one\r\n\r\ndata: two\n\n".to_vec();
+        assert_eq!(next_block(&mut buffer).unwrap(), "data: one");
+        assert_eq!(next_block(&mut buffer).unwrap(), "data: two");
+        assert!(next_block(&mut buffer).is_none());
+    }
+}
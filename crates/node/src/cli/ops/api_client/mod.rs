@@ -1,8 +1,10 @@
 pub mod api_requests;
 mod client;
 mod error;
+mod events;
 
 pub use client::ApiClient;
 pub use error::ApiError;
+pub use events::{EventStream, ServerEvent};
 
 pub use api_requests as requests;
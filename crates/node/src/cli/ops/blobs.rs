@@ -0,0 +1,104 @@
+use std::fmt;
+
+use async_trait::async_trait;
+use clap::Subcommand;
+use iroh_blobs::Hash;
+
+use jax::config::{Config, ConfigError};
+
+use super::api_client::{api_requests, ApiClient, ApiError};
+use crate::cli::args::Op;
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct Blobs {
+    #[command(subcommand)]
+    command: BlobsCommand,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum BlobsCommand {
+    /// Keep a blob regardless of the cache quota, pulling it if needed
+    Pin {
+        #[clap(value_parser)]
+        hash: Hash,
+    },
+    /// Let a pinned blob be evicted again
+    Unpin {
+        #[clap(value_parser)]
+        hash: Hash,
+    },
+    /// Evict cached blobs over the quota and reclaim their space
+    Gc,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BlobsError {
+    #[error("api error: {0}")]
+    Api(#[from] ApiError),
+    #[error("config error: {0}")]
+    Config(#[from] ConfigError),
+}
+
+#[derive(Debug)]
+pub enum BlobsOutput {
+    Pinned(Hash),
+    Unpinned(Hash),
+    Collected {
+        evicted: Vec<Hash>,
+        bytes_reclaimed: u64,
+    },
+}
+
+impl fmt::Display for BlobsOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlobsOutput::Pinned(hash) => write!(f, "pinned {}", hash),
+            BlobsOutput::Unpinned(hash) => write!(f, "unpinned {}", hash),
+            BlobsOutput::Collected {
+                evicted,
+                bytes_reclaimed,
+            } => {
+                writeln!(
+                    f,
+                    "evicted {} blobs, reclaimed {} bytes",
+                    evicted.len(),
+                    bytes_reclaimed
+                )?;
+                for hash in evicted {
+                    writeln!(f, "  {}", hash)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Op for Blobs {
+    type Error = BlobsError;
+    type Output = BlobsOutput;
+
+    async fn execute(&self) -> Result<Self::Output, Self::Error> {
+        let config = Config::from_env_or_disk()?;
+        let client = ApiClient::new(config.remote_url().as_ref())?;
+
+        let output = match &self.command {
+            BlobsCommand::Pin { hash } => {
+                let response = client.call(api_requests::Pin { hash: *hash }).await?;
+                BlobsOutput::Pinned(response.hash)
+            }
+            BlobsCommand::Unpin { hash } => {
+                let response = client.call(api_requests::Unpin { hash: *hash }).await?;
+                BlobsOutput::Unpinned(response.hash)
+            }
+            BlobsCommand::Gc => {
+                let response = client.call(api_requests::Gc {}).await?;
+                BlobsOutput::Collected {
+                    evicted: response.evicted,
+                    bytes_reclaimed: response.bytes_reclaimed,
+                }
+            }
+        };
+        Ok(output)
+    }
+}
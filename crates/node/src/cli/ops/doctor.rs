@@ -0,0 +1,154 @@
+use std::fmt;
+
+use async_trait::async_trait;
+use iroh::NodeId;
+
+use jax::config::{Config, ConfigError};
+use jax::node::{AddrSource, NatType};
+
+use super::api_client::api_requests::{DiscoveryRecord, PeerDiagnosticsResponse, SelfDiagnostics};
+use super::api_client::{api_requests, ApiClient, ApiError};
+use crate::cli::args::Op;
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct Doctor {
+    /// Also check whether, and how, we can reach this node
+    #[clap(value_parser)]
+    node_id: Option<NodeId>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DoctorError {
+    #[error("api error: {0}")]
+    Api(#[from] ApiError),
+    #[error("config error: {0}")]
+    Config(#[from] ConfigError),
+}
+
+#[derive(Debug)]
+pub struct DoctorOutput {
+    local: SelfDiagnostics,
+    peer: Option<Box<PeerDiagnosticsResponse>>,
+}
+
+fn write_discovery(
+    f: &mut fmt::Formatter<'_>,
+    label: &str,
+    record: &DiscoveryRecord,
+) -> fmt::Result {
+    if let Some(error) = &record.error {
+        return writeln!(f, "  {}: failed at {}: {}", label, record.at, error);
+    }
+    writeln!(
+        f,
+        "  {}: at {} via {}",
+        label,
+        record.at,
+        record.provenance.as_deref().unwrap_or("unknown")
+    )?;
+    writeln!(
+        f,
+        "    relay: {}",
+        record.relay_url.as_deref().unwrap_or("none")
+    )?;
+    for addr in &record.direct_addrs {
+        writeln!(f, "    addr: {}", addr)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for DoctorOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let local = &self.local;
+        writeln!(f, "Node {}", local.node_id)?;
+        for addr in &local.bound_addrs {
+            writeln!(f, "  bound: {}", addr)?;
+        }
+        for direct in &local.direct_addrs {
+            writeln!(f, "  direct: {} ({})", direct.addr, direct.source)?;
+        }
+        writeln!(
+            f,
+            "  relay: {}",
+            local.relay_url.as_deref().unwrap_or("none")
+        )?;
+        writeln!(f, "  nat: {}", local.nat)?;
+
+        let public = local
+            .direct_addrs
+            .iter()
+            .any(|direct| direct.source != AddrSource::Local);
+        if local.relay_url.is_none() && !public {
+            writeln!(
+                f,
+                "  ! no relay and no public address: peers outside this network can't reach us"
+            )?;
+        } else if local.relay_url.is_none() {
+            writeln!(
+                f,
+                "  ! no relay: peers that can't dial us directly can't reach us"
+            )?;
+        }
+        if local.nat == NatType::Hard {
+            writeln!(
+                f,
+                "  ! our NAT changes mappings per destination: most peers will only reach us through the relay"
+            )?;
+        }
+
+        let Some(peer) = &self.peer else {
+            return Ok(());
+        };
+        writeln!(f, "Peer {}", peer.node_id)?;
+        match &peer.error {
+            None => writeln!(f, "  reachable: yes")?,
+            Some(error) => writeln!(f, "  reachable: no ({})", error)?,
+        }
+        writeln!(f, "  path: {}", peer.path)?;
+        if let Some(addr) = peer.addr {
+            writeln!(f, "  addr: {}", addr)?;
+        }
+        if let Some(relay_url) = &peer.relay_url {
+            writeln!(f, "  relay: {}", relay_url)?;
+        }
+        if let Some(rtt_ms) = peer.rtt_ms {
+            writeln!(f, "  rtt: {:.1}ms", rtt_ms)?;
+        }
+        match &peer.discovery {
+            Some(record) => write_discovery(f, "discovery", record)?,
+            None => writeln!(f, "  discovery: not configured")?,
+        }
+        // Only worth showing when it tells us something this lookup didn't
+        if let Some(record) = &peer.last_resolved {
+            let failed = peer
+                .discovery
+                .as_ref()
+                .is_some_and(|discovery| discovery.error.is_some());
+            if failed {
+                write_discovery(f, "last resolved", record)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Op for Doctor {
+    type Error = DoctorError;
+    type Output = DoctorOutput;
+
+    async fn execute(&self) -> Result<Self::Output, Self::Error> {
+        let config = Config::from_env_or_disk()?;
+        let client = ApiClient::new(config.remote_url().as_ref())?;
+        let local = client.call(api_requests::Diagnostics {}).await?;
+        let peer = match self.node_id {
+            Some(node_id) => Some(Box::new(
+                client
+                    .call(api_requests::PeerDiagnostics { node_id })
+                    .await?,
+            )),
+            None => None,
+        };
+        Ok(DoctorOutput { local, peer })
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use alloy::primitives::{Address, U256};
+use async_trait::async_trait;
+use iroh_blobs::ticket::BlobTicket;
+
+use jax::config::{Config, ConfigError};
+
+use super::api_client::api_requests::{FetchPool, FetchProgress, FetchResponse};
+use super::api_client::{api_requests, ApiClient, ApiError};
+use super::progress::ProgressBar;
+use crate::cli::args::Op;
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct Fetch {
+    /// Ticket for a node that has the content
+    #[clap(value_parser)]
+    ticket: BlobTicket,
+    /// Join the pool for the content once it's in
+    #[clap(long, conflicts_with = "create_pool")]
+    join: bool,
+    /// The pool to join, when the content backs more than one
+    #[clap(long, requires = "join")]
+    pool: Option<Address>,
+    /// Create a pool for the content once it's in
+    #[clap(long)]
+    create_pool: bool,
+    /// Initial value for a created pool, in wei
+    #[clap(long, requires = "create_pool")]
+    value: Option<U256>,
+    /// Keep the content regardless of the cache quota
+    #[clap(long)]
+    pin: bool,
+    /// Don't report progress
+    #[clap(short, long)]
+    quiet: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    #[error("api error: {0}")]
+    Api(#[from] ApiError),
+    #[error("config error: {0}")]
+    Config(#[from] ConfigError),
+    #[error("invalid response: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("fetch failed: {0}")]
+    Failed(String),
+}
+
+#[derive(Debug)]
+pub struct FetchOutput {
+    response: FetchResponse,
+}
+
+impl fmt::Display for FetchOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let response = &self.response;
+        write!(f, "{}", response.message)?;
+        if response.elapsed_ms > 0 {
+            write!(f, " in {:.1}s", response.elapsed_ms as f64 / 1000.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Bytes seen so far across every blob in the download
+#[derive(Default)]
+struct Tally {
+    sizes: HashMap<u64, u64>,
+    offsets: HashMap<u64, u64>,
+    local: u64,
+}
+
+impl Tally {
+    fn update(&mut self, progress: &FetchProgress) {
+        match progress {
+            FetchProgress::FoundLocal { size, .. } => self.local += size,
+            FetchProgress::Found { id, size, .. } => {
+                self.sizes.insert(*id, *size);
+            }
+            FetchProgress::Progress { id, offset } => {
+                self.offsets.insert(*id, *offset);
+            }
+            FetchProgress::Done { id } => {
+                let size = self.sizes.get(id).copied().unwrap_or_default();
+                self.offsets.insert(*id, size);
+            }
+            _ => {}
+        }
+    }
+
+    fn report(&self, bar: &ProgressBar) {
+        let fetched = self.offsets.values().sum::<u64>();
+        let total = self.local + self.sizes.values().sum::<u64>();
+        bar.update(self.local + fetched, total, fetched);
+    }
+}
+
+#[async_trait]
+impl Op for Fetch {
+    type Error = FetchError;
+    type Output = FetchOutput;
+
+    async fn execute(&self) -> Result<Self::Output, Self::Error> {
+        let config = Config::from_env_or_disk()?;
+        let client = ApiClient::new(config.remote_url().as_ref())?;
+
+        let pool = if self.join {
+            FetchPool::Join
+        } else if self.create_pool {
+            FetchPool::Create
+        } else {
+            FetchPool::None
+        };
+        let request = api_requests::Fetch {
+            ticket: self.ticket.to_string(),
+            pool,
+            pool_address: self.pool,
+            initial_value: self.value,
+            pin: self.pin,
+            progress: !self.quiet,
+        };
+        if self.quiet {
+            let response = client.call(request).await?;
+            return Ok(FetchOutput { response });
+        }
+
+        let mut events = client.call_events(request).await?;
+        let mut tally = Tally::default();
+        let bar = ProgressBar::new();
+        while let Some(event) = events.next_event().await? {
+            match event.event.as_str() {
+                "progress" => {
+                    let progress = serde_json::from_str::<FetchProgress>(&event.data)?;
+                    tally.update(&progress);
+                    tally.report(&bar);
+                }
+                "done" => {
+                    bar.finish();
+                    let response = serde_json::from_str(&event.data)?;
+                    return Ok(FetchOutput { response });
+                }
+                "error" => {
+                    bar.finish();
+                    return Err(FetchError::Failed(event.data));
+                }
+                _ => {}
+            }
+        }
+        Err(FetchError::Failed(
+            "node closed the stream before finishing".to_string(),
+        ))
+    }
+}
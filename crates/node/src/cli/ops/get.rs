@@ -0,0 +1,366 @@
+use std::fmt;
+use std::future::Future;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use iroh_blobs::ticket::BlobTicket;
+use iroh_blobs::{BlobFormat, Hash};
+use reqwest::header::CONTENT_TYPE;
+use tokio::io::AsyncWriteExt;
+
+use jax::config::{Config, ConfigError};
+use jax::node::{ContentKey, Decryptor, EncryptedTicket, ExportLink};
+
+use super::api_client::api_requests::{FetchPool, PullResponse};
+use super::api_client::{api_requests, ApiClient, ApiError};
+use super::progress::ProgressBar;
+use crate::cli::args::Op;
+
+/// How often we ask the node how a download is going
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct Get {
+    /// A hash, or a ticket for a node that has the content. Tickets for
+    ///  encrypted content carry their key, and come out decrypted -- the
+    ///  node only ever hands us the ciphertext.
+    #[clap(value_parser)]
+    content: String,
+    /// Where to write it -- a collection comes out as a directory
+    #[clap(short, long)]
+    output: PathBuf,
+    /// Have the node write it instead, to `--output` within its
+    ///  configured export directory. Saves sending it through the API
+    ///  when we share a machine with the node.
+    #[clap(long)]
+    on_node: bool,
+    /// With `--on-node`, let the store hand over its own copy of the
+    ///  file rather than making another. Don't edit or move the result.
+    #[clap(long, requires = "on_node")]
+    reference: bool,
+    /// Don't report download progress
+    #[clap(short, long)]
+    quiet: bool,
+    /// Decrypt content shared with `--encrypt`, given by hash or plain
+    ///  ticket
+    #[clap(long)]
+    key: Option<ContentKey>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GetError {
+    #[error("api error: {0}")]
+    Api(#[from] ApiError),
+    #[error("config error: {0}")]
+    Config(#[from] ConfigError),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("not a hash or a ticket: {0}")]
+    InvalidContent(String),
+    #[error("refusing to write collection entry '{0}'")]
+    UnsafeName(String),
+    #[error("content hashed to {actual}, expected {expected}")]
+    Mismatch { expected: Hash, actual: Hash },
+}
+
+#[derive(Debug)]
+pub struct GetOutput {
+    hash: Hash,
+    format: BlobFormat,
+    path: PathBuf,
+    on_node: bool,
+}
+
+impl fmt::Display for GetOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.format {
+            BlobFormat::Raw => "blob",
+            BlobFormat::HashSeq => "collection",
+        };
+        write!(f, "wrote {} {} to {}", what, self.hash, self.path.display())?;
+        if self.on_node {
+            write!(f, " in the node's export directory")?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Op for Get {
+    type Error = GetError;
+    type Output = GetOutput;
+
+    async fn execute(&self) -> Result<Self::Output, Self::Error> {
+        let config = Config::from_env_or_disk()?;
+        let client = ApiClient::new(config.remote_url().as_ref())?;
+        let (hash, ticket, key) = self.parse()?;
+
+        if self.on_node {
+            let request = api_requests::Export {
+                content: self.content.clone(),
+                output: self.output.clone(),
+                link: if self.reference {
+                    ExportLink::Reference
+                } else {
+                    ExportLink::Copy
+                },
+                key: self.key.clone(),
+            };
+            let response = self.watch(&client, hash, client.call(request)).await?;
+            return Ok(GetOutput {
+                hash: response.hash,
+                format: response.format,
+                path: response.path,
+                on_node: true,
+            });
+        }
+
+        // the node goes to the ticket's node for it, or to its pools
+        //  on the first pull
+        if let Some(ticket) = ticket {
+            let fetch = api_requests::Fetch {
+                ticket: ticket.to_string(),
+                pool: FetchPool::None,
+                pool_address: None,
+                initial_value: None,
+                pin: false,
+                progress: false,
+            };
+            self.watch(&client, hash, client.call(fetch)).await?;
+        }
+        let pull = api_requests::Pull { hash };
+        let response = self.watch(&client, hash, client.call_stream(pull)).await?;
+
+        // a collection is described rather than streamed
+        let is_collection = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .is_some_and(|content_type| content_type.as_bytes().starts_with(b"application/json"));
+        let bar = (!self.quiet).then(ProgressBar::new);
+        let format = if is_collection {
+            let collection = response
+                .json::<PullResponse>()
+                .await
+                .map_err(ApiError::from)?;
+            let mut written = 0;
+            for entry in collection.entries {
+                // names come from whoever shared it -- stay under `output`
+                let relative = Path::new(&entry.name);
+                if !relative
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_)))
+                {
+                    return Err(GetError::UnsafeName(entry.name));
+                }
+                let pull = api_requests::Pull { hash: entry.hash };
+                let response = client.call_stream(pull).await?;
+                let path = self.output.join(relative);
+                written +=
+                    write_file(response, &path, entry.hash, None, bar.as_ref(), written).await?;
+            }
+            BlobFormat::HashSeq
+        } else {
+            write_file(response, &self.output, hash, key.as_ref(), bar.as_ref(), 0).await?;
+            BlobFormat::Raw
+        };
+        if let Some(bar) = bar {
+            bar.finish();
+        }
+
+        Ok(GetOutput {
+            hash,
+            format,
+            path: self.output.clone(),
+            on_node: false,
+        })
+    }
+}
+
+impl Get {
+    /// The hash the content is stored under, a ticket to fetch it with
+    ///  if we were given one, and the key to decrypt it with
+    fn parse(&self) -> Result<(Hash, Option<BlobTicket>, Option<ContentKey>), GetError> {
+        if let Ok(encrypted) = self.content.parse::<EncryptedTicket>() {
+            let hash = encrypted.ticket.hash();
+            return Ok((hash, Some(encrypted.ticket), Some(encrypted.key)));
+        }
+        if let Ok(ticket) = self.content.parse::<BlobTicket>() {
+            return Ok((ticket.hash(), Some(ticket), self.key.clone()));
+        }
+        let hash = self
+            .content
+            .parse::<Hash>()
+            .map_err(|_| GetError::InvalidContent(self.content.clone()))?;
+        Ok((hash, None, self.key.clone()))
+    }
+
+    /// Wait on a request that only answers once the node holds `hash`,
+    ///  watching the node's transfers for it in the meantime
+    async fn watch<T>(
+        &self,
+        client: &ApiClient,
+        hash: Hash,
+        request: impl Future<Output = Result<T, ApiError>>,
+    ) -> Result<T, ApiError> {
+        tokio::pin!(request);
+        let bar = ProgressBar::new();
+        let mut drawn = false;
+        let mut poll = tokio::time::interval(POLL_INTERVAL);
+        let result = loop {
+            tokio::select! {
+                result = &mut request => break result,
+                _ = poll.tick(), if !self.quiet => {
+                    // progress is best effort, the request is what matters
+                    let Ok(transfers) = client.call(api_requests::Transfers {}).await else {
+                        continue;
+                    };
+                    if let Some(transfer) = transfers
+                        .transfers
+                        .iter()
+                        .find(|transfer| transfer.hash == hash && transfer.state == "running")
+                    {
+                        bar.draw(
+                            transfer.bytes_done,
+                            transfer.bytes_total,
+                            transfer.rate,
+                            transfer.eta,
+                        );
+                        drawn = true;
+                    }
+                }
+            }
+        };
+        if drawn {
+            bar.finish();
+        }
+        result
+    }
+}
+
+/// Stream a pulled blob to `path`, hashing it on the way, and return how
+///  many bytes were pulled. With a `key` the blob is decrypted as it
+///  arrives, and it's the ciphertext that has to hash to `expected`. A
+///  file that doesn't hash to `expected`, doesn't decrypt or doesn't
+///  arrive whole is removed again. `offset` is what earlier files already
+///  put on the bar.
+async fn write_file(
+    mut response: reqwest::Response,
+    path: &Path,
+    expected: Hash,
+    key: Option<&ContentKey>,
+    bar: Option<&ProgressBar>,
+    offset: u64,
+) -> Result<u64, GetError> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let total = offset + response.content_length().unwrap_or_default();
+    let written = async {
+        let mut file = tokio::fs::File::create(path).await?;
+        let mut hasher = blake3::Hasher::new();
+        let mut decryptor = key.cloned().map(Decryptor::new);
+        let mut written = 0;
+        while let Some(chunk) = response.chunk().await.map_err(ApiError::from)? {
+            hasher.update(&chunk);
+            match &mut decryptor {
+                Some(decryptor) => {
+                    for plaintext in decryptor.update(&chunk)? {
+                        file.write_all(&plaintext).await?;
+                    }
+                }
+                None => file.write_all(&chunk).await?,
+            }
+            written += chunk.len() as u64;
+            if let Some(bar) = bar {
+                bar.update(offset + written, total, offset + written);
+            }
+        }
+        let actual = Hash::from(hasher.finalize());
+        if actual != expected {
+            return Err(GetError::Mismatch { expected, actual });
+        }
+        if let Some(decryptor) = decryptor {
+            file.write_all(&decryptor.finish()?).await?;
+        }
+        file.flush().await?;
+        Ok(written)
+    }
+    .await;
+    if written.is_err() {
+        let _ = tokio::fs::remove_file(path).await;
+    }
+    written
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(body: impl Into<reqwest::Body>) -> reqwest::Response {
+        reqwest::Response::from(http::Response::new(body.into()))
+    }
+
+    #[tokio::test]
+    async fn test_write_file() {
+        let dir = std::env::temp_dir().join(format!("jax-get-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("nested/hello.txt");
+
+        let written = write_file(
+            response(&b"hello"[..]),
+            &path,
+            Hash::new(b"hello"),
+            None,
+            None,
+            0,
+        )
+        .await
+        .unwrap();
+        assert_eq!(written, 5);
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"hello");
+
+        // whatever the node sent, it isn't what we asked for
+        let mismatch = write_file(
+            response(&b"hello"[..]),
+            &path,
+            Hash::new(b"goodbye"),
+            None,
+            None,
+            0,
+        )
+        .await;
+        assert!(matches!(mismatch, Err(GetError::Mismatch { .. })));
+        assert!(!path.exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_write_file_decrypted() {
+        let dir = std::env::temp_dir().join(format!("jax-get-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("secret.txt");
+        let key = ContentKey::generate();
+        let sealed = key.seal(0, true, b"hello").unwrap();
+        let hash = Hash::new(&sealed);
+
+        write_file(response(sealed.clone()), &path, hash, Some(&key), None, 0)
+            .await
+            .unwrap();
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"hello");
+
+        // the right ciphertext under the wrong key
+        let wrong = write_file(
+            response(sealed),
+            &path,
+            hash,
+            Some(&ContentKey::generate()),
+            None,
+            0,
+        )
+        .await;
+        assert!(matches!(wrong, Err(GetError::Io(_))));
+        assert!(!path.exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
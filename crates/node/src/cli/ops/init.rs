@@ -18,6 +18,9 @@ pub struct Init {
     pub iroh_port: Option<u16>,
     #[arg(short, long, default_value_t = false)]
     pub overwrite: bool,
+    /// Only find peers on the local network, and don't use relays
+    #[arg(long, default_value_t = false)]
+    pub offline: bool,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -40,6 +43,7 @@ impl Op for Init {
             self.eth_signer.clone(),
             self.http_port,
             self.iroh_port,
+            self.offline,
         )?;
         let config = Config::from_env_or_disk()?;
         let key = config.iroh_secret_key()?;
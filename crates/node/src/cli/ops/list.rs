@@ -9,7 +9,26 @@ use crate::cli::args::Op;
 use super::api_client::{api_requests, ApiClient, ApiError};
 
 #[derive(Debug, clap::Args, Clone)]
-pub struct List {}
+pub struct List {
+    /// Only blobs we hold all of
+    #[clap(long, conflicts_with = "partial")]
+    complete: bool,
+    /// Only blobs we hold part of
+    #[clap(long)]
+    partial: bool,
+    /// Only blobs that back a pool
+    #[clap(long, conflicts_with = "unpooled")]
+    pooled: bool,
+    /// Only blobs that don't back a pool
+    #[clap(long)]
+    unpooled: bool,
+    /// How many blobs to skip
+    #[clap(long, default_value_t = 0)]
+    offset: usize,
+    /// How many blobs to show
+    #[clap(long)]
+    limit: Option<usize>,
+}
 
 #[derive(Debug, thiserror::Error)]
 pub enum ListError {
@@ -21,19 +40,113 @@ pub enum ListError {
 
 #[derive(Debug)]
 pub struct ListOutput {
-    blobs: Vec<String>,
+    response: api_requests::ListResponse,
+}
+
+const HEADERS: [&str; 6] = ["HASH", "SIZE", "STATUS", "TAGS", "POOLS", "CREATED"];
+
+pub(super) fn format_size(size: Option<u64>) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let Some(size) = size else {
+        return "-".to_string();
+    };
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", size, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn format_created(created: Option<u64>) -> String {
+    created
+        .and_then(|secs| time::OffsetDateTime::from_unix_timestamp(secs as i64).ok())
+        .map(|at| format!("{} {:02}:{:02}", at.date(), at.hour(), at.minute()))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn format_row(blob: &api_requests::BlobInfo) -> [String; 6] {
+    let mut tags = blob
+        .tags
+        .iter()
+        .map(|tag| tag.kind.replace('_', "-"))
+        .collect::<Vec<_>>();
+    tags.dedup();
+    let pools = blob
+        .pools
+        .iter()
+        .map(|pool| match pool.rank {
+            Some(rank) => format!("{} (#{}/{})", pool.address, rank, pool.peers),
+            None => format!("{} (-/{})", pool.address, pool.peers),
+        })
+        .collect::<Vec<_>>();
+    let hash = if blob.pinned {
+        format!("{}*", blob.hash)
+    } else {
+        blob.hash.to_string()
+    };
+    [
+        hash,
+        format_size(blob.size),
+        if blob.complete { "complete" } else { "partial" }.to_string(),
+        if tags.is_empty() {
+            "-".to_string()
+        } else {
+            tags.join(",")
+        },
+        if pools.is_empty() {
+            "-".to_string()
+        } else {
+            pools.join(", ")
+        },
+        format_created(blob.created),
+    ]
 }
 
 impl fmt::Display for ListOutput {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Stored blobs:")?;
-        if self.blobs.is_empty() {
-            writeln!(f, "  No blobs stored")?;
-        } else {
-            for hash in &self.blobs {
-                writeln!(f, "  {}", hash)?;
+        let response = &self.response;
+        if response.blobs.is_empty() {
+            return writeln!(f, "No blobs stored");
+        }
+
+        let rows = response.blobs.iter().map(format_row).collect::<Vec<_>>();
+        let mut widths = HEADERS.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
             }
         }
+        let mut write_row = |cells: &[&str]| -> fmt::Result {
+            let line = cells
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ");
+            writeln!(f, "{}", line.trim_end())
+        };
+        write_row(&HEADERS)?;
+        for row in &rows {
+            write_row(&row.each_ref().map(String::as_str))?;
+        }
+
+        writeln!(
+            f,
+            "\nShowing {}-{} of {} (* pinned)",
+            response.offset + 1,
+            response.offset + response.blobs.len(),
+            response.total
+        )?;
+        let next = response.offset + response.blobs.len();
+        if next < response.total {
+            writeln!(f, "Next page: --offset {}", next)?;
+        }
         Ok(())
     }
 }
@@ -47,11 +160,28 @@ impl Op for List {
         let config = Config::from_env_or_disk()?;
         let client = ApiClient::new(config.remote_url().as_ref())?;
 
-        let request = api_requests::List;
+        let status = if self.complete {
+            Some("complete".to_string())
+        } else if self.partial {
+            Some("partial".to_string())
+        } else {
+            None
+        };
+        let pooled = if self.pooled {
+            Some(true)
+        } else if self.unpooled {
+            Some(false)
+        } else {
+            None
+        };
+        let request = api_requests::List {
+            offset: self.offset,
+            limit: self.limit,
+            status,
+            pooled,
+        };
         let response = client.call(request).await?;
 
-        Ok(ListOutput {
-            blobs: response.blobs.into_iter().map(|b| b.hash).collect(),
-        })
+        Ok(ListOutput { response })
     }
 }
@@ -1,19 +1,38 @@
 // mod hello;
+mod acl;
 mod api_client;
+mod blobs;
+mod doctor;
+mod fetch;
+mod get;
 mod init;
 mod list;
 mod node;
+mod peer;
 mod pools;
 mod probe;
+mod progress;
 mod query;
+mod rm;
 mod share;
+mod stats;
 mod status;
+mod transfers;
+pub use acl::Acl;
 pub use api_client::{ApiClient, ApiError};
+pub use blobs::Blobs;
+pub use doctor::Doctor;
+pub use fetch::Fetch;
+pub use get::Get;
 pub use init::Init;
 pub use list::{List, ListError, ListOutput};
 pub use node::Node;
+pub use peer::Peer;
 pub use pools::{Pools, PoolsError, PoolsOutput};
 pub use probe::Probe;
 pub use query::{Query, QueryError, QueryOutput};
+pub use rm::Rm;
 pub use share::{Share, ShareError};
+pub use stats::Stats;
 pub use status::Status;
+pub use transfers::Transfers;
@@ -0,0 +1,97 @@
+use std::fmt;
+
+use alloy::primitives::Address;
+use async_trait::async_trait;
+use iroh::NodeId;
+
+use jax::config::{Config, ConfigError};
+
+use super::api_client::api_requests::PeerResponse;
+use super::api_client::{api_requests, ApiClient, ApiError};
+use crate::cli::args::Op;
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct Peer {
+    /// The node to ask
+    #[clap(value_parser)]
+    node_id: NodeId,
+    /// Also show the node's trust in the peers of this pool
+    #[clap(long)]
+    pool: Option<Address>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PeerError {
+    #[error("api error: {0}")]
+    Api(#[from] ApiError),
+    #[error("config error: {0}")]
+    Config(#[from] ConfigError),
+}
+
+#[derive(Debug)]
+pub struct PeerOutput {
+    pool: Option<Address>,
+    response: PeerResponse,
+}
+
+impl fmt::Display for PeerOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let identity = &self.response.identity;
+        writeln!(f, "Peer {}", identity.node_id)?;
+        writeln!(
+            f,
+            "  beneficiary: {} ({})",
+            identity.beneficiary,
+            if self.response.verified {
+                "verified"
+            } else {
+                "SIGNATURE INVALID"
+            }
+        )?;
+        if self.response.pools.is_empty() {
+            writeln!(f, "  No pools")?;
+        }
+        for pool in &self.response.pools {
+            writeln!(
+                f,
+                "  pool {}: {} ({:?})",
+                pool.address, pool.hash, pool.format
+            )?;
+        }
+        if let Some(pool) = self.pool {
+            match &self.response.trust_row {
+                Some(row) => {
+                    writeln!(f, "  trust in {}:", pool)?;
+                    for (node, trust) in row {
+                        writeln!(f, "    {} (trust: {:.3})", node, trust)?;
+                    }
+                }
+                None => writeln!(f, "  not tracking {}", pool)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Op for Peer {
+    type Error = PeerError;
+    type Output = PeerOutput;
+
+    async fn execute(&self) -> Result<Self::Output, Self::Error> {
+        let config = Config::from_env_or_disk()?;
+        let client = ApiClient::new(config.remote_url().as_ref())?;
+
+        let request = api_requests::Peer {
+            node_id: self.node_id,
+            pool: self.pool,
+        };
+
+        let response = client.call(request).await?;
+
+        Ok(PeerOutput {
+            pool: self.pool,
+            response,
+        })
+    }
+}
@@ -1,9 +1,6 @@
 use std::fmt;
 
-use alloy::primitives::Address;
 use async_trait::async_trait;
-use iroh::NodeId;
-use iroh_blobs::Hash;
 
 use jax::config::{Config, ConfigError};
 
@@ -23,7 +20,7 @@ pub enum PoolsError {
 
 #[derive(Debug)]
 pub struct PoolsOutput {
-    pools: Vec<(Address, Hash, Vec<(NodeId, f64)>)>,
+    pools: Vec<api_requests::PoolEntry>,
 }
 
 impl fmt::Display for PoolsOutput {
@@ -32,8 +29,12 @@ impl fmt::Display for PoolsOutput {
         if self.pools.is_empty() {
             writeln!(f, "  No pools found")?;
         } else {
-            for (address, hash, peers) in &self.pools {
-                writeln!(f, "  Pool {} for blob {}:", address, hash)?;
+            for (address, hash, balance, peers) in &self.pools {
+                writeln!(
+                    f,
+                    "  Pool {} for blob {} (balance: {} wei):",
+                    address, hash, balance
+                )?;
                 for (node, trust) in peers {
                     writeln!(f, "    {} (trust: {:.3})", node, trust)?;
                 }
@@ -0,0 +1,110 @@
+use std::fmt;
+use std::io::Write;
+use std::time::Instant;
+
+use super::list::format_size;
+
+/// Width of the bar itself, in characters
+const BAR_WIDTH: usize = 30;
+
+/// A one-line progress bar, redrawn in place on stderr
+pub struct ProgressBar {
+    started: Instant,
+}
+
+impl ProgressBar {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+        }
+    }
+
+    /// Redraw from byte counts alone, measuring the rate ourselves.
+    ///  `fetched` is the part of `done` that actually came over the
+    ///  wire, so content we already had doesn't inflate the rate.
+    pub fn update(&self, done: u64, total: u64, fetched: u64) {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let rate = if elapsed > 0.0 {
+            fetched as f64 / elapsed
+        } else {
+            0.0
+        };
+        let eta = (rate > 0.0).then(|| (total.saturating_sub(done) as f64 / rate).ceil() as u64);
+        self.draw(done, total, rate, eta);
+    }
+
+    /// Redraw with figures the node worked out
+    pub fn draw(&self, done: u64, total: u64, rate: f64, eta: Option<u64>) {
+        write(format_args!("\r{}\x1b[K", render(done, total, rate, eta)));
+    }
+
+    /// Print a line where the bar is -- the next redraw puts the bar
+    ///  back underneath it
+    pub fn note(&self, message: impl fmt::Display) {
+        write(format_args!("\r{}\x1b[K\n", message));
+    }
+
+    /// Leave the bar as it is and move past it
+    pub fn finish(&self) {
+        write(format_args!("\n"));
+    }
+}
+
+/// Everything goes to stderr, so it never mixes with the output proper
+fn write(args: fmt::Arguments<'_>) {
+    let mut stderr = std::io::stderr().lock();
+    let _ = stderr.write_fmt(args);
+    let _ = stderr.flush();
+}
+
+fn render(done: u64, total: u64, rate: f64, eta: Option<u64>) -> String {
+    // until we know the size there's nothing to fill the bar against
+    let (filled, percent) = if total > 0 {
+        let fraction = (done as f64 / total as f64).min(1.0);
+        (
+            (fraction * BAR_WIDTH as f64) as usize,
+            format!("{:>3}%", (fraction * 100.0) as u64),
+        )
+    } else {
+        (0, "  -%".to_string())
+    };
+    let mut line = format!(
+        "[{}{}] {} {} / {}  {}/s",
+        "#".repeat(filled),
+        "-".repeat(BAR_WIDTH - filled),
+        percent,
+        format_size(Some(done)),
+        format_size(Some(total)),
+        format_size(Some(rate as u64)),
+    );
+    if let Some(eta) = eta {
+        line.push_str(&format!("  ETA {}", format_eta(eta)));
+    }
+    line
+}
+
+fn format_eta(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let line = render(512, 1024, 256.0, Some(2));
+        assert!(line.starts_with(&format!("[{}{}]", "#".repeat(15), "-".repeat(15))));
+        assert!(line.contains(" 50% 512 B / 1.0 KiB  256 B/s  ETA 2s"));
+
+        let line = render(0, 0, 0.0, None);
+        assert!(line.contains("  -%"));
+        assert!(!line.contains("ETA"));
+
+        assert_eq!(format_eta(3725), "1h02m");
+    }
+}
@@ -0,0 +1,71 @@
+use std::fmt;
+
+use alloy::primitives::Address;
+use async_trait::async_trait;
+use iroh_blobs::Hash;
+
+use jax::config::{Config, ConfigError};
+
+use super::api_client::{api_requests, ApiClient, ApiError};
+use crate::cli::args::Op;
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct Rm {
+    /// The hash to remove
+    #[clap(value_parser)]
+    hash: Hash,
+    /// Leave any pool the blob backs instead of refusing
+    #[clap(long)]
+    force: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RmError {
+    #[error("api error: {0}")]
+    Api(#[from] ApiError),
+    #[error("config error: {0}")]
+    Config(#[from] ConfigError),
+}
+
+#[derive(Debug)]
+pub struct RmOutput {
+    hash: Hash,
+    left_pools: Vec<Address>,
+    bytes_reclaimed: u64,
+}
+
+impl fmt::Display for RmOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for address in &self.left_pools {
+            writeln!(f, "left pool {}", address)?;
+        }
+        write!(
+            f,
+            "removed {}, reclaimed {} bytes",
+            self.hash, self.bytes_reclaimed
+        )
+    }
+}
+
+#[async_trait]
+impl Op for Rm {
+    type Error = RmError;
+    type Output = RmOutput;
+
+    async fn execute(&self) -> Result<Self::Output, Self::Error> {
+        let config = Config::from_env_or_disk()?;
+        let client = ApiClient::new(config.remote_url().as_ref())?;
+
+        let request = api_requests::DeleteBlob {
+            hash: self.hash,
+            force: self.force,
+        };
+        let response = client.call(request).await?;
+
+        Ok(RmOutput {
+            hash: response.hash,
+            left_pools: response.left_pools,
+            bytes_reclaimed: response.bytes_reclaimed,
+        })
+    }
+}
@@ -1,22 +1,49 @@
 use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
 use iroh::NodeId;
 use iroh_blobs::Hash;
-use std::path::PathBuf;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 
 use jax::config::{Config, ConfigError};
+use jax::node::{
+    ciphertext_size, plaintext_chunk, ContentKey, EncryptedTicket, ImportMode, SEALED_CHUNK_SIZE,
+};
 
+use super::api_client::api_requests::ShareResponse;
 use super::api_client::{api_requests, ApiClient, ApiError};
+use super::progress::ProgressBar;
 use crate::cli::args::Op as AppOp;
 
+/// Files at least this big are sent over a resumable upload rather
+///  than imported by path
+const UPLOAD_THRESHOLD: u64 = 64 * 1024 * 1024;
+/// Bytes sent per PATCH
+const UPLOAD_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+/// How many times in a row a chunk can fail before we give up
+const UPLOAD_MAX_RETRIES: usize = 5;
+
 #[derive(Debug, clap::Args, Clone)]
 pub struct Share {
-    /// Path to the file to share
+    /// Path to the file or directory to share
     #[arg(short, long)]
     pub path: PathBuf,
 
     /// Create a pool for this content
     #[arg(short, long)]
     pub create_pool: bool,
+
+    /// Reference the file where it is instead of copying it into the
+    ///  node's store. The file must not change afterwards.
+    #[arg(long)]
+    pub in_place: bool,
+
+    /// Encrypt the file with a fresh key as it's uploaded. Nodes, our
+    ///  own included, only ever see the ciphertext; the key is in the
+    ///  ticket.
+    #[arg(long, conflicts_with = "in_place")]
+    pub encrypt: bool,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -27,6 +54,10 @@ pub enum ShareError {
     Config(#[from] ConfigError),
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("only files can be encrypted")]
+    Unencryptable,
+    #[error("node answered with an invalid ticket: {0}")]
+    InvalidTicket(iroh_base::ticket::Error),
 }
 
 #[async_trait]
@@ -41,24 +72,56 @@ impl AppOp for Share {
         // Get absolute path for the file
         let abs_path = std::path::absolute(&self.path)?;
 
-        // Create share request
-        let request = api_requests::Share {
-            path: abs_path.to_string_lossy().to_string(),
-        };
+        // Large files go over a resumable upload, so a dropped connection
+        //  doesn't mean starting over. In place imports only make sense
+        //  by path. Encrypted files are always uploaded, sealed as they
+        //  go, so the key never leaves this machine.
+        let metadata = tokio::fs::metadata(&abs_path).await?;
+        let key = self.encrypt.then(ContentKey::generate);
+        if key.is_some() && !metadata.is_file() {
+            return Err(ShareError::Unencryptable);
+        }
+        let response = if key.is_some()
+            || (metadata.is_file() && !self.in_place && metadata.len() >= UPLOAD_THRESHOLD)
+        {
+            upload_file(&client, &abs_path, metadata.len(), key.as_ref()).await?
+        } else {
+            // Create share request
+            let request = api_requests::Share {
+                path: abs_path.to_string_lossy().to_string(),
+                mode: if self.in_place {
+                    ImportMode::InPlace
+                } else {
+                    ImportMode::Copy
+                },
+            };
 
-        // Call the API endpoint to share the file
-        let response = client.call(request).await?;
+            // Call the API endpoint to share the file
+            client.call(request).await?
+        };
 
+        let ticket = match &key {
+            Some(key) => EncryptedTicket {
+                ticket: response.ticket.parse().map_err(ShareError::InvalidTicket)?,
+                key: key.clone(),
+            }
+            .to_string(),
+            None => response.ticket.clone(),
+        };
         let mut output = format!(
             "{}\nShare ticket: {}\nHash: {}",
-            response.message, response.ticket, response.hash
+            response.message, ticket, response.hash
         );
+        if let Some(key) = &key {
+            output.push_str(&format!("\nKey: {}", key));
+        }
 
         // If create_pool flag is set, create a pool for this content
         if self.create_pool {
             // Create pool request
             let create_pool_request = api_requests::CreatePool {
                 hash: response.hash.clone(),
+                format: response.format,
             };
 
             // Call the API endpoint to create a pool
@@ -70,3 +133,147 @@ impl AppOp for Share {
         Ok(output)
     }
 }
+
+/// Send a file over a resumable upload session, picking up from the
+///  node's reported offset whenever a chunk fails. With a `key` the
+///  file is sealed as it goes, and the node only sees the ciphertext.
+async fn upload_file(
+    client: &ApiClient,
+    path: &Path,
+    file_size: u64,
+    key: Option<&ContentKey>,
+) -> Result<ShareResponse, ShareError> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let length = match key {
+        Some(_) => ciphertext_size(file_size),
+        None => file_size,
+    };
+    let mut info = client.call(api_requests::CreateUpload { length }).await?;
+
+    let bar = ProgressBar::new();
+    let mut sent = 0;
+    let mut retries = 0;
+    while info.offset < info.length {
+        let len = UPLOAD_CHUNK_SIZE.min(info.length - info.offset);
+        let data = match key {
+            Some(key) => read_sealed(&mut file, file_size, key, info.offset, len).await?,
+            None => read_at(&mut file, info.offset, len).await?,
+        };
+
+        let patch = api_requests::PatchUpload {
+            id: info.id,
+            offset: info.offset,
+            data,
+        };
+        match client.call(patch).await {
+            Ok(next) => {
+                sent += len;
+                info = next;
+                retries = 0;
+                bar.update(info.offset, info.length, sent);
+            }
+            Err(e) if retries < UPLOAD_MAX_RETRIES => {
+                retries += 1;
+                bar.note(format!(
+                    "upload chunk failed, resuming ({}): {}",
+                    retries, e
+                ));
+                // whatever made it before the failure is kept on the node
+                if let Ok(next) = client
+                    .call(api_requests::UploadStatus { id: info.id })
+                    .await
+                {
+                    info = next;
+                }
+            }
+            Err(e) => {
+                bar.finish();
+                return Err(e.into());
+            }
+        }
+    }
+    bar.finish();
+
+    Ok(client
+        .call(api_requests::FinalizeUpload { id: info.id })
+        .await?)
+}
+
+/// `len` bytes of the file from `offset`
+async fn read_at(file: &mut tokio::fs::File, offset: u64, len: u64) -> std::io::Result<Bytes> {
+    let mut data = BytesMut::zeroed(len as usize);
+    file.seek(SeekFrom::Start(offset)).await?;
+    file.read_exact(&mut data).await?;
+    Ok(data.freeze())
+}
+
+/// `len` bytes from `offset` of the file's ciphertext under `key`.
+///  Sealing is deterministic, so a resumed upload can reseal just the
+///  chunks it still needs.
+async fn read_sealed(
+    file: &mut tokio::fs::File,
+    file_size: u64,
+    key: &ContentKey,
+    offset: u64,
+    len: u64,
+) -> std::io::Result<Bytes> {
+    let first = offset / SEALED_CHUNK_SIZE;
+    let last = (offset + len).div_ceil(SEALED_CHUNK_SIZE);
+    let mut sealed = BytesMut::new();
+    for index in first..last {
+        let (chunk_offset, chunk_len, is_last) = plaintext_chunk(index, file_size);
+        let chunk = read_at(file, chunk_offset, chunk_len).await?;
+        sealed.extend_from_slice(&key.seal(index, is_last, &chunk)?);
+    }
+    let start = (offset - first * SEALED_CHUNK_SIZE) as usize;
+    Ok(sealed.freeze().slice(start..start + len as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use jax::node::encrypt_stream;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_sealed() {
+        let path = std::env::temp_dir().join(format!("jax-share-{}", uuid::Uuid::new_v4()));
+        let data = (0..3 * 64 * 1024 + 100)
+            .map(|i| i as u8)
+            .collect::<Vec<_>>();
+        tokio::fs::write(&path, &data).await.unwrap();
+        let key = ContentKey::generate();
+
+        let parts = futures::stream::iter([Ok(Bytes::from(data.clone()))]);
+        let mut stream = encrypt_stream(key.clone(), parts);
+        let mut expected = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            expected.extend_from_slice(&chunk.unwrap());
+        }
+        let size = ciphertext_size(data.len() as u64);
+        assert_eq!(expected.len() as u64, size);
+
+        // windows that start and end mid chunk, as a resumed upload's do
+        let mut file = tokio::fs::File::open(&path).await.unwrap();
+        let mut offset = 0;
+        for len in [1, 70_000, SEALED_CHUNK_SIZE, 5] {
+            let sealed = read_sealed(&mut file, data.len() as u64, &key, offset, len)
+                .await
+                .unwrap();
+            assert_eq!(
+                sealed,
+                expected[offset as usize..(offset + len) as usize],
+                "window at {}",
+                offset
+            );
+            offset += len;
+        }
+        let rest = read_sealed(&mut file, data.len() as u64, &key, offset, size - offset)
+            .await
+            .unwrap();
+        assert_eq!(rest, expected[offset as usize..]);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,90 @@
+use std::fmt;
+
+use async_trait::async_trait;
+
+use jax::config::{Config, ConfigError};
+
+use super::api_client::api_requests::{Counters, StatsResponse};
+use super::api_client::{api_requests, ApiClient, ApiError};
+use super::list::format_size;
+use crate::cli::args::Op;
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct Stats {
+    /// Only show this many of the busiest peers and pools
+    #[clap(short, long, default_value_t = 20)]
+    limit: usize,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StatsError {
+    #[error("api error: {0}")]
+    Api(#[from] ApiError),
+    #[error("config error: {0}")]
+    Config(#[from] ConfigError),
+}
+
+#[derive(Debug)]
+pub struct StatsOutput {
+    limit: usize,
+    response: StatsResponse,
+}
+
+fn write_counters(f: &mut fmt::Formatter<'_>, counters: &Counters) -> fmt::Result {
+    writeln!(
+        f,
+        "  served:  {} in {} requests ({} failed)",
+        format_size(Some(counters.served_bytes)),
+        counters.served_requests,
+        counters.serve_failures
+    )?;
+    writeln!(
+        f,
+        "  fetched: {} in {} requests ({} failed)",
+        format_size(Some(counters.fetched_bytes)),
+        counters.fetch_requests,
+        counters.fetch_failures
+    )
+}
+
+impl fmt::Display for StatsOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Total")?;
+        write_counters(f, &self.response.totals)?;
+        for pool in self.response.pools.iter().take(self.limit) {
+            let addresses = pool
+                .addresses
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            let label = if addresses.len() == 1 {
+                "Pool"
+            } else {
+                "Pools"
+            };
+            writeln!(f, "{} {} ({})", label, addresses.join(", "), pool.hash)?;
+            write_counters(f, &pool.counters)?;
+        }
+        for peer in self.response.peers.iter().take(self.limit) {
+            writeln!(f, "Peer {}", peer.node_id)?;
+            write_counters(f, &peer.counters)?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Op for Stats {
+    type Error = StatsError;
+    type Output = StatsOutput;
+
+    async fn execute(&self) -> Result<Self::Output, Self::Error> {
+        let config = Config::from_env_or_disk()?;
+        let client = ApiClient::new(config.remote_url().as_ref())?;
+        let response = client.call(api_requests::Stats {}).await?;
+        Ok(StatsOutput {
+            limit: self.limit,
+            response,
+        })
+    }
+}
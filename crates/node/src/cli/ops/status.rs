@@ -1,3 +1,5 @@
+use std::net::SocketAddr;
+
 use alloy::primitives::Address;
 use async_trait::async_trait;
 
@@ -26,6 +28,8 @@ pub enum StatusError {
 pub struct StatusOutput {
     node_id: NodeId,
     eth_address: Address,
+    bound_addrs: Vec<SocketAddr>,
+    direct_addrs: Vec<SocketAddr>,
 }
 
 impl std::fmt::Display for StatusOutput {
@@ -34,7 +38,14 @@ impl std::fmt::Display for StatusOutput {
             f,
             "Server Status:\n- Node ID: {}\n- ETH Address: {}",
             self.node_id, self.eth_address
-        )
+        )?;
+        for addr in &self.bound_addrs {
+            write!(f, "\n- Bound: {}", addr)?;
+        }
+        for addr in &self.direct_addrs {
+            write!(f, "\n- Direct: {}", addr)?;
+        }
+        Ok(())
     }
 }
 
@@ -53,6 +64,8 @@ impl Op for Status {
         Ok(StatusOutput {
             node_id: readiness.node_id,
             eth_address: readiness.eth_address,
+            bound_addrs: readiness.bound_addrs,
+            direct_addrs: readiness.direct_addrs,
         })
     }
 }
@@ -0,0 +1,116 @@
+use std::fmt;
+
+use async_trait::async_trait;
+use clap::Subcommand;
+use uuid::Uuid;
+
+use jax::config::{Config, ConfigError};
+
+use super::api_client::api_requests::TransferInfo;
+use super::api_client::{api_requests, ApiClient, ApiError};
+use super::list::format_size;
+use crate::cli::args::Op;
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct Transfers {
+    #[command(subcommand)]
+    command: Option<TransfersCommand>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum TransfersCommand {
+    /// Show running and recently finished downloads (the default)
+    List,
+    /// Stop a running download
+    Cancel {
+        #[clap(value_parser)]
+        id: Uuid,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransfersError {
+    #[error("api error: {0}")]
+    Api(#[from] ApiError),
+    #[error("config error: {0}")]
+    Config(#[from] ConfigError),
+}
+
+#[derive(Debug)]
+pub enum TransfersOutput {
+    Listed(Vec<TransferInfo>),
+    Cancelled(Uuid),
+}
+
+fn format_progress(transfer: &TransferInfo) -> String {
+    let mut progress = format!(
+        "{} / {}",
+        format_size(Some(transfer.bytes_done)),
+        format_size(Some(transfer.bytes_total))
+    );
+    if transfer.state == "running" {
+        progress.push_str(&format!(
+            " at {}/s",
+            format_size(Some(transfer.rate as u64))
+        ));
+        if let Some(eta) = transfer.eta {
+            progress.push_str(&format!(", {}s left", eta));
+        }
+    }
+    progress
+}
+
+impl fmt::Display for TransfersOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransfersOutput::Cancelled(id) => write!(f, "cancelled {}", id),
+            TransfersOutput::Listed(transfers) if transfers.is_empty() => {
+                write!(f, "No transfers")
+            }
+            TransfersOutput::Listed(transfers) => {
+                for transfer in transfers {
+                    writeln!(f, "{}  {}", transfer.id, transfer.state)?;
+                    writeln!(f, "  hash:     {}", transfer.hash)?;
+                    writeln!(f, "  progress: {}", format_progress(transfer))?;
+                    writeln!(
+                        f,
+                        "  chunks:   {} / {}",
+                        transfer.chunks_done, transfer.chunks_total
+                    )?;
+                    for source in &transfer.sources {
+                        writeln!(f, "  from:     {}", source)?;
+                    }
+                    if let Some(reason) = &transfer.reason {
+                        writeln!(f, "  reason:   {}", reason)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Op for Transfers {
+    type Error = TransfersError;
+    type Output = TransfersOutput;
+
+    async fn execute(&self) -> Result<Self::Output, Self::Error> {
+        let config = Config::from_env_or_disk()?;
+        let client = ApiClient::new(config.remote_url().as_ref())?;
+
+        let output = match &self.command {
+            None | Some(TransfersCommand::List) => {
+                let response = client.call(api_requests::Transfers {}).await?;
+                TransfersOutput::Listed(response.transfers)
+            }
+            Some(TransfersCommand::Cancel { id }) => {
+                let response = client
+                    .call(api_requests::CancelTransfer { id: *id })
+                    .await?;
+                TransfersOutput::Cancelled(response.id)
+            }
+        };
+        Ok(output)
+    }
+}
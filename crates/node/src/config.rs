@@ -1,11 +1,11 @@
 use std::env;
-use std::net::SocketAddr;
+use std::net::{SocketAddr, SocketAddrV6};
 use std::path::PathBuf;
 use std::str::FromStr;
 
 use alloy::{primitives::Address, signers::local::PrivateKeySigner};
 use dotenvy::dotenv;
-use iroh::SecretKey;
+use iroh::{NodeId, SecretKey};
 use iroh_blobs::store::fs::{BatchOptions, InlineOptions, Options, PathOptions, Store};
 use rand_core::OsRng;
 use serde::{Deserialize, Serialize};
@@ -16,6 +16,83 @@ pub const DEFAULT_XDG_CONFIG_DIR: &str = ".config";
 pub const DEFAULT_XDG_CONFIG_DIR_NAME: &str = "jax";
 pub const DEFAULT_CONFIG_NAME: &str = "jax.conf";
 pub const CONFIG_PATH_ENV: &str = "JAX_CONFIG_PATH";
+pub const DEFAULT_TRACKER_STATE_NAME: &str = "tracker.json";
+pub const DEFAULT_CACHE_QUOTA: u64 = 10 * 1024 * 1024 * 1024;
+
+/// How an endpoint finds the addresses of nodes it only knows by id
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DiscoveryMethod {
+    /// Publish to and resolve from the mainline DHT
+    Dht,
+    /// Publish to and resolve from a pkarr relay -- n0's if no url is given
+    Pkarr {
+        #[serde(default)]
+        url: Option<Url>,
+    },
+    /// Announce ourselves to, and listen for, nodes on the local network
+    Local,
+    /// A fixed address book
+    Static { peers: Vec<StaticPeer> },
+}
+
+/// A peer we know how to reach without asking anyone
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaticPeer {
+    pub node_id: NodeId,
+    #[serde(default)]
+    pub addrs: Vec<SocketAddr>,
+    #[serde(default)]
+    pub relay_url: Option<Url>,
+}
+
+/// Which relay servers an endpoint falls back on when it can't dial direct
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RelayConfig {
+    /// n0's public relays
+    #[default]
+    Default,
+    Custom {
+        url: Url,
+    },
+    /// Direct connections only
+    Disabled,
+}
+
+/// Limits on what we serve to other nodes over the blobs protocol. Peers
+///  fetching the content of a pool we've both joined aren't held to the
+///  per-peer limits, and get a share of the global ones no one else can
+///  use.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServeConfig {
+    /// Bytes per second to all peers together
+    #[serde(default)]
+    pub global_bytes_per_sec: Option<u64>,
+    /// Bytes per second to any one peer
+    #[serde(default)]
+    pub peer_bytes_per_sec: Option<u64>,
+    /// Blob requests per minute from all peers together. A request for
+    ///  a hash seq counts once, however many blobs come with it.
+    #[serde(default)]
+    pub global_requests_per_min: Option<u32>,
+    /// Blob requests per minute from any one peer
+    #[serde(default)]
+    pub peer_requests_per_min: Option<u32>,
+    #[serde(default)]
+    pub policy: ServePolicy,
+}
+
+/// Which content we hand out at all
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServePolicy {
+    /// Anything in the store
+    #[default]
+    All,
+    /// Only content backing a pool we joined, or that we shared ourselves
+    PooledOrPublic,
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct BlobsOptions {
@@ -45,7 +122,12 @@ impl BlobsOptions {
 #[derive(Serialize, Deserialize)]
 pub struct OnDiskConfig {
     pub remote_listen_addr: SocketAddr,
+    // either family -- we bind the other on the same port as well, as a
+    //  wildcard if this is one and on loopback otherwise
     pub endpoint_listen_addr: SocketAddr,
+    // where to bind IPv6, if not alongside endpoint_listen_addr
+    #[serde(default)]
+    pub endpoint_listen_addr_v6: Option<SocketAddrV6>,
     pub blobs_path: PathBuf,
     pub blobs_option: BlobsOptions,
     pub iroh_key_file_path: PathBuf,
@@ -53,6 +135,35 @@ pub struct OnDiskConfig {
     pub eth_ws_rpc_url: Url,
     // NOTE (amiller68): these are optional since we don't have good defaults
     pub factory_contract_address: Option<Address>,
+    // relative to xdg config dir -- defaulted so older configs still load
+    #[serde(default = "default_tracker_state_path")]
+    pub tracker_state_path: PathBuf,
+    // bytes of pulled content we cache before evicting the least recently used
+    #[serde(default = "default_cache_quota")]
+    pub cache_quota: u64,
+    // how our endpoints find peers -- empty means only dial addresses we're given
+    #[serde(default = "default_discovery")]
+    pub discovery: Vec<DiscoveryMethod>,
+    #[serde(default)]
+    pub relay: RelayConfig,
+    #[serde(default)]
+    pub serve: ServeConfig,
+    // where the node may write exported content, relative to the xdg
+    //  config dir -- unset means it writes nothing outside its own store
+    #[serde(default)]
+    pub export_path: Option<PathBuf>,
+}
+
+fn default_tracker_state_path() -> PathBuf {
+    PathBuf::from(DEFAULT_TRACKER_STATE_NAME)
+}
+
+fn default_cache_quota() -> u64 {
+    DEFAULT_CACHE_QUOTA
+}
+
+fn default_discovery() -> Vec<DiscoveryMethod> {
+    vec![DiscoveryMethod::Dht]
 }
 
 impl Default for OnDiskConfig {
@@ -60,6 +171,7 @@ impl Default for OnDiskConfig {
         Self {
             remote_listen_addr: "127.0.0.1:8080".parse().unwrap(),
             endpoint_listen_addr: "0.0.0.0:3001".parse().unwrap(),
+            endpoint_listen_addr_v6: None,
             // relative to xdg config dir
             blobs_path: PathBuf::from("blobs"),
             blobs_option: BlobsOptions {
@@ -73,6 +185,13 @@ impl Default for OnDiskConfig {
             eth_ws_rpc_url: "ws://127.0.0.1:8545".parse().unwrap(),
             // NOTE (amiller68): these are optional since we don't have good defaults
             factory_contract_address: None,
+            // relative to xdg config dir
+            tracker_state_path: default_tracker_state_path(),
+            cache_quota: default_cache_quota(),
+            discovery: default_discovery(),
+            relay: RelayConfig::Default,
+            serve: ServeConfig::default(),
+            export_path: None,
         }
     }
 }
@@ -91,6 +210,10 @@ impl OnDiskConfig {
         self.endpoint_listen_addr
     }
 
+    pub fn endpoint_listen_addr_v6(&self) -> Option<SocketAddrV6> {
+        self.endpoint_listen_addr_v6
+    }
+
     pub fn iroh_key_file_path(&self) -> PathBuf {
         let path = Self::find_config_dir();
         path.join(self.iroh_key_file_path.clone())
@@ -106,10 +229,19 @@ impl OnDiskConfig {
         path.join(self.blobs_path.clone())
     }
 
+    pub fn blobs_temp_path(&self) -> PathBuf {
+        self.blobs_path().join(self.blobs_option.path_temp.clone())
+    }
+
     pub fn eth_ws_rpc_url(&self) -> &Url {
         &self.eth_ws_rpc_url
     }
 
+    pub fn tracker_state_path(&self) -> PathBuf {
+        let path = Self::find_config_dir();
+        path.join(self.tracker_state_path.clone())
+    }
+
     pub fn factory_contract_address(&self) -> Address {
         self.factory_contract_address
             .expect("factory contract address is not set")
@@ -133,6 +265,7 @@ impl OnDiskConfig {
         eth_signer: Option<String>,
         http_port: Option<u16>,
         iroh_port: Option<u16>,
+        offline: bool,
     ) -> Result<(), ConfigError> {
         let path = Self::find_config_dir();
         if path.exists() {
@@ -151,6 +284,10 @@ impl OnDiskConfig {
         if let Some(port) = iroh_port {
             on_disk_config.endpoint_listen_addr = format!("0.0.0.0:{}", port).parse().unwrap();
         }
+        if offline {
+            on_disk_config.discovery = vec![DiscoveryMethod::Local];
+            on_disk_config.relay = RelayConfig::Disabled;
+        }
         // set the contract addresses
         on_disk_config.factory_contract_address = Some(factory_contract_address);
 
@@ -199,6 +336,29 @@ impl OnDiskConfig {
         Ok(config)
     }
 
+    pub fn cache_quota(&self) -> u64 {
+        self.cache_quota
+    }
+
+    pub fn discovery(&self) -> &[DiscoveryMethod] {
+        &self.discovery
+    }
+
+    pub fn relay(&self) -> &RelayConfig {
+        &self.relay
+    }
+
+    pub fn serve(&self) -> &ServeConfig {
+        &self.serve
+    }
+
+    pub fn export_path(&self) -> Option<PathBuf> {
+        let path = Self::find_config_dir();
+        self.export_path
+            .as_ref()
+            .map(|export_path| path.join(export_path))
+    }
+
     pub fn blobs_option(&self) -> Options {
         self.blobs_option.to_options()
     }
@@ -208,11 +368,19 @@ impl OnDiskConfig {
 pub struct Config {
     remote_listen_addr: SocketAddr,
     endpoint_listen_addr: SocketAddr,
+    endpoint_listen_addr_v6: Option<SocketAddrV6>,
     iroh_key_file_path: PathBuf,
     blobs_path: PathBuf,
+    blobs_temp_path: PathBuf,
     eth_key_file_path: PathBuf,
     eth_ws_rpc_url: Url,
     factory_contract_address: Address,
+    tracker_state_path: PathBuf,
+    cache_quota: u64,
+    discovery: Vec<DiscoveryMethod>,
+    relay: RelayConfig,
+    serve: ServeConfig,
+    export_path: Option<PathBuf>,
 
     // Logging Level
     log_level: tracing::Level,
@@ -236,6 +404,11 @@ impl Config {
             Err(_e) => on_disk_config.endpoint_listen_addr(),
         };
 
+        let endpoint_listen_addr_v6 = match env::var("ENDPOINT_LISTEN_ADDR_V6") {
+            Ok(addr) => Some(addr.parse()?),
+            Err(_e) => on_disk_config.endpoint_listen_addr_v6(),
+        };
+
         let log_level_str = match env::var("LOG_LEVEL") {
             Ok(level) => level,
             Err(_e) => {
@@ -254,11 +427,19 @@ impl Config {
         Ok(Config {
             remote_listen_addr,
             endpoint_listen_addr,
+            endpoint_listen_addr_v6,
             iroh_key_file_path: on_disk_config.iroh_key_file_path(),
             eth_key_file_path: on_disk_config.eth_key_file_path(),
             blobs_path: on_disk_config.blobs_path(),
+            blobs_temp_path: on_disk_config.blobs_temp_path(),
             eth_ws_rpc_url: on_disk_config.eth_ws_rpc_url().clone(),
             factory_contract_address: on_disk_config.factory_contract_address(),
+            tracker_state_path: on_disk_config.tracker_state_path(),
+            cache_quota: on_disk_config.cache_quota(),
+            discovery: on_disk_config.discovery().to_vec(),
+            relay: on_disk_config.relay().clone(),
+            serve: on_disk_config.serve().clone(),
+            export_path: on_disk_config.export_path(),
             log_level,
         })
     }
@@ -285,6 +466,10 @@ impl Config {
         &self.endpoint_listen_addr
     }
 
+    pub fn endpoint_listen_addr_v6(&self) -> Option<&SocketAddrV6> {
+        self.endpoint_listen_addr_v6.as_ref()
+    }
+
     pub fn iroh_secret_key(&self) -> Result<SecretKey, ConfigError> {
         let key_bytes = std::fs::read(&self.iroh_key_file_path)
             .map_err(|e| ConfigError::Io(e, self.iroh_key_file_path.clone()))?;
@@ -304,6 +489,30 @@ impl Config {
         &self.blobs_path
     }
 
+    pub fn blobs_temp_path(&self) -> &PathBuf {
+        &self.blobs_temp_path
+    }
+
+    pub fn cache_quota(&self) -> u64 {
+        self.cache_quota
+    }
+
+    pub fn discovery(&self) -> &[DiscoveryMethod] {
+        &self.discovery
+    }
+
+    pub fn relay(&self) -> &RelayConfig {
+        &self.relay
+    }
+
+    pub fn serve(&self) -> &ServeConfig {
+        &self.serve
+    }
+
+    pub fn export_path(&self) -> Option<&PathBuf> {
+        self.export_path.as_ref()
+    }
+
     pub fn eth_signer(&self) -> Result<PrivateKeySigner, ConfigError> {
         let key_bytes = std::fs::read(&self.eth_key_file_path)
             .map_err(|e| ConfigError::Io(e, self.eth_key_file_path.clone()))?;
@@ -322,6 +531,10 @@ impl Config {
     pub fn factory_contract_address(&self) -> &Address {
         &self.factory_contract_address
     }
+
+    pub fn tracker_state_path(&self) -> &PathBuf {
+        &self.tracker_state_path
+    }
 }
 #[derive(Debug, thiserror::Error)]
 pub enum ConfigError {
@@ -172,7 +172,7 @@ impl PoolContract {
         let contract = RewardPool::new(self.address, provider);
         let iroh_signature = self.iroh_signature;
         let node_id = self.tracker.current_node_id;
-        let k_bytes = FixedBytes::<32>::try_from(self.tracker.current_node_id.as_bytes()).expect("Failed to convert node_id to FixedBytes");
+        let k_bytes = FixedBytes::<32>::from(self.tracker.current_node_id.as_bytes());
         let r_bytes = FixedBytes::<32>::from(iroh_signature.r_bytes());
         let s_bytes = FixedBytes::<32>::from(iroh_signature.s_bytes());
         let m = self.private_key.address();
@@ -252,7 +252,7 @@ mod test {
         let private_key: PrivateKeySigner = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse()?;
         let message = private_key.address().into_array();
         let signature = secret_key.sign(message.as_ref());
-        let k_bytes = FixedBytes::<32>::try_from(node_id.as_bytes()).expect("Failed to convert node_id to FixedBytes");
+        let k_bytes = FixedBytes::<32>::from(node_id.as_bytes());
         let r_bytes = FixedBytes::<32>::from(signature.r_bytes());
         let s_bytes = FixedBytes::<32>::from(signature.s_bytes());
         let m = private_key.address();
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use alloy::primitives::Address;
+use axum::extract::{Json, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use iroh_blobs::Hash;
+use serde::Serialize;
+
+use crate::node::iroh::{AclEntry, AclUpdate};
+use crate::node::State as NodeState;
+
+#[derive(Serialize)]
+pub struct AclResponse {
+    hash: Hash,
+    #[serde(flatten)]
+    entry: AclEntry,
+}
+
+/// Every ACL we hold
+pub async fn list_handler(State(state): State<NodeState>) -> Result<impl IntoResponse, AclError> {
+    let entries: HashMap<Hash, AclEntry> = state.blobs_service().acls().list().await;
+    let mut entries = entries
+        .into_iter()
+        .map(|(hash, entry)| AclResponse { hash, entry })
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|response| response.hash);
+    Ok((StatusCode::OK, Json(entries)))
+}
+
+/// Who may fetch `hash` from us
+pub async fn get_handler(
+    State(state): State<NodeState>,
+    Path(hash): Path<Hash>,
+) -> Result<impl IntoResponse, AclError> {
+    let entry = state
+        .blobs_service()
+        .acls()
+        .get(&hash)
+        .await
+        .ok_or(AclError::NotFound(hash))?;
+    Ok((StatusCode::OK, Json(AclResponse { hash, entry })))
+}
+
+/// Allow or revoke nodes, or flip the public flag. Content backing a
+///  pool is public by definition, so its ACL can't be changed.
+pub async fn patch_handler(
+    State(state): State<NodeState>,
+    Path(hash): Path<Hash>,
+    Json(update): Json<AclUpdate>,
+) -> Result<impl IntoResponse, AclError> {
+    let blobs_service = state.blobs_service();
+    if blobs_service.get_stored_size(hash).await?.is_none() {
+        return Err(AclError::BlobNotFound(hash));
+    }
+    let pools = state.tracker().pools_for_hash(hash).await;
+    if !pools.is_empty() {
+        return Err(AclError::BacksPools(
+            pools.into_iter().map(|key| key.address).collect(),
+        ));
+    }
+    let entry = blobs_service.acls().update(hash, update).await?;
+    Ok((StatusCode::OK, Json(AclResponse { hash, entry })))
+}
+
+/// Drop the ACL, leaving `hash` to the serving policy
+pub async fn delete_handler(
+    State(state): State<NodeState>,
+    Path(hash): Path<Hash>,
+) -> Result<impl IntoResponse, AclError> {
+    let entry = state
+        .blobs_service()
+        .acls()
+        .remove(&hash)
+        .await?
+        .ok_or(AclError::NotFound(hash))?;
+    Ok((StatusCode::OK, Json(AclResponse { hash, entry })))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AclError {
+    #[error(transparent)]
+    Default(#[from] anyhow::Error),
+    #[error("no ACL for {0}")]
+    NotFound(Hash),
+    #[error("blob not found: {0}")]
+    BlobNotFound(Hash),
+    #[error("content backs pools {0:?}, and pool content is always public")]
+    BacksPools(Vec<Address>),
+}
+
+impl IntoResponse for AclError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            AclError::NotFound(_) | AclError::BlobNotFound(_) => StatusCode::NOT_FOUND,
+            AclError::BacksPools(_) => StatusCode::CONFLICT,
+            AclError::Default(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let body = Json(serde_json::json!({
+            "error": self.to_string(),
+        }));
+
+        (status, body).into_response()
+    }
+}
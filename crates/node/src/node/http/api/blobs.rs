@@ -0,0 +1,118 @@
+use axum::extract::{Json, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use iroh_blobs::{BlobFormat, Hash};
+use serde::{Deserialize, Serialize};
+
+use crate::node::State as NodeState;
+
+#[derive(Deserialize)]
+pub struct PinRequest {
+    hash: Hash,
+}
+
+#[derive(Serialize)]
+pub struct PinResponse {
+    hash: Hash,
+    format: BlobFormat,
+    message: String,
+}
+
+/// Pin content so retention never evicts it, pulling it first if
+///  we don't hold it yet
+pub async fn pin_handler(
+    State(state): State<NodeState>,
+    Json(request): Json<PinRequest>,
+) -> Result<impl IntoResponse, BlobsError> {
+    let hash = request.hash;
+    state
+        .tracker()
+        .pull_blob(hash)
+        .await
+        .map_err(BlobsError::NotFound)?;
+    let format = state.tracker().stored_format(hash).await?;
+    state.blobs_service().pin(hash, format).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(PinResponse {
+            hash,
+            format,
+            message: format!("Pinned {}", hash),
+        }),
+    ))
+}
+
+#[derive(Serialize)]
+pub struct UnpinResponse {
+    hash: Hash,
+    message: String,
+}
+
+pub async fn unpin_handler(
+    State(state): State<NodeState>,
+    Json(request): Json<PinRequest>,
+) -> Result<impl IntoResponse, BlobsError> {
+    let hash = request.hash;
+    if !state.blobs_service().unpin(hash).await? {
+        return Err(BlobsError::NotPinned(hash));
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(UnpinResponse {
+            hash,
+            message: format!("Unpinned {}", hash),
+        }),
+    ))
+}
+
+#[derive(Serialize)]
+pub struct GcResponse {
+    evicted: Vec<Hash>,
+    bytes_reclaimed: u64,
+    message: String,
+}
+
+/// Evict cached content over the quota and wait for GC to sweep it
+pub async fn gc_handler(State(state): State<NodeState>) -> Result<impl IntoResponse, BlobsError> {
+    let report = state.blobs_service().gc().await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(GcResponse {
+            message: format!(
+                "Evicted {} blobs, reclaimed {} bytes",
+                report.evicted.len(),
+                report.bytes_reclaimed
+            ),
+            evicted: report.evicted,
+            bytes_reclaimed: report.bytes_reclaimed,
+        }),
+    ))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BlobsError {
+    #[error(transparent)]
+    Default(#[from] anyhow::Error),
+    #[error("content not found: {0}")]
+    NotFound(anyhow::Error),
+    #[error("not pinned: {0}")]
+    NotPinned(Hash),
+}
+
+impl IntoResponse for BlobsError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            BlobsError::NotFound(_) | BlobsError::NotPinned(_) => StatusCode::NOT_FOUND,
+            BlobsError::Default(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let body = Json(serde_json::json!({
+            "error": self.to_string(),
+        }));
+
+        (status, body).into_response()
+    }
+}
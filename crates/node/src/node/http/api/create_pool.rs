@@ -1,7 +1,7 @@
 use alloy::primitives::U256;
 use axum::extract::{Json, State};
 use axum::response::{IntoResponse, Response};
-use iroh_blobs::Hash;
+use iroh_blobs::{BlobFormat, Hash};
 use serde::{Deserialize, Serialize};
 
 use crate::node::State as NodeState;
@@ -9,6 +9,9 @@ use crate::node::State as NodeState;
 #[derive(Deserialize)]
 pub struct CreatePoolRequest {
     hash: Hash,
+    // Raw unless told otherwise -- use HashSeq for collections
+    #[serde(default)]
+    format: BlobFormat,
     value: Option<U256>,
 }
 
@@ -30,7 +33,7 @@ pub async fn handler(
     // Create a pool using the tracker
     state
         .tracker()
-        .create_pool(hash, value)
+        .create_pool(hash, request.format, value)
         .await
         .map_err(CreatePoolError::Default)?;
 
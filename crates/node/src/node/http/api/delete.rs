@@ -0,0 +1,88 @@
+use alloy::primitives::Address;
+use axum::extract::{Json, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use iroh_blobs::Hash;
+use serde::{Deserialize, Serialize};
+
+use crate::node::State as NodeState;
+
+#[derive(Deserialize)]
+pub struct DeleteQuery {
+    /// Leave any pool the content backs rather than refusing
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Serialize)]
+pub struct DeleteResponse {
+    hash: Hash,
+    left_pools: Vec<Address>,
+    bytes_reclaimed: u64,
+    message: String,
+}
+
+/// Remove content from the node. Content backing a pool we've joined
+///  is only removed with `?force=true`, which leaves those pools once
+///  it's gone.
+pub async fn handler(
+    State(state): State<NodeState>,
+    Path(hash): Path<Hash>,
+    Query(query): Query<DeleteQuery>,
+) -> Result<impl IntoResponse, DeleteError> {
+    if state.blobs_service().get_stored_size(hash).await?.is_none() {
+        return Err(DeleteError::NotFound(hash));
+    }
+    let tracker = state.tracker();
+    let joined = tracker.joined_pools(hash).await?;
+    if !joined.is_empty() && !query.force {
+        return Err(DeleteError::BacksPools(
+            joined.into_iter().map(|key| key.address).collect(),
+        ));
+    }
+
+    let (left, report) = tracker
+        .delete_content(hash)
+        .await?
+        .ok_or(DeleteError::NotFound(hash))?;
+    let left_pools = left.into_iter().map(|key| key.address).collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(DeleteResponse {
+            hash,
+            message: format!(
+                "Deleted {}, reclaimed {} bytes",
+                hash, report.bytes_reclaimed
+            ),
+            left_pools,
+            bytes_reclaimed: report.bytes_reclaimed,
+        }),
+    ))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteError {
+    #[error(transparent)]
+    Default(#[from] anyhow::Error),
+    #[error("blob not found: {0}")]
+    NotFound(Hash),
+    #[error("content backs joined pools {0:?}, use force to leave them")]
+    BacksPools(Vec<Address>),
+}
+
+impl IntoResponse for DeleteError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            DeleteError::NotFound(_) => StatusCode::NOT_FOUND,
+            DeleteError::BacksPools(_) => StatusCode::CONFLICT,
+            DeleteError::Default(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let body = Json(serde_json::json!({
+            "error": self.to_string(),
+        }));
+
+        (status, body).into_response()
+    }
+}
@@ -0,0 +1,24 @@
+use axum::extract::{Json, Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use iroh::NodeId;
+
+use crate::node::State as NodeState;
+
+/// How reachable we are: bound and direct addresses, home relay and
+///  what NAT we seem to be behind
+pub async fn self_handler(State(state): State<NodeState>) -> impl IntoResponse {
+    (StatusCode::OK, Json(state.diagnostics().local()))
+}
+
+/// Look a peer up and try to reach it, reporting the path we got there
+///  by. An unreachable peer is a finding, not an error.
+pub async fn peer_handler(
+    State(state): State<NodeState>,
+    Path(node_id): Path<NodeId>,
+) -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        Json(state.diagnostics().peer(node_id).await),
+    )
+}
@@ -0,0 +1,178 @@
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use axum::extract::{Json, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use iroh_blobs::ticket::BlobTicket;
+use iroh_blobs::{BlobFormat, Hash};
+use serde::{Deserialize, Serialize};
+
+use crate::node::iroh::{BlobTag, ContentKey, EncryptedTicket, ExportLink};
+use crate::node::State as NodeState;
+
+#[derive(Deserialize)]
+pub struct ExportRequest {
+    /// A hash we can find through our pools, or a ticket naming a node
+    ///  that has it. An encrypted ticket carries its own key.
+    content: String,
+    /// Where to write it, relative to the node's export directory
+    output: PathBuf,
+    #[serde(default)]
+    link: ExportLink,
+    /// Decrypt content shared with `--encrypt` on the way out
+    key: Option<ContentKey>,
+}
+
+#[derive(Serialize)]
+pub struct ExportResponse {
+    hash: Hash,
+    format: BlobFormat,
+    path: PathBuf,
+    message: String,
+}
+
+/// Fetch content if we don't hold it, then write it out under the
+///  node's export directory. Collections come out as a directory tree.
+///  Nothing is written unless the node has an export directory
+///  configured; clients that want the content themselves pull it.
+pub async fn handler(
+    State(state): State<NodeState>,
+    Json(request): Json<ExportRequest>,
+) -> Result<impl IntoResponse, ExportError> {
+    let output = destination(state.export_path(), &request.output)?;
+    let (content, key) = match EncryptedTicket::from_str(&request.content) {
+        Ok(encrypted) => (encrypted.ticket.to_string(), Some(encrypted.key)),
+        Err(_) => (request.content, request.key),
+    };
+
+    let (hash, format) = match Hash::from_str(&content) {
+        Ok(hash) => {
+            state
+                .tracker()
+                .pull_blob(hash)
+                .await
+                .map_err(ExportError::NotFound)?;
+            (hash, state.tracker().stored_format(hash).await?)
+        }
+        Err(_) => {
+            let ticket = BlobTicket::from_str(&content)
+                .map_err(|_| ExportError::InvalidContent(content.clone()))?;
+            let blobs_service = state.blobs_service();
+            if !blobs_service
+                .has_content(ticket.hash(), ticket.format())
+                .await?
+            {
+                blobs_service
+                    .download_blob(&ticket, BlobTag::Cache(ticket.hash()))
+                    .await
+                    .map_err(ExportError::NotFound)?;
+            }
+            (ticket.hash(), ticket.format())
+        }
+    };
+
+    match key {
+        // only single files are ever encrypted
+        Some(_) if format == BlobFormat::HashSeq => return Err(ExportError::NotEncrypted(hash)),
+        Some(key) => {
+            state
+                .blobs_service()
+                .export_decrypted(hash, key, output.clone())
+                .await?
+        }
+        None => {
+            state
+                .blobs_service()
+                .export(hash, format, output.clone(), request.link)
+                .await?
+        }
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(ExportResponse {
+            hash,
+            format,
+            message: format!("Content {} written to '{}'", hash, output.display()),
+            path: request.output,
+        }),
+    ))
+}
+
+/// Where `output` ends up, so long as exports are on and it stays
+///  within the export directory
+fn destination(export_path: Option<&Path>, output: &Path) -> Result<PathBuf, ExportError> {
+    let export_path = export_path.ok_or(ExportError::Disabled)?;
+    let relative = output.components().next().is_some()
+        && output
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    if !relative {
+        return Err(ExportError::InvalidPath(output.to_path_buf()));
+    }
+    Ok(export_path.join(output))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error(transparent)]
+    Default(#[from] anyhow::Error),
+    #[error("this node has no export directory configured")]
+    Disabled,
+    #[error("output must be a relative path within the export directory: {}", .0.display())]
+    InvalidPath(PathBuf),
+    #[error("not a hash or a ticket: {0}")]
+    InvalidContent(String),
+    #[error("failed to get content: {0}")]
+    NotFound(anyhow::Error),
+    #[error("{0} is a collection, and collections aren't encrypted")]
+    NotEncrypted(Hash),
+}
+
+impl IntoResponse for ExportError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            ExportError::InvalidPath(_)
+            | ExportError::InvalidContent(_)
+            | ExportError::NotEncrypted(_) => StatusCode::BAD_REQUEST,
+            ExportError::Disabled => StatusCode::FORBIDDEN,
+            ExportError::NotFound(_) => StatusCode::NOT_FOUND,
+            ExportError::Default(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let body = Json(serde_json::json!({
+            "error": self.to_string(),
+        }));
+
+        (status, body).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_destination() {
+        let exports = Path::new("/var/jax/exports");
+        assert_eq!(
+            destination(Some(exports), Path::new("site/index.html")).unwrap(),
+            exports.join("site/index.html")
+        );
+        for output in ["/etc/passwd", "../outside", "site/../../outside", "", "./x"] {
+            assert!(
+                matches!(
+                    destination(Some(exports), Path::new(output)),
+                    Err(ExportError::InvalidPath(_))
+                ),
+                "{} allowed",
+                output
+            );
+        }
+        assert!(matches!(
+            destination(None, Path::new("site")),
+            Err(ExportError::Disabled)
+        ));
+    }
+}
@@ -0,0 +1,226 @@
+use std::convert::Infallible;
+
+use alloy::primitives::{Address, U256};
+use axum::extract::{Json, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, Sse};
+use axum::response::{IntoResponse, Response};
+use iroh_blobs::ticket::BlobTicket;
+use iroh_blobs::{BlobFormat, Hash};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::node::iroh::{BlobTag, FetchProgress};
+use crate::node::State as NodeState;
+
+/// How many progress events can queue up before the download waits on the client
+const PROGRESS_CHANNEL_CAPACITY: usize = 64;
+
+/// What to do about a pool once the content is in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchPool {
+    #[default]
+    None,
+    /// Join the existing pool for the hash
+    Join,
+    /// Create a pool for the hash, funded with `initial_value`
+    Create,
+}
+
+#[derive(Deserialize)]
+pub struct FetchRequest {
+    ticket: BlobTicket,
+    #[serde(default)]
+    pool: FetchPool,
+    /// The pool to join -- needed when the hash backs more than one
+    pool_address: Option<Address>,
+    initial_value: Option<U256>,
+    /// Keep the content regardless of the cache quota
+    #[serde(default)]
+    pin: bool,
+    /// Respond with a stream of download progress events (as Server-Sent
+    ///  Events) ending in a `done` event, instead of a single JSON body
+    #[serde(default)]
+    progress: bool,
+}
+
+#[derive(Serialize)]
+pub struct FetchResponse {
+    hash: Hash,
+    format: BlobFormat,
+    bytes_read: u64,
+    elapsed_ms: u64,
+    /// The pool we joined, if any -- a created pool's address isn't
+    ///  known until the chain sync picks it up
+    joined_pool: Option<Address>,
+    created_pool: bool,
+    message: String,
+}
+
+/// Download content straight from the node a ticket names, no pool needed
+pub async fn handler(
+    State(state): State<NodeState>,
+    Json(request): Json<FetchRequest>,
+) -> Result<Response, FetchError> {
+    if !request.progress {
+        let response = fetch(&state, request, None).await?;
+        return Ok((StatusCode::OK, Json(response)).into_response());
+    }
+
+    let (tx, rx) = mpsc::channel(PROGRESS_CHANNEL_CAPACITY);
+    let task = tokio::spawn(async move { fetch(&state, request, Some(tx)).await });
+
+    // Forward progress until the download drops its sender, then report
+    //  how the fetch as a whole went
+    let stream = futures::stream::unfold(
+        (rx, Some(task)),
+        |(mut rx, task): (mpsc::Receiver<FetchProgress>, Option<JoinHandle<_>>)| async move {
+            if let Some(progress) = rx.recv().await {
+                let event = Event::default()
+                    .event("progress")
+                    .json_data(&progress)
+                    .unwrap_or_else(|e| Event::default().event("error").data(e.to_string()));
+                return Some((Ok::<_, Infallible>(event), (rx, task)));
+            }
+            let event = match task?.await {
+                Ok(Ok(response)) => Event::default()
+                    .event("done")
+                    .json_data(&response)
+                    .unwrap_or_else(|e| Event::default().event("error").data(e.to_string())),
+                Ok(Err(e)) => Event::default().event("error").data(e.to_string()),
+                Err(e) => Event::default().event("error").data(e.to_string()),
+            };
+            Some((Ok(event), (rx, None)))
+        },
+    );
+
+    Ok(Sse::new(stream).into_response())
+}
+
+async fn fetch(
+    state: &NodeState,
+    request: FetchRequest,
+    progress: Option<mpsc::Sender<FetchProgress>>,
+) -> Result<FetchResponse, FetchError> {
+    let ticket = request.ticket;
+    let (hash, format) = (ticket.hash(), ticket.format());
+    let tracker = state.tracker();
+
+    // check up front, so we don't download for a pool we can't make or join
+    let pool_key = match request.pool {
+        FetchPool::Join => {
+            let mut pools = tracker.pools_for_hash(hash).await;
+            match request.pool_address {
+                Some(address) => Some(
+                    pools
+                        .into_iter()
+                        .find(|key| key.address == address)
+                        .ok_or(FetchError::NoSuchPool(address, hash))?,
+                ),
+                // only guess when there's nothing to choose between
+                None if pools.len() > 1 => {
+                    return Err(FetchError::AmbiguousPool(
+                        hash,
+                        pools.into_iter().map(|key| key.address).collect(),
+                    ))
+                }
+                None => Some(pools.pop().ok_or(FetchError::NoPool(hash))?),
+            }
+        }
+        FetchPool::Create => {
+            let initial_value = request.initial_value.unwrap_or_default();
+            let balance = tracker.get_address_balance(state.eth_address()).await?;
+            if balance < initial_value {
+                return Err(FetchError::InsufficientBalance(balance, initial_value));
+            }
+            None
+        }
+        FetchPool::None => None,
+    };
+
+    // content backing a pool we made is ours to keep
+    let tag = if request.pin || request.pool == FetchPool::Create {
+        BlobTag::Pinned(hash)
+    } else {
+        BlobTag::Cache(hash)
+    };
+    let stats = state
+        .blobs_service()
+        .download(&ticket, tag, progress)
+        .await
+        .map_err(FetchError::Download)?;
+    tracker.set_content_format(hash, format).await;
+
+    let joined_pool = match pool_key {
+        Some(key) => {
+            tracker.enter_pool(key.clone()).await?;
+            Some(key.address)
+        }
+        None => None,
+    };
+    let created_pool = request.pool == FetchPool::Create;
+    if created_pool {
+        tracker
+            .create_pool(hash, format, request.initial_value)
+            .await?;
+    }
+
+    let mut message = format!(
+        "Fetched {} ({} bytes) from {}",
+        hash,
+        stats.bytes_read,
+        ticket.node_addr().node_id
+    );
+    if let Some(address) = joined_pool {
+        message.push_str(&format!(" and joined pool {}", address));
+    }
+    if created_pool {
+        message.push_str(" and created a pool for it");
+    }
+
+    Ok(FetchResponse {
+        hash,
+        format,
+        bytes_read: stats.bytes_read,
+        elapsed_ms: stats.elapsed.as_millis() as u64,
+        joined_pool,
+        created_pool,
+        message,
+    })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    #[error(transparent)]
+    Default(#[from] anyhow::Error),
+    #[error("download failed: {0}")]
+    Download(anyhow::Error),
+    #[error("no pool to join for {0}")]
+    NoPool(Hash),
+    #[error("pool {0} isn't backed by {1}")]
+    NoSuchPool(Address, Hash),
+    #[error("{0} backs several pools {1:?}, name the one to join")]
+    AmbiguousPool(Hash, Vec<Address>),
+    #[error("insufficient balance (have {0}, need {1})")]
+    InsufficientBalance(U256, U256),
+}
+
+impl IntoResponse for FetchError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            FetchError::NoPool(_) | FetchError::NoSuchPool(_, _) => StatusCode::NOT_FOUND,
+            FetchError::AmbiguousPool(_, _) => StatusCode::CONFLICT,
+            FetchError::InsufficientBalance(_, _) => StatusCode::BAD_REQUEST,
+            FetchError::Download(_) => StatusCode::BAD_GATEWAY,
+            FetchError::Default(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let body = Json(serde_json::json!({
+            "error": self.to_string(),
+        }));
+
+        (status, body).into_response()
+    }
+}
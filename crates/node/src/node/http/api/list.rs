@@ -1,54 +1,31 @@
-use axum::extract::State;
+use axum::extract::{Query, State};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
-use iroh_blobs::store::ReadableStore;
-use serde::Serialize;
 
+use crate::node::http::listing::{self, ListQuery};
 use crate::node::State as NodeState;
 
-#[derive(Serialize)]
-pub struct BlobInfo {
-    hash: String,
-}
-
-#[derive(Serialize)]
-pub struct ListBlobsResponse {
-    blobs: Vec<BlobInfo>,
-}
-
-pub async fn handler(State(state): State<NodeState>) -> Result<impl IntoResponse, ListBlobsError> {
-    // Get blobs from state
-    let blobs = state.blobs_service().get_inner_blobs();
-
-    let blob_list = blobs.store().blobs().await?;
-
-    // Convert to response format
-    let blob_infos = blob_list
-        .into_iter()
-        .filter_map(|blob_result| {
-            blob_result.ok().map(|hash| BlobInfo {
-                hash: hash.to_string(),
-            })
-        })
-        .collect();
-
-    let response = ListBlobsResponse { blobs: blob_infos };
+/// Page through the blobs we hold, with their sizes, tags and the
+///  pools they back
+pub async fn handler(
+    State(state): State<NodeState>,
+    Query(query): Query<ListQuery>,
+) -> Result<impl IntoResponse, ListBlobsError> {
+    let listing = listing::list_blobs(&state, &query).await?;
 
-    Ok((axum::http::StatusCode::OK, Json(response)))
+    Ok((axum::http::StatusCode::OK, Json(listing)))
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum ListBlobsError {
     #[error(transparent)]
     Default(#[from] anyhow::Error),
-    #[error("io error: {0}")]
-    Io(#[from] std::io::Error),
 }
 
 impl IntoResponse for ListBlobsError {
     fn into_response(self) -> Response {
         let (status, error_message) = match self {
-            ListBlobsError::Default(_) | ListBlobsError::Io(_) => (
+            ListBlobsError::Default(_) => (
                 axum::http::StatusCode::INTERNAL_SERVER_ERROR,
                 "internal server error".to_string(),
             ),
@@ -3,27 +3,64 @@ use http::header::{ACCEPT, ORIGIN};
 use http::Method;
 use tower_http::cors::{Any, CorsLayer};
 
+mod acl;
+mod blobs;
+mod claim_rewards;
 mod create_pool;
+mod delete;
 mod deposit;
+mod diagnostics;
+mod events;
+mod export;
+mod fetch;
 mod list;
+mod peer;
 mod pools;
 mod probe;
 mod pull;
 mod query;
 mod share;
-mod claim_rewards;
 mod share_stream;
+mod stats;
+mod transfers;
+mod uploads;
 
+pub use acl::{
+    delete_handler as acl_delete_handler, get_handler as acl_get_handler,
+    list_handler as acl_list_handler, patch_handler as acl_patch_handler,
+};
+pub use blobs::{
+    gc_handler as blobs_gc_handler, pin_handler as blobs_pin_handler,
+    unpin_handler as blobs_unpin_handler,
+};
+pub use claim_rewards::handler as claim_rewards_handler;
 pub use create_pool::handler as create_pool_handler;
+pub use delete::handler as delete_handler;
 pub use deposit::handler as deposit_handler;
+pub use diagnostics::{
+    peer_handler as diagnostics_peer_handler, self_handler as diagnostics_handler,
+};
+pub use events::handler as events_handler;
+pub use export::handler as export_handler;
+pub use fetch::handler as fetch_handler;
 pub use list::handler as list_handler;
+pub use peer::handler as peer_handler;
 pub use pools::handler as pools_handler;
 pub use probe::handler as probe_handler;
 pub use pull::handler as pull_handler;
 pub use query::handler as query_handler;
 pub use share::handler as share_handler;
 pub use share_stream::handler as share_stream_handler;
-pub use claim_rewards::handler as claim_rewards_handler;
+pub use stats::handler as stats_handler;
+pub use transfers::{
+    cancel_handler as transfer_cancel_handler, get_handler as transfer_get_handler,
+    list_handler as transfers_handler,
+};
+pub use uploads::{
+    create_handler as upload_create_handler, delete_handler as upload_delete_handler,
+    finalize_handler as upload_finalize_handler, patch_handler as upload_patch_handler,
+    status_handler as upload_status_handler,
+};
 
 use crate::node::State as NodeState;
 
@@ -31,6 +68,8 @@ use crate::node::State as NodeState;
 // const MAX_UPLOAD_SIZE: usize = 1024 * 1024 * 100; // 100MB, adjust as needed
 
 pub fn router(state: NodeState) -> Router<NodeState> {
+    // the API is unauthenticated, so pages on other origins don't get
+    //  to PATCH or DELETE through a visitor's browser
     let cors_layer = CorsLayer::new()
         .allow_methods(vec![Method::GET, Method::POST])
         .allow_headers(vec![ACCEPT, ORIGIN])
@@ -45,9 +84,46 @@ pub fn router(state: NodeState) -> Router<NodeState> {
         .route("/query/:hash", routing::get(query_handler))
         .route("/pool", routing::post(create_pool_handler))
         .route("/pools", routing::get(pools_handler))
+        .route("/peer/:node_id", routing::get(peer_handler))
+        .route("/diagnostics", routing::get(diagnostics_handler))
+        .route(
+            "/diagnostics/:node_id",
+            routing::get(diagnostics_peer_handler),
+        )
         .route("/pull/:hash", routing::get(pull_handler))
+        .route("/export", routing::post(export_handler))
+        .route("/fetch", routing::post(fetch_handler))
         .route("/pool/deposit", routing::post(deposit_handler))
         .route("/rewards", routing::post(claim_rewards_handler))
+        .route("/events", routing::get(events_handler))
+        .route("/blob/:hash", routing::delete(delete_handler))
+        .route("/blobs/pin", routing::post(blobs_pin_handler))
+        .route("/blobs/unpin", routing::post(blobs_unpin_handler))
+        .route("/blobs/gc", routing::post(blobs_gc_handler))
+        .route("/acl", routing::get(acl_list_handler))
+        .route(
+            "/acl/:hash",
+            routing::get(acl_get_handler)
+                .patch(acl_patch_handler)
+                .delete(acl_delete_handler),
+        )
+        .route("/stats", routing::get(stats_handler))
+        .route("/transfers", routing::get(transfers_handler))
+        .route(
+            "/transfers/:id",
+            routing::get(transfer_get_handler).delete(transfer_cancel_handler),
+        )
+        .route("/uploads", routing::post(upload_create_handler))
+        .route(
+            "/uploads/:id",
+            routing::get(upload_status_handler)
+                .patch(upload_patch_handler)
+                .delete(upload_delete_handler),
+        )
+        .route(
+            "/uploads/:id/finalize",
+            routing::post(upload_finalize_handler),
+        )
         .with_state(state)
         .layer(cors_layer)
         // Remove the default body size limit
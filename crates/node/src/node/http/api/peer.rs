@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use alloy::primitives::Address;
+use axum::extract::{Json, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use iroh::NodeId;
+use serde::{Deserialize, Serialize};
+
+use crate::node::iroh::protocol::{
+    NodeIdentity, PoolAnnouncement, Request, Response as JaxResponse,
+};
+use crate::node::State as NodeState;
+
+#[derive(Deserialize)]
+pub struct PeerQuery {
+    /// Also ask for the peer's trust row in this pool
+    pool: Option<Address>,
+}
+
+#[derive(Serialize)]
+pub struct PeerResponse {
+    identity: NodeIdentity,
+    /// Whether the peer's signature over its beneficiary checks out
+    verified: bool,
+    pools: Vec<PoolAnnouncement>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trust_row: Option<HashMap<NodeId, f64>>,
+}
+
+/// What a peer says about itself over the jax protocol -- no RPC involved
+pub async fn handler(
+    State(state): State<NodeState>,
+    Path(node_id): Path<NodeId>,
+    Query(query): Query<PeerQuery>,
+) -> Result<impl IntoResponse, PeerError> {
+    let tracker = state.tracker();
+    let identity = match tracker.query_peer(node_id, &Request::Identity).await? {
+        JaxResponse::Identity(identity) => identity,
+        _ => return Err(PeerError::Unexpected),
+    };
+    // a peer answering for someone else's key is no better than a forgery
+    let verified = identity.node_id == node_id && identity.verify();
+    let pools = match tracker.query_peer(node_id, &Request::Pools).await? {
+        JaxResponse::Pools { pools } => pools,
+        _ => return Err(PeerError::Unexpected),
+    };
+    let trust_row = match query.pool {
+        Some(pool) => match tracker
+            .query_peer(node_id, &Request::TrustRow { pool })
+            .await?
+        {
+            JaxResponse::TrustRow { row, .. } => row,
+            _ => return Err(PeerError::Unexpected),
+        },
+        None => None,
+    };
+    Ok((
+        StatusCode::OK,
+        Json(PeerResponse {
+            identity,
+            verified,
+            pools,
+            trust_row,
+        }),
+    ))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PeerError {
+    #[error("peer unreachable: {0}")]
+    Unreachable(#[from] anyhow::Error),
+    #[error("peer answered with the wrong kind of response")]
+    Unexpected,
+}
+
+impl IntoResponse for PeerError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            PeerError::Unreachable(_) | PeerError::Unexpected => StatusCode::BAD_GATEWAY,
+        };
+
+        let body = Json(serde_json::json!({
+            "error": self.to_string(),
+        }));
+
+        (status, body).into_response()
+    }
+}
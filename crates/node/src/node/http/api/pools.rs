@@ -7,9 +7,12 @@ use serde::Serialize;
 
 use crate::node::State as NodeState;
 
+/// (pool address, hash, balance, peers sorted by trust)
+type PoolEntry = (Address, iroh_blobs::Hash, U256, Vec<(iroh::NodeId, f64)>);
+
 #[derive(Serialize)]
 pub struct PoolsResponse {
-    pools: Vec<(Address, iroh_blobs::Hash, U256, Vec<(iroh::NodeId, f64)>)>,
+    pools: Vec<PoolEntry>,
     message: String,
 }
 
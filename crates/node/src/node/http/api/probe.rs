@@ -3,17 +3,18 @@ use axum::extract::{Json, State};
 use axum::response::{IntoResponse, Response};
 use iroh::NodeId;
 use iroh_blobs::get::Stats;
-use iroh_blobs::Hash;
+use iroh_blobs::{BlobFormat, Hash};
 use serde::{Deserialize, Serialize};
 
-use crate::node::tracker::{PoolKey, ProbeResult, Tracker};
+use crate::node::tracker::{PoolKey, ProbeResult};
 use crate::node::State as NodeState;
 
 #[derive(Deserialize)]
 pub struct ProbeRequest {
     hash: Hash,
     node: NodeId,
-    address: Option<Address>, // Optional pool address
+    address: Option<Address>,   // Optional pool address
+    format: Option<BlobFormat>, // Defaults to what the tracker knows about the hash
 }
 
 #[derive(Serialize)]
@@ -66,13 +67,14 @@ pub async fn handler(
         }
     } else {
         // Fall back to basic probe_node if no pool address
-        let ticket = iroh_blobs::ticket::BlobTicket::new(
-            request.node.into(),
-            request.hash,
-            iroh_blobs::BlobFormat::Raw,
-        )?;
+        let format = match request.format {
+            Some(format) => format,
+            None => state.tracker().content_format(request.hash).await,
+        };
+        let ticket =
+            iroh_blobs::ticket::BlobTicket::new(request.node.into(), request.hash, format)?;
 
-        let probe_result = Tracker::probe_node(ticket).await;
+        let probe_result = state.tracker().probe_node(ticket).await;
 
         Ok((
             axum::http::StatusCode::OK,
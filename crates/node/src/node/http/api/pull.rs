@@ -1,33 +1,106 @@
-use axum::body::Body;
-use axum::extract::{Path, State};
-use axum::response::IntoResponse;
-use iroh_blobs::Hash;
+use axum::extract::{Json, Path, State};
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use iroh_blobs::{BlobFormat, Hash};
+use serde::Serialize;
 
+use crate::node::http::content;
 use crate::node::State as NodeState;
 
+#[derive(Serialize)]
+pub struct CollectionEntry {
+    name: String,
+    hash: Hash,
+}
+
+#[derive(Serialize)]
+pub struct PullResponse {
+    hash: Hash,
+    format: BlobFormat,
+    entries: Vec<CollectionEntry>,
+    message: String,
+}
+
 #[axum::debug_handler]
-pub async fn handler(State(state): State<NodeState>, Path(hash): Path<Hash>) -> impl IntoResponse {
+pub async fn handler(
+    State(state): State<NodeState>,
+    Path(hash): Path<Hash>,
+    headers: HeaderMap,
+) -> Result<Response, PullError> {
     // Pull if needed, then stream
-    match state.tracker().pull_blob(hash).await {
-        Ok(_) => {
-            // Stream the blob
-            match state.blobs_service().read_blob(hash).await {
-                Ok(reader) => {
-                    let body = Body::from_stream(reader);
-                    Ok((
-                        [(axum::http::header::CONTENT_TYPE, "application/octet-stream")],
-                        body,
-                    ))
-                }
-                Err(e) => Err((
-                    axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Failed to stream blob: {}", e),
-                )),
-            }
+    state
+        .tracker()
+        .pull_blob(hash)
+        .await
+        .map_err(PullError::NotFound)?;
+    let format = state
+        .tracker()
+        .stored_format(hash)
+        .await
+        .map_err(PullError::Default)?;
+
+    match format {
+        // A collection has no single body to stream -- describe it, and
+        //  the client pulls each entry to restore the tree
+        BlobFormat::HashSeq => {
+            let collection = state
+                .blobs_service()
+                .get_collection(hash)
+                .await
+                .map_err(PullError::Default)?;
+            let entries = collection
+                .iter()
+                .map(|(name, hash)| CollectionEntry {
+                    name: name.clone(),
+                    hash: *hash,
+                })
+                .collect::<Vec<_>>();
+            let response = PullResponse {
+                hash,
+                format,
+                message: format!("Collection {} has {} entries", hash, entries.len()),
+                entries,
+            };
+            Ok((axum::http::StatusCode::OK, Json(response)).into_response())
         }
-        Err(e) => Err((
-            axum::http::StatusCode::NOT_FOUND,
-            format!("Failed to get blob: {}", e),
-        )),
+        // Stream the blob, or whatever part of it was asked for
+        BlobFormat::Raw => content::blob_response(state.blobs_service(), hash, &headers, None)
+            .await
+            .map_err(PullError::Stream),
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PullError {
+    #[error(transparent)]
+    Default(#[from] anyhow::Error),
+    #[error("failed to get blob: {0}")]
+    NotFound(anyhow::Error),
+    #[error("failed to stream blob: {0}")]
+    Stream(anyhow::Error),
+}
+
+impl IntoResponse for PullError {
+    fn into_response(self) -> Response {
+        let (status, error_message) = match self {
+            PullError::NotFound(e) => (
+                axum::http::StatusCode::NOT_FOUND,
+                format!("Failed to get blob: {}", e),
+            ),
+            PullError::Stream(e) => (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to stream blob: {}", e),
+            ),
+            PullError::Default(e) => (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Error: {}", e),
+            ),
+        };
+
+        let body = Json(serde_json::json!({
+            "error": error_message,
+        }));
+
+        (status, body).into_response()
     }
 }
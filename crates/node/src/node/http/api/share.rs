@@ -1,44 +1,97 @@
+use std::convert::Infallible;
+use std::path::PathBuf;
+
 use alloy::primitives::U256;
 use axum::extract::{Json, State};
+use axum::response::sse::{Event, Sse};
 use axum::response::{IntoResponse, Response};
 use iroh_blobs::ticket::BlobTicket;
 use iroh_blobs::BlobFormat;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 
+use crate::node::iroh::{ImportMode, ImportProgress};
 use crate::node::State as NodeState;
 
+/// How many progress events can queue up before the import waits on the client
+const PROGRESS_CHANNEL_CAPACITY: usize = 64;
+
 #[derive(Deserialize)]
 pub struct ShareRequest {
     path: String,
     initial_value: Option<U256>,
+    #[serde(default)]
+    mode: ImportMode,
+    /// Respond with a stream of import progress events (as Server-Sent
+    ///  Events) ending in a `done` event, instead of a single JSON body
+    #[serde(default)]
+    progress: bool,
 }
 
 #[derive(Serialize)]
 pub struct ShareResponse {
     ticket: String,
     hash: String,
+    format: BlobFormat,
     message: String,
 }
 
 pub async fn handler(
     State(state): State<NodeState>,
     Json(request): Json<ShareRequest>,
-) -> Result<impl IntoResponse, ShareError> {
+) -> Result<Response, ShareError> {
+    if !request.progress {
+        let response = share(&state, request, None).await?;
+        return Ok((axum::http::StatusCode::OK, Json(response)).into_response());
+    }
+
+    let (tx, rx) = mpsc::channel(PROGRESS_CHANNEL_CAPACITY);
+    let task = tokio::spawn(async move { share(&state, request, Some(tx)).await });
+
+    // Forward progress until the import drops its sender, then report
+    //  how the share as a whole went
+    let stream = futures::stream::unfold(
+        (rx, Some(task)),
+        |(mut rx, task): (mpsc::Receiver<ImportProgress>, Option<JoinHandle<_>>)| async move {
+            if let Some(progress) = rx.recv().await {
+                let event = Event::default()
+                    .event("progress")
+                    .json_data(&progress)
+                    .unwrap_or_else(|e| Event::default().event("error").data(e.to_string()));
+                return Some((Ok::<_, Infallible>(event), (rx, task)));
+            }
+            let event = match task?.await {
+                Ok(Ok(response)) => Event::default()
+                    .event("done")
+                    .json_data(&response)
+                    .unwrap_or_else(|e| Event::default().event("error").data(e.to_string())),
+                Ok(Err(e)) => Event::default().event("error").data(e.to_string()),
+                Err(e) => Event::default().event("error").data(e.to_string()),
+            };
+            Some((Ok(event), (rx, None)))
+        },
+    );
+
+    Ok(Sse::new(stream).into_response())
+}
+
+async fn share(
+    state: &NodeState,
+    request: ShareRequest,
+    progress: Option<mpsc::Sender<ImportProgress>>,
+) -> Result<ShareResponse, ShareError> {
     // Convert path string to PathBuf and get absolute path
     let path = PathBuf::from(request.path);
     let abs_path = std::path::absolute(&path).map_err(ShareError::Io)?;
 
-    // Read file content
-    let content = tokio::fs::read(&abs_path).await.map_err(ShareError::Io)?;
-
-    // Determine format based on file extension
-    let format = BlobFormat::Raw;
-
-    // Use blobs_service instead of blob_service
-    let hash = state
+    // Directories are shared as a collection of their files
+    let metadata = tokio::fs::metadata(&abs_path)
+        .await
+        .map_err(ShareError::Io)?;
+    let (hash, format) = state
         .blobs_service()
-        .store_blob(content)
+        .import_path(abs_path.clone(), request.mode, progress)
         .await
         .map_err(ShareError::BlobOperation)?;
 
@@ -64,18 +117,27 @@ pub async fn handler(
         // Create pool with initial value
         state
             .tracker()
-            .create_pool(hash, Some(initial_value))
+            .create_pool(hash, format, Some(initial_value))
             .await
             .map_err(ShareError::Default)?;
     }
 
+    // peers in any pool backing the content hear that we have it
+    state.tracker().announce_have(hash).await;
+
     let hash_str = hash.to_string();
 
     let response = ShareResponse {
         ticket: ticket.to_string(),
         hash: hash_str,
+        format,
         message: format!(
-            "File '{}' has been added to the blob store and announced to the network{}",
+            "{} '{}' has been added to the blob store and announced to the network{}",
+            if metadata.is_dir() {
+                "Directory"
+            } else {
+                "File"
+            },
             abs_path.display(),
             if request.initial_value.is_some() {
                 " with initial pool value"
@@ -85,7 +147,7 @@ pub async fn handler(
         ),
     };
 
-    Ok((axum::http::StatusCode::OK, Json(response)))
+    Ok(response)
 }
 
 #[derive(Debug, thiserror::Error)]
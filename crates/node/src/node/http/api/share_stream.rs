@@ -3,12 +3,14 @@ use axum::{
     extract::{Json, Multipart, State},
     response::{IntoResponse, Response},
 };
-use bytes::Bytes;
-use futures::StreamExt;
+use futures::SinkExt;
 use iroh_blobs::{ticket::BlobTicket, BlobFormat};
 use serde::Serialize;
 use std::io;
 
+/// How many multipart chunks can be in flight to the store at once
+const UPLOAD_CHANNEL_CAPACITY: usize = 16;
+
 #[derive(Serialize)]
 pub struct ShareResponse {
     ticket: String,
@@ -29,33 +31,46 @@ pub async fn handler(
         return Err(ShareError::NoFile);
     };
 
-    // Read the field into chunks and collect them
-    let mut chunks = Vec::new();
-    while let Some(chunk) = field.chunk().await.map_err(ShareError::Multipart)? {
-        chunks.push(chunk.to_vec());
-    }
+    // Pipe chunks straight into the store as they arrive. The channel is
+    //  bounded, so a slow import stops us reading from the client.
+    let (mut tx, rx) = futures::channel::mpsc::channel(UPLOAD_CHANNEL_CAPACITY);
+    let blobs_service = state.blobs_service().clone();
+    let store = tokio::spawn(async move { blobs_service.store_stream(rx).await });
 
-    // Create a stream from the collected chunks
-    let stream = futures::stream::iter(chunks)
-        .map(|chunk| Ok(Bytes::from(chunk)))
-        .boxed();
+    loop {
+        match field.chunk().await {
+            Ok(Some(chunk)) => {
+                if tx.send(Ok(chunk)).await.is_err() {
+                    // the import bailed, its error is reported below
+                    break;
+                }
+            }
+            Ok(None) => break,
+            Err(e) => {
+                // abort the import rather than storing a truncated blob
+                let _ = tx.send(Err(io::Error::other(e.to_string()))).await;
+                return Err(ShareError::Multipart(e));
+            }
+        }
+    }
+    drop(tx);
 
-    // Use store_stream with our chunk stream
-    let hash = state
-        .blobs_service()
-        .store_stream(stream)
+    let hash = store
         .await
+        .map_err(|e| ShareError::BlobOperation(e.into()))?
         .map_err(ShareError::BlobOperation)?;
 
     // Create ticket
     let node_id = state.endpoint().node_id();
     let ticket = BlobTicket::new(node_id.into(), hash, BlobFormat::Raw)
         .map_err(ShareError::BlobOperation)?;
+    state.tracker().announce_have(hash).await;
 
     let response = ShareResponse {
         ticket: ticket.to_string(),
         hash: hash.to_string(),
-        message: format!("File has been streamed to the blob store and announced to the network"),
+        message: "File has been streamed to the blob store and announced to the network"
+            .to_string(),
     };
 
     Ok((axum::http::StatusCode::OK, Json(response)))
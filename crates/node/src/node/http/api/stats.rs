@@ -0,0 +1,96 @@
+use alloy::primitives::Address;
+use axum::extract::{Json, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use iroh::NodeId;
+use iroh_blobs::Hash;
+use serde::Serialize;
+
+use crate::node::iroh::Counters;
+use crate::node::State as NodeState;
+
+#[derive(Serialize)]
+pub struct PeerStats {
+    node_id: NodeId,
+    #[serde(flatten)]
+    counters: Counters,
+}
+
+/// The traffic for one piece of content, and every pool it backs
+#[derive(Serialize)]
+pub struct PoolStats {
+    hash: Hash,
+    addresses: Vec<Address>,
+    #[serde(flatten)]
+    counters: Counters,
+}
+
+#[derive(Serialize)]
+pub struct StatsResponse {
+    totals: Counters,
+    peers: Vec<PeerStats>,
+    pools: Vec<PoolStats>,
+}
+
+/// What we've served and fetched, by peer and by pool, busiest first.
+///  A pool's figures are the traffic for the content backing it, so
+///  pools backed by the same content are reported together, once.
+pub async fn handler(State(state): State<NodeState>) -> Result<impl IntoResponse, StatsError> {
+    let ledger = state.blobs_service().accounting().ledger().await;
+
+    let mut totals = ledger.evicted;
+    let mut peers = ledger
+        .peers
+        .into_iter()
+        .map(|(node_id, counters)| {
+            totals += counters;
+            PeerStats { node_id, counters }
+        })
+        .collect::<Vec<_>>();
+    peers.sort_by_key(|peer| std::cmp::Reverse(peer.counters.traffic()));
+
+    let mut pools = Vec::new();
+    for (hash, memberships) in state.tracker().blob_pools().await? {
+        if memberships.is_empty() {
+            continue;
+        }
+        pools.push(PoolStats {
+            hash,
+            addresses: memberships
+                .into_iter()
+                .map(|membership| membership.address)
+                .collect(),
+            counters: ledger.content.get(&hash).copied().unwrap_or_default(),
+        });
+    }
+    pools.sort_by_key(|pool| std::cmp::Reverse(pool.counters.traffic()));
+
+    Ok((
+        StatusCode::OK,
+        Json(StatsResponse {
+            totals,
+            peers,
+            pools,
+        }),
+    ))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StatsError {
+    #[error(transparent)]
+    Default(#[from] anyhow::Error),
+}
+
+impl IntoResponse for StatsError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            StatsError::Default(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let body = Json(serde_json::json!({
+            "error": self.to_string(),
+        }));
+
+        (status, body).into_response()
+    }
+}
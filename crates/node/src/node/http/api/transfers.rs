@@ -0,0 +1,78 @@
+use axum::extract::{Json, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::node::iroh::TransferInfo;
+use crate::node::State as NodeState;
+
+#[derive(Serialize)]
+pub struct TransfersResponse {
+    transfers: Vec<TransferInfo>,
+}
+
+#[derive(Serialize)]
+pub struct CancelResponse {
+    id: Uuid,
+    message: String,
+}
+
+/// Running downloads, oldest first, then the most recently finished
+pub async fn list_handler(State(state): State<NodeState>) -> impl IntoResponse {
+    let transfers = state.blobs_service().transfers().list().await;
+    (StatusCode::OK, Json(TransfersResponse { transfers }))
+}
+
+pub async fn get_handler(
+    State(state): State<NodeState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, TransferError> {
+    let transfer = state
+        .blobs_service()
+        .transfers()
+        .get(id)
+        .await
+        .ok_or(TransferError::NotFound(id))?;
+    Ok((StatusCode::OK, Json(transfer)))
+}
+
+/// Stop a running download. Whatever it already fetched stays in the
+///  store until GC gets to it.
+pub async fn cancel_handler(
+    State(state): State<NodeState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, TransferError> {
+    if !state.blobs_service().transfers().cancel(id).await {
+        return Err(TransferError::NotRunning(id));
+    }
+    Ok((
+        StatusCode::OK,
+        Json(CancelResponse {
+            id,
+            message: format!("Cancelled transfer {}", id),
+        }),
+    ))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransferError {
+    #[error("transfer not found: {0}")]
+    NotFound(Uuid),
+    #[error("no running transfer {0}")]
+    NotRunning(Uuid),
+}
+
+impl IntoResponse for TransferError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            TransferError::NotFound(_) | TransferError::NotRunning(_) => StatusCode::NOT_FOUND,
+        };
+
+        let body = Json(serde_json::json!({
+            "error": self.to_string(),
+        }));
+
+        (status, body).into_response()
+    }
+}
@@ -0,0 +1,148 @@
+use axum::body::Body;
+use axum::extract::{Json, Path, State};
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use iroh_blobs::ticket::BlobTicket;
+use iroh_blobs::BlobFormat;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::node::uploads::{UploadError, UploadInfo};
+use crate::node::State as NodeState;
+
+// Same header names as tus, so the offsets are visible on a HEAD
+const UPLOAD_OFFSET: HeaderName = HeaderName::from_static("upload-offset");
+const UPLOAD_LENGTH: HeaderName = HeaderName::from_static("upload-length");
+
+#[derive(Deserialize)]
+pub struct CreateUploadRequest {
+    length: u64,
+}
+
+#[derive(Serialize)]
+pub struct FinalizeUploadResponse {
+    ticket: String,
+    hash: String,
+    format: BlobFormat,
+    message: String,
+}
+
+fn upload_response(status: StatusCode, info: UploadInfo) -> Response {
+    (
+        status,
+        [
+            (UPLOAD_OFFSET, HeaderValue::from(info.offset)),
+            (UPLOAD_LENGTH, HeaderValue::from(info.length)),
+            (header::CACHE_CONTROL, HeaderValue::from_static("no-store")),
+        ],
+        Json(info),
+    )
+        .into_response()
+}
+
+/// Start an upload session for `length` bytes
+pub async fn create_handler(
+    State(state): State<NodeState>,
+    Json(request): Json<CreateUploadRequest>,
+) -> Result<Response, UploadsError> {
+    let info = state.uploads().create(request.length).await?;
+    let mut response = upload_response(StatusCode::CREATED, info);
+    if let Ok(location) = HeaderValue::from_str(&format!("/api/v0/uploads/{}", info.id)) {
+        response.headers_mut().insert(header::LOCATION, location);
+    }
+    Ok(response)
+}
+
+/// Report how far an upload has got
+pub async fn status_handler(
+    State(state): State<NodeState>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, UploadsError> {
+    let info = state.uploads().info(id).await?;
+    Ok(upload_response(StatusCode::OK, info))
+}
+
+/// Append the request body to an upload. The `Upload-Offset` header
+///  has to match where the upload currently stands.
+pub async fn patch_handler(
+    State(state): State<NodeState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, UploadsError> {
+    let offset = headers
+        .get(&UPLOAD_OFFSET)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        .ok_or(UploadsError::MissingOffset)?;
+    let info = state
+        .uploads()
+        .append(id, offset, body.into_data_stream())
+        .await?;
+    Ok(upload_response(StatusCode::OK, info))
+}
+
+/// Import a complete upload into the blob store
+pub async fn finalize_handler(
+    State(state): State<NodeState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, UploadsError> {
+    let (hash, format) = state.uploads().finalize(id, state.blobs_service()).await?;
+
+    let node_id = state.endpoint().node_id();
+    let ticket =
+        BlobTicket::new(node_id.into(), hash, format).map_err(UploadsError::BlobOperation)?;
+    state.tracker().announce_have(hash).await;
+
+    let response = FinalizeUploadResponse {
+        ticket: ticket.to_string(),
+        hash: hash.to_string(),
+        format,
+        message: format!("Upload {} has been added to the blob store", id),
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Abandon an upload and drop its partial data
+pub async fn delete_handler(
+    State(state): State<NodeState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, UploadsError> {
+    state.uploads().remove(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UploadsError {
+    #[error(transparent)]
+    Upload(#[from] UploadError),
+    #[error("missing or invalid Upload-Offset header")]
+    MissingOffset,
+    #[error("blob operation failed: {0}")]
+    BlobOperation(anyhow::Error),
+}
+
+impl IntoResponse for UploadsError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            UploadsError::Upload(UploadError::NotFound(_)) => StatusCode::NOT_FOUND,
+            UploadsError::Upload(UploadError::OffsetMismatch { .. })
+            | UploadsError::Upload(UploadError::Incomplete { .. }) => StatusCode::CONFLICT,
+            UploadsError::Upload(UploadError::TooLong(_)) => StatusCode::PAYLOAD_TOO_LARGE,
+            UploadsError::Upload(UploadError::Busy) => StatusCode::LOCKED,
+            UploadsError::Upload(UploadError::TooManySessions(_)) => StatusCode::TOO_MANY_REQUESTS,
+            UploadsError::Upload(UploadError::OverQuota { .. }) => StatusCode::INSUFFICIENT_STORAGE,
+            UploadsError::MissingOffset => StatusCode::BAD_REQUEST,
+            UploadsError::Upload(UploadError::Io(_))
+            | UploadsError::Upload(UploadError::Default(_))
+            | UploadsError::BlobOperation(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let body = Json(serde_json::json!({
+            "error": self.to_string(),
+        }));
+
+        (status, body).into_response()
+    }
+}
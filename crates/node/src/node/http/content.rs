@@ -0,0 +1,279 @@
+use anyhow::{anyhow, Result};
+use axum::body::Body;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::Response;
+use iroh_blobs::Hash;
+
+use crate::node::iroh::BlobsService;
+
+/// How many leading bytes `sniff_content_type` wants to look at
+pub const SNIFF_LEN: u64 = 512;
+
+/// Magic numbers for formats worth handing a browser directly
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"\x1a\x45\xdf\xa3", "video/webm"),
+    (b"OggS", "audio/ogg"),
+    (b"fLaC", "audio/flac"),
+    (b"ID3", "audio/mpeg"),
+    (b"\0asm", "application/wasm"),
+];
+
+/// Guess a content type from the first few hundred bytes of a blob.
+///  Blobs are untrusted and served from the same origin as the API, so
+///  markup -- html, svg, xml -- is never sniffed as such; it comes out as
+///  plain bytes rather than something a browser would render.
+pub fn sniff_content_type(data: &[u8]) -> &'static str {
+    for (magic, content_type) in SIGNATURES {
+        if data.starts_with(magic) {
+            return content_type;
+        }
+    }
+    // containers with the interesting bit a little way in
+    if data.len() >= 12 {
+        match (&data[0..4], &data[4..8], &data[8..12]) {
+            (b"RIFF", _, b"WEBP") => return "image/webp",
+            (b"RIFF", _, b"WAVE") => return "audio/wav",
+            (_, b"ftyp", _) => return "video/mp4",
+            _ => {}
+        }
+    }
+    if data.trim_ascii_start().starts_with(b"<") {
+        return "application/octet-stream";
+    }
+    if looks_like_text(data) {
+        return "text/plain; charset=utf-8";
+    }
+    "application/octet-stream"
+}
+
+/// UTF-8 without control characters, allowing for a code point cut
+///  off at the end of the sample
+fn looks_like_text(data: &[u8]) -> bool {
+    let valid = match std::str::from_utf8(data) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => {
+            // only the tail is incomplete
+            std::str::from_utf8(&data[..e.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return false,
+    };
+    !valid
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t' | '\x0c'))
+}
+
+/// An inclusive byte range resolved against the size of a blob
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    /// Parse a `Range` header for a blob of `size` bytes.
+    ///  `Ok(None)` means serve the whole thing -- we don't do multipart
+    ///  responses, so several ranges are answered in full, as are headers
+    ///  we don't understand. `Err(())` means the range can't be satisfied.
+    pub fn parse(header: &str, size: u64) -> Result<Option<Self>, ()> {
+        let Some(spec) = header.trim().strip_prefix("bytes=") else {
+            return Ok(None);
+        };
+        if spec.contains(',') {
+            return Ok(None);
+        }
+        let Some((start, end)) = spec.trim().split_once('-') else {
+            return Ok(None);
+        };
+        let (start, end) = match (start.trim(), end.trim()) {
+            // the last `n` bytes
+            ("", suffix) => {
+                let Ok(suffix) = suffix.parse::<u64>() else {
+                    return Ok(None);
+                };
+                if suffix == 0 || size == 0 {
+                    return Err(());
+                }
+                (size.saturating_sub(suffix), size - 1)
+            }
+            (start, end) => {
+                let Ok(start) = start.parse::<u64>() else {
+                    return Ok(None);
+                };
+                let end = match end {
+                    "" => u64::MAX,
+                    end => match end.parse::<u64>() {
+                        Ok(end) if end >= start => end,
+                        _ => return Ok(None),
+                    },
+                };
+                if start >= size {
+                    return Err(());
+                }
+                (start, end.min(size - 1))
+            }
+        };
+        Ok(Some(Self { start, end }))
+    }
+}
+
+/// Blobs are content addressed, so a response never goes stale
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// Headers for anything we serve out of the store. The browser takes
+///  the content type at its word, and whatever it renders runs in a
+///  sandbox -- no scripts, and an origin of its own rather than the API's.
+pub fn content_security_headers(headers: &mut HeaderMap) {
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    headers.insert(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static("sandbox"),
+    );
+}
+
+/// Build a response for a complete raw blob, honouring `Range`,
+///  `If-Range` and `If-None-Match`. The ETag is the blob's hash. Without
+///  a `content_type` one is sniffed from the start of the blob.
+pub async fn blob_response(
+    blobs_service: &BlobsService,
+    hash: Hash,
+    headers: &HeaderMap,
+    content_type: Option<String>,
+) -> Result<Response> {
+    let size = blobs_service
+        .get_blob_size(hash)
+        .await?
+        .ok_or_else(|| anyhow!("blob {} is not complete", hash))?;
+    let etag = format!("\"{}\"", hash);
+    let header_str = |name| headers.get(name).and_then(|value| value.to_str().ok());
+
+    let not_modified = header_str(header::IF_NONE_MATCH).is_some_and(|tags| {
+        tags.split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag == etag)
+    });
+    if not_modified {
+        let mut response = Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, &etag)
+            .header(header::CACHE_CONTROL, IMMUTABLE)
+            .body(Body::empty())?;
+        content_security_headers(response.headers_mut());
+        return Ok(response);
+    }
+
+    let content_type = match content_type {
+        Some(content_type) => content_type,
+        None => {
+            let head = blobs_service.read_range(hash, 0, SNIFF_LEN).await?;
+            sniff_content_type(&head).to_string()
+        }
+    };
+    let builder = Response::builder()
+        .header(header::ETAG, &etag)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CACHE_CONTROL, IMMUTABLE)
+        .header(header::CONTENT_TYPE, content_type);
+
+    // a stale If-Range means the client's partial copy is no good, send it all
+    let if_range_ok = header_str(header::IF_RANGE).is_none_or(|tag| tag.trim() == etag);
+    let range = match header_str(header::RANGE) {
+        Some(range) if if_range_ok => ByteRange::parse(range, size),
+        _ => Ok(None),
+    };
+
+    let mut response = match range {
+        Ok(Some(range)) => {
+            let body = Body::from_stream(
+                blobs_service
+                    .read_blob_range(hash, range.start, range.len())
+                    .await?,
+            );
+            builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(
+                    header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", range.start, range.end, size),
+                )
+                .header(header::CONTENT_LENGTH, range.len())
+                .body(body)?
+        }
+        Ok(None) => {
+            let body = Body::from_stream(blobs_service.read_blob(hash).await?);
+            builder
+                .status(StatusCode::OK)
+                .header(header::CONTENT_LENGTH, size)
+                .body(body)?
+        }
+        Err(()) => builder
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", size))
+            .body(Body::empty())?,
+    };
+    content_security_headers(response.headers_mut());
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        let range = |start, end| Ok(Some(ByteRange { start, end }));
+        assert_eq!(ByteRange::parse("bytes=0-99", 1000), range(0, 99));
+        assert_eq!(ByteRange::parse("bytes=900-", 1000), range(900, 999));
+        assert_eq!(ByteRange::parse("bytes=-100", 1000), range(900, 999));
+        assert_eq!(ByteRange::parse("bytes=-5000", 1000), range(0, 999));
+        assert_eq!(ByteRange::parse("bytes=990-2000", 1000), range(990, 999));
+        assert_eq!(ByteRange::parse("bytes=1000-", 1000), Err(()));
+        assert_eq!(ByteRange::parse("bytes=-0", 1000), Err(()));
+        assert_eq!(ByteRange::parse("bytes=0-1,5-6", 1000), Ok(None));
+        assert_eq!(ByteRange::parse("bytes=5-1", 1000), Ok(None));
+        assert_eq!(ByteRange::parse("items=0-1", 1000), Ok(None));
+    }
+
+    #[test]
+    fn test_sniff_content_type() {
+        assert_eq!(sniff_content_type(b"\x89PNG\r\n\x1a\n...."), "image/png");
+        assert_eq!(
+            sniff_content_type(b"\0\0\0\x20ftypisom\0\0\x02\0"),
+            "video/mp4"
+        );
+        // markup is never handed to the browser to render
+        for markup in [
+            &b"  <!DOCTYPE html><html>"[..],
+            b"<html><script>alert(1)</script>",
+            b"<svg xmlns=\"http://www.w3.org/2000/svg\">",
+            b"<?xml version=\"1.0\"?>",
+        ] {
+            assert_eq!(sniff_content_type(markup), "application/octet-stream");
+        }
+        assert_eq!(
+            sniff_content_type("hello wörld\n".as_bytes()),
+            "text/plain; charset=utf-8"
+        );
+        // a multi-byte character cut off by the sample is still text
+        assert_eq!(
+            sniff_content_type(&"wörld".as_bytes()[..2]),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(
+            sniff_content_type(b"\x00\x01\x02\x03"),
+            "application/octet-stream"
+        );
+    }
+}
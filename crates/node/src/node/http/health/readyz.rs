@@ -1,3 +1,5 @@
+use std::net::SocketAddr;
+
 use axum::extract::State;
 use axum::Json;
 use serde::Serialize;
@@ -8,13 +10,30 @@ use crate::node::State as NodeState;
 pub struct ReadinessResponse {
     node_id: String,
     eth_address: String,
+    /// Sockets the endpoint is bound to, IPv4 first
+    bound_addrs: Vec<SocketAddr>,
+    /// Addresses peers can dial us on directly, as far as we know
+    direct_addrs: Vec<SocketAddr>,
 }
 
 pub async fn handler(state: State<NodeState>) -> Json<ReadinessResponse> {
     let node_id = state.iroh_node_id();
     let eth_address = state.eth_address();
+    let endpoint = state.endpoint();
+    let (bound_v4, bound_v6) = endpoint.bound_sockets();
+    let direct_addrs = endpoint
+        .direct_addresses()
+        .get()
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|direct| direct.addr)
+        .collect();
     Json(ReadinessResponse {
         node_id: node_id.to_string(),
         eth_address: eth_address.to_string(),
+        bound_addrs: std::iter::once(bound_v4).chain(bound_v6).collect(),
+        direct_addrs,
     })
 }
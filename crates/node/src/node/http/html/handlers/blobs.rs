@@ -1,28 +1,91 @@
 use askama::Template;
 use askama_axum::IntoResponse;
-use axum::extract::State;
-use iroh_blobs::store::ReadableStore;
+use axum::extract::{Query, State};
 
+use crate::node::http::listing::{self, format_timestamp, BlobEntry, Completeness, ListQuery};
 use crate::node::State as NodeState;
 
+struct BlobRow {
+    entry: BlobEntry,
+    created: String,
+}
+
 #[derive(Template)]
 #[template(path = "blobs.html")]
 struct BlobsTemplate {
-    blobs: Vec<String>,
+    blobs: Vec<BlobRow>,
+    total: usize,
+    first: usize,
+    last: usize,
+    /// the filters as a query string, for the page links
+    filters: String,
+    prev: Option<usize>,
+    next: Option<usize>,
+    limit: usize,
+    error: Option<String>,
 }
 
 #[axum::debug_handler]
-pub async fn blobs_handler(State(state): State<NodeState>) -> impl IntoResponse {
-    let blobs = state
-        .blobs_service()
-        .get_inner_blobs()
-        .store()
-        .blobs()
-        .await
-        .unwrap_or_else(|_| Box::new(std::iter::empty()))
-        .filter_map(|r| r.ok())
-        .map(|h| h.to_string())
+pub async fn blobs_handler(
+    State(state): State<NodeState>,
+    Query(query): Query<ListQuery>,
+) -> impl IntoResponse {
+    let mut filters = String::new();
+    if let Some(status) = query.status {
+        let status = match status {
+            Completeness::Complete => "complete",
+            Completeness::Partial => "partial",
+        };
+        filters.push_str(&format!("&status={}", status));
+    }
+    if let Some(pooled) = query.pooled {
+        filters.push_str(&format!("&pooled={}", pooled));
+    }
+
+    let (listing, error) = match listing::list_blobs(&state, &query).await {
+        Ok(listing) => (Some(listing), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    let limit = query.limit();
+    let Some(listing) = listing else {
+        return BlobsTemplate {
+            blobs: vec![],
+            total: 0,
+            first: 0,
+            last: 0,
+            filters,
+            prev: None,
+            next: None,
+            limit,
+            error,
+        };
+    };
+
+    let first = listing.offset + 1;
+    let last = listing.offset + listing.blobs.len();
+    let prev = (listing.offset > 0).then(|| listing.offset.saturating_sub(limit));
+    let next = (last < listing.total).then_some(last);
+    let blobs = listing
+        .blobs
+        .into_iter()
+        .map(|entry| BlobRow {
+            created: entry
+                .created
+                .map(format_timestamp)
+                .unwrap_or_else(|| "-".to_string()),
+            entry,
+        })
         .collect();
 
-    BlobsTemplate { blobs }
+    BlobsTemplate {
+        blobs,
+        total: listing.total,
+        first,
+        last,
+        filters,
+        prev,
+        next,
+        limit,
+        error,
+    }
 }
@@ -0,0 +1,369 @@
+use std::collections::BTreeMap;
+
+use askama::Template;
+use askama_axum::IntoResponse;
+use axum::extract::{OriginalUri, Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{Redirect, Response};
+use iroh_blobs::format::collection::Collection;
+use iroh_blobs::{BlobFormat, Hash};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use serde::Deserialize;
+
+use crate::node::http::content;
+use crate::node::iroh::BlobsService;
+use crate::node::State as NodeState;
+
+/// Served in place of a listing when a directory has one
+const INDEX_FILE: &str = "index.html";
+
+/// Characters that can't appear as-is in a path segment of a link
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+#[derive(Deserialize)]
+pub struct GatewayPath {
+    hash: Hash,
+    #[serde(default)]
+    path: String,
+}
+
+struct DirectoryEntry {
+    name: String,
+    href: String,
+    is_dir: bool,
+    size: Option<u64>,
+}
+
+#[derive(Template)]
+#[template(path = "directory.html")]
+struct DirectoryTemplate {
+    hash: Hash,
+    path: String,
+    entries: Vec<DirectoryEntry>,
+}
+
+/// What a path into a collection turned out to be
+#[derive(Debug, PartialEq)]
+enum Resolved<'a> {
+    File(&'a str, Hash),
+    /// A directory asked for without its trailing slash
+    Redirect,
+    /// The children of a directory, relative to it, when it has no index
+    Listing(Vec<(&'a str, Hash)>),
+}
+
+/// Find `path` in a collection. Only whole segments are matched, and
+///  paths with empty, `.` or `..` segments don't resolve at all, so
+///  neither a request nor an oddly named entry can step outside the
+///  directory it's in.
+fn resolve<'a>(
+    collection: &'a Collection,
+    path: &str,
+    trailing_slash: bool,
+) -> Result<Resolved<'a>, GatewayError> {
+    let path = path.trim_matches('/');
+    let traverses = !path.is_empty()
+        && path
+            .split('/')
+            .any(|segment| matches!(segment, "" | "." | ".."));
+    if traverses {
+        return Err(GatewayError::NoSuchPath(path.to_string()));
+    }
+
+    // a file by that name
+    if let Some((name, hash)) = collection.iter().find(|(name, _)| name == path) {
+        return Ok(Resolved::File(name, *hash));
+    }
+
+    // otherwise a directory -- anything underneath it
+    let prefix = if path.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", path)
+    };
+    let children = collection
+        .iter()
+        .filter_map(|(name, hash)| Some((name.strip_prefix(&prefix)?, *hash)))
+        .collect::<Vec<_>>();
+    if children.is_empty() {
+        return Err(GatewayError::NoSuchPath(path.to_string()));
+    }
+    // relative links in the listing or the index need the trailing slash
+    if !trailing_slash {
+        return Ok(Resolved::Redirect);
+    }
+    if let Some((_, index_hash)) = children.iter().find(|(name, _)| *name == INDEX_FILE) {
+        return Ok(Resolved::File(INDEX_FILE, *index_hash));
+    }
+    Ok(Resolved::Listing(children))
+}
+
+/// The type to serve a collection file as, going by its name. `None`
+///  leaves it to sniffing. Everything we serve is sandboxed, so html
+///  renders but its scripts don't run, which is enough for a static
+///  site. Other types that carry script -- javascript, svg and the rest
+///  of the xml family -- are served as plain bytes instead.
+fn content_type_for(name: &str) -> Option<String> {
+    let mime = mime_guess::from_path(name).first()?;
+    let active = matches!(
+        mime.subtype().as_str(),
+        "javascript" | "x-javascript" | "ecmascript" | "xml" | "xsl" | "xslt"
+    ) || mime.suffix().is_some_and(|suffix| suffix == "xml");
+    if active {
+        return Some("application/octet-stream".to_string());
+    }
+    Some(mime.to_string())
+}
+
+/// Read-only gateway onto blobs and collections. Raw blobs are served
+///  at `/blob/:hash`; files in a collection at `/blob/:hash/*path`,
+///  with directories rendered as listings unless they hold an index.html.
+///  Content we don't hold is pulled from pool peers first.
+pub async fn gateway_handler(
+    State(state): State<NodeState>,
+    Path(GatewayPath { hash, path }): Path<GatewayPath>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<Response, GatewayError> {
+    state
+        .tracker()
+        .pull_blob(hash)
+        .await
+        .map_err(GatewayError::NotFound)?;
+    let format = state
+        .tracker()
+        .stored_format(hash)
+        .await
+        .map_err(GatewayError::Default)?;
+
+    if format == BlobFormat::Raw {
+        let path = path.trim_matches('/');
+        if !path.is_empty() {
+            return Err(GatewayError::NoSuchPath(path.to_string()));
+        }
+        return Ok(content::blob_response(state.blobs_service(), hash, &headers, None).await?);
+    }
+
+    let collection = state
+        .blobs_service()
+        .get_collection(hash)
+        .await
+        .map_err(GatewayError::Default)?;
+
+    let children = match resolve(&collection, &path, uri.path().ends_with('/'))? {
+        Resolved::File(name, file_hash) => {
+            return serve_file(state.blobs_service(), name, file_hash, &headers).await
+        }
+        Resolved::Redirect => {
+            return Ok(Redirect::permanent(&format!("{}/", uri.path())).into_response())
+        }
+        Resolved::Listing(children) => children,
+    };
+
+    // immediate children only, sub directories collapsed into one entry
+    let mut listing = BTreeMap::new();
+    for (name, child_hash) in children {
+        match name.split_once('/') {
+            Some((dir, _)) => {
+                listing.entry(dir.to_string()).or_insert(None);
+            }
+            None => {
+                listing.insert(name.to_string(), Some(child_hash));
+            }
+        }
+    }
+    let mut entries = Vec::with_capacity(listing.len());
+    for (name, child_hash) in listing {
+        let encoded = utf8_percent_encode(&name, SEGMENT).to_string();
+        let entry = match child_hash {
+            Some(child_hash) => DirectoryEntry {
+                size: state
+                    .blobs_service()
+                    .get_blob_size(child_hash)
+                    .await
+                    .map_err(GatewayError::Default)?,
+                href: encoded,
+                is_dir: false,
+                name,
+            },
+            None => DirectoryEntry {
+                size: None,
+                href: format!("{}/", encoded),
+                is_dir: true,
+                name,
+            },
+        };
+        entries.push(entry);
+    }
+    // directories first, the way most listings do it
+    entries.sort_by_key(|entry| !entry.is_dir);
+
+    let path = path.trim_matches('/');
+    Ok(DirectoryTemplate {
+        hash,
+        path: if path.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", path)
+        },
+        entries,
+    }
+    .into_response())
+}
+
+async fn serve_file(
+    blobs_service: &BlobsService,
+    name: &str,
+    hash: Hash,
+    headers: &HeaderMap,
+) -> Result<Response, GatewayError> {
+    // go by the file name, and sniff the content when that doesn't help
+    let content_type = content_type_for(name);
+    Ok(content::blob_response(blobs_service, hash, headers, content_type).await?)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GatewayError {
+    #[error(transparent)]
+    Default(#[from] anyhow::Error),
+    #[error("content not found: {0}")]
+    NotFound(anyhow::Error),
+    #[error("no such path: /{0}")]
+    NoSuchPath(String),
+}
+
+impl IntoResponse for GatewayError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            GatewayError::NotFound(_) | GatewayError::NoSuchPath(_) => StatusCode::NOT_FOUND,
+            GatewayError::Default(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::header;
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::node::iroh::RetentionPolicy;
+
+    fn collection(names: &[&str]) -> Collection {
+        names
+            .iter()
+            .map(|name| (name.to_string(), Hash::new(name)))
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_index() {
+        let site = collection(&["index.html", "docs/index.html", "docs/a.md", "img/x.png"]);
+        assert_eq!(
+            resolve(&site, "", true).unwrap(),
+            Resolved::File(INDEX_FILE, Hash::new("index.html"))
+        );
+        assert_eq!(
+            resolve(&site, "docs/", true).unwrap(),
+            Resolved::File(INDEX_FILE, Hash::new("docs/index.html"))
+        );
+        assert_eq!(resolve(&site, "docs", false).unwrap(), Resolved::Redirect);
+        assert_eq!(
+            resolve(&site, "docs/a.md", false).unwrap(),
+            Resolved::File("docs/a.md", Hash::new("docs/a.md"))
+        );
+        // no index, so a listing
+        assert_eq!(
+            resolve(&site, "img/", true).unwrap(),
+            Resolved::Listing(vec![("x.png", Hash::new("img/x.png"))])
+        );
+        // a prefix that isn't a whole segment is not a directory
+        assert!(resolve(&site, "do", true).is_err());
+    }
+
+    #[test]
+    fn test_resolve_traversal() {
+        let entries = collection(&["a/secret", "../escape", "b/./c", "b//d"]);
+        for path in [
+            "a/../a/secret",
+            "../escape",
+            "b/./c",
+            "b//d",
+            "./a/secret",
+            "a/..",
+        ] {
+            assert!(
+                matches!(
+                    resolve(&entries, path, true),
+                    Err(GatewayError::NoSuchPath(_))
+                ),
+                "{} resolved",
+                path
+            );
+        }
+        assert!(resolve(&entries, "a/secret", false).is_ok());
+    }
+
+    #[test]
+    fn test_content_type_for() {
+        assert_eq!(content_type_for("index.html").as_deref(), Some("text/html"));
+        assert_eq!(content_type_for("x.png").as_deref(), Some("image/png"));
+        for active in ["app.js", "x.svg", "feed.xml", "page.xhtml"] {
+            assert_eq!(
+                content_type_for(active).as_deref(),
+                Some("application/octet-stream"),
+                "{}",
+                active
+            );
+        }
+        assert_eq!(content_type_for("README"), None);
+    }
+
+    #[tokio::test]
+    async fn test_served_headers() {
+        let path = std::env::temp_dir().join(format!("jax-gateway-{}", uuid::Uuid::new_v4()));
+        let endpoint = iroh::Endpoint::builder()
+            .relay_mode(iroh::RelayMode::Disabled)
+            .bind_addr_v4("127.0.0.1:0".parse().unwrap())
+            .bind()
+            .await
+            .unwrap();
+        let blobs_service =
+            BlobsService::load(&path, endpoint.clone(), RetentionPolicy { cache_quota: 0 })
+                .await
+                .unwrap();
+        let html = b"<html><script>fetch('/api/v0/pools')</script></html>";
+        let hash = blobs_service
+            .store_stream(futures::stream::iter([Ok(Bytes::from_static(html))]))
+            .await
+            .unwrap();
+
+        for (name, content_type) in [
+            ("index.html", "text/html"),
+            ("app.js", "application/octet-stream"),
+            ("README", "application/octet-stream"),
+        ] {
+            let response = serve_file(&blobs_service, name, hash, &HeaderMap::new())
+                .await
+                .unwrap();
+            let headers = response.headers();
+            assert_eq!(headers[header::CONTENT_TYPE], content_type, "{}", name);
+            assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+            assert_eq!(headers[header::CONTENT_SECURITY_POLICY], "sandbox");
+        }
+
+        endpoint.close().await;
+        let _ = std::fs::remove_dir_all(path);
+    }
+}
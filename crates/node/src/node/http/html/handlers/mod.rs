@@ -1,10 +1,12 @@
 mod blobs;
+mod gateway;
 mod index;
 mod pools;
 mod query;
 mod share;
 
 pub use blobs::blobs_handler;
+pub use gateway::gateway_handler;
 pub use index::index_handler;
 pub use pools::pools_handler;
 pub use query::query_handler;
@@ -9,10 +9,13 @@ use iroh_blobs::Hash;
 
 use crate::node::State as NodeState;
 
+/// (pool address, hash, balance, peers sorted by trust)
+type PoolEntry = (Address, Hash, U256, Vec<(NodeId, f64)>);
+
 #[derive(Template)]
 #[template(path = "pools.html")]
 struct PoolsTemplate {
-    pools: Vec<(Address, Hash, U256, Vec<(NodeId, f64)>)>,
+    pools: Vec<PoolEntry>,
     eth_balance: U256,
 }
 
@@ -31,7 +34,7 @@ pub async fn pools_handler(State(state): State<NodeState>) -> impl IntoResponse
         .await
         .unwrap_or_default();
 
-    let mut pools_vec: Vec<(Address, Hash, U256, Vec<(NodeId, f64)>)> = pools
+    let mut pools_vec: Vec<PoolEntry> = pools
         .into_iter()
         .map(|(key, peers)| {
             let mut peers_vec = peers.into_iter().collect::<Vec<_>>();
@@ -12,6 +12,10 @@ pub fn router(state: NodeState) -> Router<NodeState> {
         .route("/pools", get(handlers::pools_handler))
         .route("/share", get(handlers::share_handler))
         .route("/query", get(handlers::query_handler))
+        // read-only gateway onto blobs and collections
+        .route("/blob/:hash", get(handlers::gateway_handler))
+        .route("/blob/:hash/", get(handlers::gateway_handler))
+        .route("/blob/:hash/*path", get(handlers::gateway_handler))
         .with_state(state)
         // TODO: make this configurable
         .nest_service("/static", ServeDir::new("crates/node/static"))
@@ -0,0 +1,117 @@
+use std::cmp::Reverse;
+
+use anyhow::Result;
+use iroh_blobs::Hash;
+use serde::{Deserialize, Serialize};
+
+use crate::node::iroh::BlobTag;
+use crate::node::tracker::PoolMembership;
+use crate::node::State as NodeState;
+
+/// Page size when the caller doesn't ask for one
+pub const DEFAULT_LIMIT: usize = 50;
+/// Largest page we hand out in one go
+pub const MAX_LIMIT: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Completeness {
+    Complete,
+    Partial,
+}
+
+/// Which blobs to list, and which page of them
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ListQuery {
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+    pub status: Option<Completeness>,
+    /// Only blobs that do (or don't) back a pool
+    pub pooled: Option<bool>,
+}
+
+impl ListQuery {
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BlobEntry {
+    pub hash: Hash,
+    /// For a partial blob, the size we expect once it's complete
+    pub size: Option<u64>,
+    pub complete: bool,
+    pub tags: Vec<BlobTag>,
+    /// Seconds since the epoch -- unknown for content older than the access log
+    pub created: Option<u64>,
+    pub pools: Vec<PoolMembership>,
+    pub pinned: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BlobListing {
+    pub blobs: Vec<BlobEntry>,
+    /// How many blobs match the filters, across all pages
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+/// Everything we know about the blobs in the store, newest first
+pub async fn list_blobs(state: &NodeState, query: &ListQuery) -> Result<BlobListing> {
+    let blobs_service = state.blobs_service();
+    let mut tags = blobs_service.tags_by_hash().await?;
+    let mut pools = state.tracker().blob_pools().await?;
+
+    let mut matching = Vec::new();
+    for (hash, complete) in blobs_service.list_hashes().await? {
+        let status_matches = match query.status {
+            Some(Completeness::Complete) => complete,
+            Some(Completeness::Partial) => !complete,
+            None => true,
+        };
+        let pooled = pools.contains_key(&hash);
+        if status_matches && query.pooled.is_none_or(|want| want == pooled) {
+            let created = blobs_service
+                .access_record(&hash)
+                .await
+                .map(|record| record.created);
+            matching.push((hash, complete, created));
+        }
+    }
+    // newest first, then by hash so pages stay put between requests
+    matching.sort_by_key(|(hash, _, created)| (Reverse(*created), *hash));
+
+    let total = matching.len();
+    let limit = query.limit();
+    let mut blobs = Vec::new();
+    for (hash, complete, created) in matching.into_iter().skip(query.offset).take(limit) {
+        let tags = tags.remove(&hash).unwrap_or_default();
+        blobs.push(BlobEntry {
+            hash,
+            size: blobs_service.get_stored_size(hash).await?,
+            complete,
+            pinned: tags.contains(&BlobTag::Pinned(hash)),
+            tags,
+            created,
+            pools: pools.remove(&hash).unwrap_or_default(),
+        });
+    }
+
+    Ok(BlobListing {
+        blobs,
+        total,
+        offset: query.offset,
+        limit,
+    })
+}
+
+/// A timestamp the way the listings show it, to the minute in UTC
+pub fn format_timestamp(secs: u64) -> String {
+    match time::OffsetDateTime::from_unix_timestamp(secs as i64) {
+        Ok(at) => format!("{} {:02}:{:02} UTC", at.date(), at.hour(), at.minute()),
+        Err(_) => "-".to_string(),
+    }
+}
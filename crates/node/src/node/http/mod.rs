@@ -1,4 +1,6 @@
 mod api;
+mod content;
+mod listing;
 // mod error_handlers;
 mod health;
 mod html;
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use iroh::NodeId;
+use iroh_blobs::provider::{CustomEventSender, Event, EventSender};
+use iroh_blobs::Hash;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+/// How often counters are written out, if anything changed
+pub const ACCOUNTING_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+/// Most peers, and most pieces of content, the ledger keeps counters
+///  for. Past that the quietest one makes way for a newcomer.
+pub const MAX_LEDGER_ENTRIES: usize = 10_000;
+
+/// Traffic in each direction, and how much of it went wrong
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Counters {
+    pub served_bytes: u64,
+    pub served_requests: u64,
+    pub serve_failures: u64,
+    pub fetched_bytes: u64,
+    pub fetch_requests: u64,
+    pub fetch_failures: u64,
+}
+
+impl Counters {
+    /// Bytes moved either way
+    pub fn traffic(&self) -> u64 {
+        self.served_bytes + self.fetched_bytes
+    }
+
+    fn serve(&mut self, bytes: u64, ok: bool) {
+        self.served_bytes += bytes;
+        self.served_requests += 1;
+        self.serve_failures += !ok as u64;
+    }
+
+    fn fetch(&mut self, bytes: u64, ok: bool) {
+        self.fetched_bytes += bytes;
+        self.fetch_requests += 1;
+        self.fetch_failures += !ok as u64;
+    }
+}
+
+impl std::ops::AddAssign for Counters {
+    fn add_assign(&mut self, other: Self) {
+        self.served_bytes += other.served_bytes;
+        self.served_requests += other.served_requests;
+        self.serve_failures += other.serve_failures;
+        self.fetched_bytes += other.fetched_bytes;
+        self.fetch_requests += other.fetch_requests;
+        self.fetch_failures += other.fetch_failures;
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Ledger {
+    pub peers: HashMap<NodeId, Counters>,
+    /// By the hash asked for -- the root, for a collection
+    pub content: HashMap<Hash, Counters>,
+    /// What peers dropped to make room had to their name, so totals
+    ///  still add up
+    #[serde(default)]
+    pub evicted: Counters,
+}
+
+impl Ledger {
+    /// Counters for `peer` and for `hash`, making room for them first
+    fn entries(&mut self, peer: NodeId, hash: Hash) -> (&mut Counters, &mut Counters) {
+        if let Some(counters) = make_room(&mut self.peers, &peer, MAX_LEDGER_ENTRIES) {
+            self.evicted += counters;
+        }
+        make_room(&mut self.content, &hash, MAX_LEDGER_ENTRIES);
+        (
+            self.peers.entry(peer).or_default(),
+            self.content.entry(hash).or_default(),
+        )
+    }
+}
+
+/// If `key` would take `map` past `limit` entries, drop the entry with
+///  the least traffic and hand back its counters
+fn make_room<K: Eq + std::hash::Hash + Copy>(
+    map: &mut HashMap<K, Counters>,
+    key: &K,
+    limit: usize,
+) -> Option<Counters> {
+    if map.len() < limit || map.contains_key(key) {
+        return None;
+    }
+    let quietest = map
+        .iter()
+        .min_by_key(|(_, counters)| counters.traffic())
+        .map(|(key, _)| *key)?;
+    map.remove(&quietest)
+}
+
+/// Running totals of what we've served and fetched, by peer and by
+///  content, kept across restarts
+#[derive(Debug, Clone)]
+pub struct Accounting {
+    path: PathBuf,
+    ledger: Arc<RwLock<Ledger>>,
+    dirty: Arc<AtomicBool>,
+}
+
+impl Accounting {
+    pub async fn load(path: PathBuf) -> Result<Self> {
+        let ledger = match tokio::fs::read(&path).await {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|e| {
+                tracing::warn!(
+                    "node::iroh::accounting: ignoring unreadable ledger {}: {}",
+                    path.display(),
+                    e
+                );
+                Ledger::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ledger::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path,
+            ledger: Arc::new(RwLock::new(ledger)),
+            dirty: Arc::default(),
+        })
+    }
+
+    /// A request `peer` made of us for `hash`
+    pub async fn served(&self, peer: NodeId, hash: Hash, bytes: u64, ok: bool) {
+        let mut ledger = self.ledger.write().await;
+        let (peer, content) = ledger.entries(peer, hash);
+        peer.serve(bytes, ok);
+        content.serve(bytes, ok);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// A download of `hash` we made from `peer`
+    pub async fn fetched(&self, peer: NodeId, hash: Hash, bytes: u64, ok: bool) {
+        let mut ledger = self.ledger.write().await;
+        let (peer, content) = ledger.entries(peer, hash);
+        peer.fetch(bytes, ok);
+        content.fetch(bytes, ok);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    pub async fn ledger(&self) -> Ledger {
+        self.ledger.read().await.clone()
+    }
+
+    /// Write the counters out if they changed since last time
+    pub async fn save(&self) -> Result<()> {
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+        let data = serde_json::to_vec(&*self.ledger.read().await)?;
+        let tmp_path = self.path.with_extension("tmp");
+        let written = async {
+            tokio::fs::write(&tmp_path, data).await?;
+            tokio::fs::rename(&tmp_path, &self.path).await
+        }
+        .await;
+        if written.is_err() {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        Ok(written?)
+    }
+
+    /// Provider events for one connection, counted against `peer` and
+    ///  then passed on to `forward`
+    pub fn events(&self, peer: NodeId, forward: EventSender) -> ServeEvents {
+        ServeEvents {
+            accounting: self.clone(),
+            peer,
+            requests: Arc::default(),
+            forward,
+        }
+    }
+}
+
+/// Counts what the blobs provider sends over one connection. Events
+///  only name the connection, so each one gets its own sender.
+#[derive(Debug, Clone)]
+pub struct ServeEvents {
+    accounting: Accounting,
+    peer: NodeId,
+    /// What each request in flight asked for
+    requests: Arc<StdMutex<HashMap<u64, Hash>>>,
+    /// Whoever was listening to the provider before us
+    forward: EventSender,
+}
+
+impl ServeEvents {
+    async fn handle(&self, event: Event) {
+        let (request_id, bytes, ok) = match event {
+            Event::GetRequestReceived {
+                request_id, hash, ..
+            } => {
+                self.requests.lock().unwrap().insert(request_id, hash);
+                return;
+            }
+            Event::TransferCompleted {
+                request_id, stats, ..
+            } => (request_id, stats.send.total().size, true),
+            Event::TransferAborted {
+                request_id, stats, ..
+            } => {
+                let bytes = stats.map(|stats| stats.send.total().size);
+                (request_id, bytes.unwrap_or_default(), false)
+            }
+            _ => return,
+        };
+        let hash = self.requests.lock().unwrap().remove(&request_id);
+        if let Some(hash) = hash {
+            self.accounting.served(self.peer, hash, bytes, ok).await;
+        }
+    }
+}
+
+impl CustomEventSender for ServeEvents {
+    fn send(&self, event: Event) -> futures::future::BoxFuture<'static, ()> {
+        let this = self.clone();
+        Box::pin(async move {
+            let forwarded = event.clone();
+            this.forward.send(|| forwarded).await;
+            this.handle(event).await
+        })
+    }
+
+    fn try_send(&self, event: Event) {
+        // only progress comes this way, and we count whole transfers
+        self.forward.try_send(|| event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ledger_persists() {
+        let path =
+            std::env::temp_dir().join(format!("jax-accounting-{}.json", uuid::Uuid::new_v4()));
+        let accounting = Accounting::load(path.clone()).await.unwrap();
+        let peer = iroh::SecretKey::from_bytes(&[1u8; 32]).public();
+        let hash = Hash::new(b"content");
+
+        accounting.served(peer, hash, 100, true).await;
+        accounting.served(peer, hash, 10, false).await;
+        accounting.fetched(peer, hash, 50, true).await;
+        accounting.save().await.unwrap();
+
+        let ledger = Accounting::load(path.clone()).await.unwrap().ledger().await;
+        let expected = Counters {
+            served_bytes: 110,
+            served_requests: 2,
+            serve_failures: 1,
+            fetched_bytes: 50,
+            fetch_requests: 1,
+            fetch_failures: 0,
+        };
+        assert_eq!(ledger.peers[&peer], expected);
+        assert_eq!(ledger.content[&hash], expected);
+        let _ = std::fs::remove_file(path);
+    }
+
+    /// Counts whatever it's handed
+    #[derive(Debug, Clone, Default)]
+    struct Tally(Arc<std::sync::atomic::AtomicUsize>);
+
+    impl CustomEventSender for Tally {
+        fn send(&self, _: Event) -> futures::future::BoxFuture<'static, ()> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Box::pin(async {})
+        }
+
+        fn try_send(&self, _: Event) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_events_forwarded() {
+        let path =
+            std::env::temp_dir().join(format!("jax-accounting-{}.json", uuid::Uuid::new_v4()));
+        let accounting = Accounting::load(path).await.unwrap();
+        let peer = iroh::SecretKey::from_bytes(&[1u8; 32]).public();
+        let tally = Tally::default();
+
+        let events = accounting.events(peer, tally.clone().into());
+        events
+            .send(Event::ClientConnected { connection_id: 1 })
+            .await;
+        events.try_send(Event::ClientConnected { connection_id: 1 });
+        assert_eq!(tally.0.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_make_room() {
+        let counters = |bytes| Counters {
+            served_bytes: bytes,
+            ..Counters::default()
+        };
+        let mut map = HashMap::from([(1, counters(10)), (2, counters(5))]);
+
+        // known keys and spare room don't evict anything
+        assert_eq!(make_room(&mut map, &1, 2), None);
+        assert_eq!(make_room(&mut map, &3, 3), None);
+        // the quietest makes way
+        assert_eq!(make_room(&mut map, &3, 2), Some(counters(5)));
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec![&1]);
+    }
+}
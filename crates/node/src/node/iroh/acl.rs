@@ -0,0 +1,147 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use iroh::NodeId;
+use iroh_blobs::Hash;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+/// Who may fetch a piece of content from us. Content without an entry
+///  is served according to the serving policy -- or, if we only hold it
+///  as part of collections, according to theirs. Pool content is always
+///  public, whatever its entry says.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AclEntry {
+    /// Anyone may fetch it -- the node list is kept, but unused
+    pub public: bool,
+    pub nodes: BTreeSet<NodeId>,
+}
+
+impl AclEntry {
+    pub fn allows(&self, node_id: &NodeId) -> bool {
+        self.public || self.nodes.contains(node_id)
+    }
+}
+
+/// A change to an entry, creating it if there's none yet
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AclUpdate {
+    #[serde(default)]
+    pub public: Option<bool>,
+    #[serde(default)]
+    pub allow: Vec<NodeId>,
+    #[serde(default)]
+    pub revoke: Vec<NodeId>,
+}
+
+/// Access control lists for our content, persisted next to the store
+#[derive(Debug, Clone)]
+pub struct Acls {
+    path: PathBuf,
+    entries: Arc<RwLock<HashMap<Hash, AclEntry>>>,
+}
+
+impl Acls {
+    pub async fn load(path: PathBuf) -> Result<Self> {
+        // unlike the access log, losing these would quietly make private
+        //  content public -- refuse to start instead
+        let entries = match tokio::fs::read(&path).await {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path,
+            entries: Arc::new(RwLock::new(entries)),
+        })
+    }
+
+    pub async fn get(&self, hash: &Hash) -> Option<AclEntry> {
+        self.entries.read().await.get(hash).cloned()
+    }
+
+    pub async fn list(&self) -> HashMap<Hash, AclEntry> {
+        self.entries.read().await.clone()
+    }
+
+    /// Apply `update` to the entry for `hash`, and return the result
+    pub async fn update(&self, hash: Hash, update: AclUpdate) -> Result<AclEntry> {
+        let mut entries = self.entries.write().await;
+        let entry = entries.entry(hash).or_default();
+        if let Some(public) = update.public {
+            entry.public = public;
+        }
+        entry.nodes.extend(update.allow);
+        for node_id in &update.revoke {
+            entry.nodes.remove(node_id);
+        }
+        let entry = entry.clone();
+        self.save(&entries).await?;
+        Ok(entry)
+    }
+
+    /// Drop the entry for `hash`, if there is one
+    pub async fn remove(&self, hash: &Hash) -> Result<Option<AclEntry>> {
+        let mut entries = self.entries.write().await;
+        let entry = entries.remove(hash);
+        if entry.is_some() {
+            self.save(&entries).await?;
+        }
+        Ok(entry)
+    }
+
+    async fn save(&self, entries: &HashMap<Hash, AclEntry>) -> Result<()> {
+        let data = serde_json::to_vec(entries)?;
+        let tmp_path = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, data).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_update_and_reload() {
+        let path = std::env::temp_dir().join(format!("jax-acl-{}.json", uuid::Uuid::new_v4()));
+        let acls = Acls::load(path.clone()).await.unwrap();
+        let hash = Hash::new(b"private");
+        let alice = iroh::SecretKey::from_bytes(&[1u8; 32]).public();
+        let bob = iroh::SecretKey::from_bytes(&[2u8; 32]).public();
+
+        let entry = acls
+            .update(
+                hash,
+                AclUpdate {
+                    allow: vec![alice, bob],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(entry.allows(&alice) && entry.allows(&bob));
+
+        acls.update(
+            hash,
+            AclUpdate {
+                revoke: vec![bob],
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let reloaded = Acls::load(path.clone()).await.unwrap();
+        let entry = reloaded.get(&hash).await.unwrap();
+        assert!(entry.allows(&alice));
+        assert!(!entry.allows(&bob));
+        assert!(!entry.public);
+
+        assert!(reloaded.remove(&hash).await.unwrap().is_some());
+        assert!(reloaded.get(&hash).await.is_none());
+        let _ = std::fs::remove_file(path);
+    }
+}
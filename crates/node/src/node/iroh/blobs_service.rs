@@ -1,27 +1,184 @@
 use anyhow::{anyhow, Result};
 use bytes::Bytes;
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use iroh::Endpoint;
-use iroh_blobs::rpc::client::blobs::{BlobStatus, Reader};
-use iroh_blobs::util::SetTagOption;
-use iroh_blobs::{net_protocol::Blobs, store::fs::Store, ticket::BlobTicket, Hash};
-use std::path::Path;
+use iroh_blobs::format::collection::Collection;
+use iroh_blobs::get::db::DownloadProgress;
+use iroh_blobs::get::Stats;
+use iroh_blobs::hashseq::HashSeq;
+use iroh_blobs::provider::AddProgress;
+use iroh_blobs::rpc::client::blobs::{
+    BlobStatus, DownloadMode, DownloadOptions, ReadAtLen, Reader, WrapOption,
+};
+use iroh_blobs::store::{ExportFormat, ExportMode, GcConfig, ReadableStore as _, Store as _};
+use iroh_blobs::util::{SetTagOption, Tag};
+use iroh_blobs::{
+    net_protocol::Blobs, store::fs::Store, ticket::BlobTicket, BlobFormat, Hash, HashAndFormat,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, watch};
+use uuid::Uuid;
+
+use super::accounting::{Accounting, ACCOUNTING_SAVE_INTERVAL};
+use super::acl::Acls;
+use super::encryption::{plaintext_size, sealed_chunk, ContentKey, CHUNK_SIZE};
+use super::retention::{AccessLog, AccessRecord, BlobTag, GcReport, RetentionPolicy, GC_PERIOD};
+use super::tag_index::TagIndex;
+use super::transfers::{TransferState, Transfers};
+
+/// Where we keep track of when cached content was last used
+const ACCESS_LOG_NAME: &str = "access.json";
+/// Where we keep who may fetch private content
+const ACL_NAME: &str = "acl.json";
+/// Where we keep traffic counters
+const ACCOUNTING_NAME: &str = "accounting.json";
+
+/// How exported files relate to the store's copy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportLink {
+    /// An independent copy -- reflinked where the filesystem supports it
+    #[default]
+    Copy,
+    /// Hand the store's own file over where the filesystem lets us, and
+    ///  have the store read the blob from there on; copied otherwise.
+    ///  The file must not be edited or moved, or the stored blob goes
+    ///  bad with it.
+    Reference,
+}
+
+/// Hash a file on disk and check it is the content we expected
+async fn verify_file(path: &Path, expected: Hash) -> Result<()> {
+    let path = path.to_path_buf();
+    let actual = tokio::task::spawn_blocking(move || -> std::io::Result<Hash> {
+        let mut file = std::fs::File::open(&path)?;
+        let mut hasher = blake3::Hasher::new();
+        std::io::copy(&mut file, &mut hasher)?;
+        Ok(hasher.finalize().into())
+    })
+    .await??;
+    if actual != expected {
+        return Err(anyhow!(
+            "exported content hashed to {}, expected {}",
+            actual,
+            expected
+        ));
+    }
+    Ok(())
+}
+
+/// How a local path is brought into the store
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Copy the data into the store
+    #[default]
+    Copy,
+    /// Reference the file where it is. Cheaper, but the file must not
+    ///  change or move afterwards.
+    InPlace,
+}
+
+/// Progress of a path import. `id` identifies a single file within the
+///  import, as announced by `Found`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImportProgress {
+    Found { id: u64, name: String, size: u64 },
+    Progress { id: u64, offset: u64 },
+    Done { id: u64, hash: Hash },
+    AllDone { hash: Hash, format: BlobFormat },
+}
+
+/// Progress of a download. Each blob is announced once by `Found` (or
+///  `FoundLocal` if we had it already) and then referred to by its `id`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FetchProgress {
+    Connected,
+    FoundLocal { hash: Hash, size: u64 },
+    Found { id: u64, hash: Hash, size: u64 },
+    FoundHashSeq { hash: Hash, children: u64 },
+    Progress { id: u64, offset: u64 },
+    Done { id: u64 },
+    AllDone { stats: Stats },
+}
 
 /// Service that handles blob operations
 #[derive(Clone, Debug)]
 pub struct BlobsService {
     blobs: Arc<Blobs<Store>>,
+    transfers: Transfers,
+    policy: RetentionPolicy,
+    access: AccessLog,
+    acls: Acls,
+    accounting: Accounting,
+    tag_index: TagIndex,
+    /// bumped every time a GC round finishes
+    gc_rounds: watch::Receiver<u64>,
 }
 
 impl BlobsService {
-    /// Create a new blob service
-    pub async fn load(blobs_path: &Path, endpoint: Endpoint) -> Result<Self> {
+    /// Create a new blob service, and start GC
+    pub async fn load(
+        blobs_path: &Path,
+        endpoint: Endpoint,
+        policy: RetentionPolicy,
+    ) -> Result<Self> {
         let store = Store::load(blobs_path).await?;
         let blobs = Blobs::builder(store).build(&endpoint);
-        Ok(Self {
+        let (gc_tx, gc_rounds) = watch::channel(0u64);
+        blobs.start_gc(GcConfig {
+            period: GC_PERIOD,
+            done_callback: Some(Box::new(move || {
+                gc_tx.send_modify(|round| *round += 1);
+            })),
+        })?;
+        let access = AccessLog::load(blobs_path.join(ACCESS_LOG_NAME)).await?;
+        let acls = Acls::load(blobs_path.join(ACL_NAME)).await?;
+        let accounting = Accounting::load(blobs_path.join(ACCOUNTING_NAME)).await?;
+        let tag_index = TagIndex::default();
+        let mut tagged = Vec::new();
+        for item in blobs.store().tags().await? {
+            let (name, content) = item?;
+            if let Some(tag) = BlobTag::parse(&name) {
+                tag_index.set(tag, Some(content));
+                tagged.push(content);
+            }
+        }
+        tokio::spawn({
+            let accounting = accounting.clone();
+            async move {
+                let mut interval = tokio::time::interval(ACCOUNTING_SAVE_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    if let Err(e) = accounting.save().await {
+                        tracing::warn!(
+                            "node::iroh::blobs_service: failed to save accounting: {}",
+                            e
+                        );
+                    }
+                }
+            }
+        });
+        let this = Self {
             blobs: Arc::new(blobs),
-        })
+            transfers: Transfers::default(),
+            policy,
+            access,
+            acls,
+            accounting,
+            tag_index,
+            gc_rounds,
+        };
+        for content in tagged {
+            this.index_children(content).await;
+        }
+        Ok(this)
     }
 
     /// Store a stream as a blob
@@ -38,13 +195,127 @@ impl BlobsService {
             .finish()
             .await
             .map_err(|e| anyhow!(e))?;
+        self.retag(
+            outcome.tag,
+            BlobTag::UserShared(outcome.hash),
+            outcome.hash,
+            outcome.format,
+        )
+        .await?;
         Ok(outcome.hash)
     }
 
     /// Store a blob with the given format
     pub async fn store_blob(&self, data: Vec<u8>) -> Result<Hash> {
-        let hash = self.blobs.client().add_bytes(data).await?.hash;
-        Ok(hash)
+        let outcome = self.blobs.client().add_bytes(data).await?;
+        self.retag(
+            outcome.tag,
+            BlobTag::UserShared(outcome.hash),
+            outcome.hash,
+            outcome.format,
+        )
+        .await?;
+        Ok(outcome.hash)
+    }
+
+    /// Import a file or directory from the local filesystem without reading
+    ///  it into memory. A directory becomes a collection -- a hash seq with
+    ///  one child per file, named by its path relative to the directory.
+    ///  Progress is forwarded to `progress` if given; a slow receiver slows
+    ///  down the import rather than buffering.
+    pub async fn import_path(
+        &self,
+        path: PathBuf,
+        mode: ImportMode,
+        progress: Option<mpsc::Sender<ImportProgress>>,
+    ) -> Result<(Hash, BlobFormat)> {
+        let in_place = matches!(mode, ImportMode::InPlace);
+        let mut stream = self
+            .blobs
+            .client()
+            .add_from_path(path, in_place, SetTagOption::Auto, WrapOption::NoWrap)
+            .await?;
+        while let Some(event) = stream.next().await {
+            let event = match event? {
+                AddProgress::Found { id, name, size } => ImportProgress::Found { id, name, size },
+                AddProgress::Progress { id, offset } => ImportProgress::Progress { id, offset },
+                AddProgress::Done { id, hash } => ImportProgress::Done { id, hash },
+                AddProgress::AllDone { hash, format, tag } => {
+                    self.retag(tag, BlobTag::UserShared(hash), hash, format)
+                        .await?;
+                    if let Some(progress) = &progress {
+                        let _ = progress
+                            .send(ImportProgress::AllDone { hash, format })
+                            .await;
+                    }
+                    return Ok((hash, format));
+                }
+                AddProgress::Abort(e) => return Err(anyhow!(e)),
+            };
+            if let Some(progress) = &progress {
+                // the caller went away, keep importing regardless
+                let _ = progress.send(event).await;
+            }
+        }
+        Err(anyhow!("import ended before completing"))
+    }
+
+    /// Get the names and hashes of the entries in a collection
+    pub async fn get_collection(&self, hash: Hash) -> Result<Collection> {
+        self.blobs.client().get_collection(hash).await
+    }
+
+    /// Write content out to the filesystem. Collections are restored as
+    ///  a directory tree rooted at `destination`. Every file written is
+    ///  hashed again and removed if it doesn't match.
+    pub async fn export(
+        &self,
+        hash: Hash,
+        format: BlobFormat,
+        destination: PathBuf,
+        link: ExportLink,
+    ) -> Result<()> {
+        let files = match format {
+            BlobFormat::Raw => vec![(destination, hash)],
+            BlobFormat::HashSeq => {
+                let collection = self.get_collection(hash).await?;
+                let mut files = Vec::new();
+                for (name, child) in collection.iter() {
+                    let relative = Path::new(name);
+                    // names come from whoever shared it -- stay under `destination`
+                    if !relative
+                        .components()
+                        .all(|component| matches!(component, Component::Normal(_)))
+                    {
+                        return Err(anyhow!("refusing to export entry '{}'", name));
+                    }
+                    files.push((destination.join(relative), *child));
+                }
+                files
+            }
+        };
+
+        for (path, hash) in files {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            // the store reflinks copies where the filesystem lets it
+            let mode = match link {
+                ExportLink::Copy => ExportMode::Copy,
+                ExportLink::Reference => ExportMode::TryReference,
+            };
+            self.blobs
+                .client()
+                .export(hash, path.clone(), ExportFormat::Blob, mode)
+                .await?
+                .finish()
+                .await?;
+            if let Err(e) = verify_file(&path, hash).await {
+                let _ = tokio::fs::remove_file(&path).await;
+                return Err(e);
+            }
+        }
+        Ok(())
     }
 
     /// Get the stat of a blob
@@ -53,6 +324,54 @@ impl BlobsService {
         Ok(matches!(stat, BlobStatus::Complete { .. }))
     }
 
+    /// Check whether we hold all of some content -- for a hash seq
+    ///  that means the root and every one of its children
+    pub async fn has_content(&self, hash: Hash, format: BlobFormat) -> Result<bool> {
+        if !self.get_blob_stat(hash).await? {
+            return Ok(false);
+        }
+        if format == BlobFormat::Raw {
+            return Ok(true);
+        }
+        let root = self.blobs.client().read_to_bytes(hash).await?;
+        let hash_seq = HashSeq::try_from(root)?;
+        for child in hash_seq.iter() {
+            if !self.get_blob_stat(child).await? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Get the format we hold some content in. Content is only a hash seq
+    ///  if we tagged it as one when storing or downloading it.
+    pub async fn get_format(&self, hash: Hash) -> Result<BlobFormat> {
+        let mut tags = self.blobs.client().tags().list_hash_seq().await?;
+        while let Some(tag) = tags.next().await {
+            if tag?.hash == hash {
+                return Ok(BlobFormat::HashSeq);
+            }
+        }
+        Ok(BlobFormat::Raw)
+    }
+
+    /// Get the size of a blob, if we hold all of it
+    pub async fn get_blob_size(&self, hash: Hash) -> Result<Option<u64>> {
+        let stat = self.blobs.client().status(hash).await?;
+        match stat {
+            BlobStatus::Complete { size } => Ok(Some(size)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Read at most `len` bytes of a blob starting at `offset`
+    pub async fn read_range(&self, hash: Hash, offset: u64, len: u64) -> Result<Bytes> {
+        self.blobs
+            .client()
+            .read_at_to_bytes(hash, offset, ReadAtLen::AtMost(len))
+            .await
+    }
+
     // TODO: get ticket
 
     /// Get the underlying Blobs instance
@@ -60,19 +379,612 @@ impl BlobsService {
         &self.blobs
     }
 
-    /// Get a blob using a ticket
-    pub async fn download_blob(&self, ticket: &BlobTicket) -> Result<()> {
-        self.blobs
+    /// Download the content a ticket points at, held under `tag`
+    pub async fn download_blob(&self, ticket: &BlobTicket, tag: BlobTag) -> Result<()> {
+        self.download(ticket, tag, None).await?;
+        Ok(())
+    }
+
+    /// Download the content a ticket points at, held under `tag`,
+    ///  reporting progress as it goes. The ticket's relay and direct
+    ///  addresses are all tried.
+    pub async fn download(
+        &self,
+        ticket: &BlobTicket,
+        tag: BlobTag,
+        progress: Option<mpsc::Sender<FetchProgress>>,
+    ) -> Result<Stats> {
+        let (id, cancel) = self
+            .transfers
+            .start(
+                ticket.hash(),
+                ticket.format(),
+                vec![ticket.node_addr().node_id],
+            )
+            .await;
+        let result = tokio::select! {
+            result = self.run_download(id, ticket, tag, progress) => result,
+            _ = cancel.cancelled() => Err(anyhow!("download cancelled")),
+        };
+        let state = match &result {
+            Ok(_) => TransferState::Complete,
+            Err(_) if cancel.is_cancelled() => TransferState::Cancelled,
+            Err(e) => TransferState::Failed(e.to_string()),
+        };
+        // cancelling is on us, not the peer
+        if !cancel.is_cancelled() {
+            let bytes = result.as_ref().map(|stats| stats.bytes_read);
+            self.accounting
+                .fetched(
+                    ticket.node_addr().node_id,
+                    ticket.hash(),
+                    bytes.unwrap_or_default(),
+                    bytes.is_ok(),
+                )
+                .await;
+        }
+        self.transfers.finish(id, state).await;
+        result
+    }
+
+    async fn run_download(
+        &self,
+        id: Uuid,
+        ticket: &BlobTicket,
+        tag: BlobTag,
+        progress: Option<mpsc::Sender<FetchProgress>>,
+    ) -> Result<Stats> {
+        let options = DownloadOptions {
+            format: ticket.format(),
+            nodes: vec![ticket.node_addr().clone()],
+            tag: SetTagOption::Named(tag.name()),
+            mode: DownloadMode::Queued,
+        };
+        let mut stream = self
+            .blobs
             .client()
-            .download(ticket.hash(), ticket.node_addr().clone())
-            .await?
-            .finish()
+            .download_with_opts(ticket.hash(), options)
             .await?;
-        Ok(())
+        while let Some(event) = stream.next().await {
+            let event = match event? {
+                DownloadProgress::Connected => FetchProgress::Connected,
+                DownloadProgress::FoundLocal { hash, size, .. } => {
+                    self.transfers.found_local(id, size.value()).await;
+                    FetchProgress::FoundLocal {
+                        hash,
+                        size: size.value(),
+                    }
+                }
+                DownloadProgress::Found {
+                    id: blob,
+                    hash,
+                    size,
+                    ..
+                } => {
+                    self.transfers.found(id, blob, size).await;
+                    FetchProgress::Found {
+                        id: blob,
+                        hash,
+                        size,
+                    }
+                }
+                DownloadProgress::FoundHashSeq { hash, children } => {
+                    FetchProgress::FoundHashSeq { hash, children }
+                }
+                DownloadProgress::Progress { id: blob, offset } => {
+                    self.transfers.progress(id, blob, offset).await;
+                    FetchProgress::Progress { id: blob, offset }
+                }
+                DownloadProgress::Done { id: blob } => {
+                    self.transfers.done(id, blob).await;
+                    FetchProgress::Done { id: blob }
+                }
+                DownloadProgress::AllDone(stats) => {
+                    // the downloader tags it once it's all here
+                    let content = HashAndFormat {
+                        hash: ticket.hash(),
+                        format: ticket.format(),
+                    };
+                    self.tag_index.set(tag, Some(content));
+                    self.index_children(content).await;
+                    self.access.touch(ticket.hash()).await;
+                    if let Some(progress) = &progress {
+                        let _ = progress
+                            .send(FetchProgress::AllDone {
+                                stats: stats.clone(),
+                            })
+                            .await;
+                    }
+                    return Ok(stats);
+                }
+                DownloadProgress::Abort(e) => return Err(anyhow!(e)),
+                DownloadProgress::InitialState(_) => continue,
+            };
+            if let Some(progress) = &progress {
+                // the caller went away, keep downloading regardless
+                let _ = progress.send(event).await;
+            }
+        }
+        Err(anyhow!("download ended before completing"))
+    }
+
+    /// Downloads in flight, and the last few to finish
+    pub fn transfers(&self) -> &Transfers {
+        &self.transfers
     }
 
     /// Read a blob from the given reader
     pub async fn read_blob(&self, hash: Hash) -> Result<Reader> {
         self.blobs.client().read(hash).await
     }
+
+    /// Read exactly `len` bytes of a blob starting at `offset`, as a stream
+    pub async fn read_blob_range(&self, hash: Hash, offset: u64, len: u64) -> Result<Reader> {
+        self.blobs
+            .client()
+            .read_at(hash, offset, ReadAtLen::Exact(len))
+            .await
+    }
+
+    /// The size of encrypted content once decrypted
+    pub async fn decrypted_size(&self, hash: Hash) -> Result<Option<u64>> {
+        match self.get_blob_size(hash).await? {
+            Some(size) => Ok(Some(plaintext_size(size)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Decrypt `len` bytes of encrypted content starting at `offset`,
+    ///  reading only the chunks the range falls in
+    pub async fn read_decrypted(
+        &self,
+        hash: Hash,
+        key: ContentKey,
+        offset: u64,
+        len: u64,
+    ) -> Result<impl Stream<Item = std::io::Result<Bytes>> + Send + 'static> {
+        let size = self
+            .get_blob_size(hash)
+            .await?
+            .ok_or_else(|| anyhow!("blob {} is not complete", hash))?;
+        let end = offset + len;
+        let first = offset / CHUNK_SIZE;
+        let last = end.saturating_sub(1).max(offset) / CHUNK_SIZE;
+        let this = self.clone();
+        Ok(futures::stream::iter(first..=last).then(move |index| {
+            let this = this.clone();
+            let key = key.clone();
+            async move {
+                let (at, sealed_len, last) = sealed_chunk(index, size);
+                let sealed = this
+                    .read_range(hash, at, sealed_len)
+                    .await
+                    .map_err(std::io::Error::other)?;
+                let chunk = key.open(index, last, &sealed)?;
+                let chunk_start = index * CHUNK_SIZE;
+                let from = (offset.saturating_sub(chunk_start) as usize).min(chunk.len());
+                let to = ((end - chunk_start) as usize).min(chunk.len());
+                Ok(chunk.slice(from..to))
+            }
+        }))
+    }
+
+    /// Write encrypted content out to `destination`, decrypted
+    pub async fn export_decrypted(
+        &self,
+        hash: Hash,
+        key: ContentKey,
+        destination: PathBuf,
+    ) -> Result<()> {
+        let size = self
+            .decrypted_size(hash)
+            .await?
+            .ok_or_else(|| anyhow!("blob {} is not complete", hash))?;
+        if let Some(parent) = destination.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let written = async {
+            let mut file = tokio::fs::File::create(&destination).await?;
+            let stream = self.read_decrypted(hash, key, 0, size).await?;
+            futures::pin_mut!(stream);
+            while let Some(chunk) = stream.next().await {
+                file.write_all(&chunk?).await?;
+            }
+            file.flush().await?;
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+        // don't leave half a file, or one that failed to authenticate
+        if written.is_err() {
+            let _ = tokio::fs::remove_file(&destination).await;
+        }
+        written
+    }
+
+    /// Swap the auto tag an add left behind for one of ours
+    async fn retag(&self, auto: Tag, tag: BlobTag, hash: Hash, format: BlobFormat) -> Result<()> {
+        self.tag(tag, hash, format).await?;
+        self.blobs.store().set_tag(auto, None).await?;
+        self.access.touch(hash).await;
+        Ok(())
+    }
+
+    /// Hold content under `tag`, replacing whatever it pointed at before
+    pub async fn tag(&self, tag: BlobTag, hash: Hash, format: BlobFormat) -> Result<()> {
+        self.blobs
+            .store()
+            .set_tag(tag.name(), Some(HashAndFormat { hash, format }))
+            .await?;
+        self.tag_index
+            .set(tag, Some(HashAndFormat { hash, format }));
+        self.index_children(HashAndFormat { hash, format }).await;
+        Ok(())
+    }
+
+    /// Note what a hash seq we just tagged is made of, unless we know
+    async fn index_children(&self, content: HashAndFormat) {
+        if content.format != BlobFormat::HashSeq || self.tag_index.has_children(&content.hash) {
+            return;
+        }
+        match self.hash_seq_children(content.hash).await {
+            Ok(children) => self.tag_index.set_children(content.hash, children),
+            Err(e) => tracing::debug!(
+                "node::iroh::blobs_service: can't read hash seq {}: {}",
+                content.hash,
+                e
+            ),
+        }
+    }
+
+    pub async fn untag(&self, tag: BlobTag) -> Result<()> {
+        self.blobs.store().set_tag(tag.name(), None).await?;
+        self.tag_index.set(tag, None);
+        Ok(())
+    }
+
+    /// Our tags holding `hash`, from memory rather than the store
+    pub fn tags_for(&self, hash: &Hash) -> Vec<(BlobTag, HashAndFormat)> {
+        self.tag_index.get(hash)
+    }
+
+    /// The hash seqs we hold under a tag that `hash` is part of
+    pub fn hash_seqs_containing(&self, hash: &Hash) -> Vec<Hash> {
+        self.tag_index.parents(hash)
+    }
+
+    /// All of our tags and what they point at
+    pub async fn tags(&self) -> Result<Vec<(BlobTag, HashAndFormat)>> {
+        let mut tags = Vec::new();
+        for item in self.blobs.store().tags().await? {
+            let (name, content) = item?;
+            if let Some(tag) = BlobTag::parse(&name) {
+                tags.push((tag, content));
+            }
+        }
+        Ok(tags)
+    }
+
+    /// Note that content was just used, for LRU eviction
+    pub async fn touch(&self, hash: Hash) {
+        self.access.touch(hash).await;
+    }
+
+    /// Who may fetch what from us
+    pub fn acls(&self) -> &Acls {
+        &self.acls
+    }
+
+    /// What we've served and fetched, by peer and by content
+    pub fn accounting(&self) -> &Accounting {
+        &self.accounting
+    }
+
+    pub async fn access_record(&self, hash: &Hash) -> Option<AccessRecord> {
+        self.access.record(hash).await
+    }
+
+    /// Every hash in the store, complete or not
+    pub async fn list_hashes(&self) -> Result<Vec<(Hash, bool)>> {
+        let store = self.blobs.store();
+        let mut hashes = Vec::new();
+        for hash in store.blobs().await? {
+            hashes.push((hash?, true));
+        }
+        for hash in store.partial_blobs().await? {
+            hashes.push((hash?, false));
+        }
+        Ok(hashes)
+    }
+
+    /// Hashes we hold in full, starting after `after`. The store can't
+    ///  start a listing partway, so every page walks the whole store and
+    ///  skips what's up to the cursor -- the store's ordering is what
+    ///  keeps pages from overlapping.
+    pub async fn complete_hashes_after(
+        &self,
+        after: Option<Hash>,
+    ) -> Result<impl Iterator<Item = Result<Hash>>> {
+        let hashes = self.blobs.store().blobs().await?;
+        Ok(hashes
+            .map(|hash| hash.map_err(Into::into))
+            .filter(move |hash| match (hash, after) {
+                (Ok(hash), Some(after)) => *hash > after,
+                _ => true,
+            }))
+    }
+
+    /// Size of a blob, whether or not we hold all of it yet
+    pub async fn get_stored_size(&self, hash: Hash) -> Result<Option<u64>> {
+        let stat = self.blobs.client().status(hash).await?;
+        match stat {
+            BlobStatus::Complete { size } => Ok(Some(size)),
+            BlobStatus::Partial { size } => Ok(Some(size.value())),
+            BlobStatus::NotFound => Ok(None),
+        }
+    }
+
+    /// Pin content so retention never evicts it
+    pub async fn pin(&self, hash: Hash, format: BlobFormat) -> Result<()> {
+        self.tag(BlobTag::Pinned(hash), hash, format).await
+    }
+
+    /// Drop a pin. Content nothing else holds goes back to the cache,
+    ///  to be evicted in its turn rather than straight away.
+    pub async fn unpin(&self, hash: Hash) -> Result<bool> {
+        let tags = self.tags().await?;
+        let Some(content) = tags
+            .iter()
+            .find(|(tag, _)| *tag == BlobTag::Pinned(hash))
+            .map(|(_, content)| *content)
+        else {
+            return Ok(false);
+        };
+        self.untag(BlobTag::Pinned(hash)).await?;
+        let still_held = tags
+            .iter()
+            .any(|(tag, other)| *tag != BlobTag::Pinned(hash) && other.hash == hash);
+        if !still_held {
+            self.tag(BlobTag::Cache(hash), content.hash, content.format)
+                .await?;
+            self.access.touch(hash).await;
+        }
+        Ok(true)
+    }
+
+    /// The children of a hash seq we hold the root of
+    pub async fn hash_seq_children(&self, hash: Hash) -> Result<Vec<Hash>> {
+        let root = self.blobs.client().read_to_bytes(hash).await?;
+        Ok(HashSeq::try_from(root)?.iter().collect())
+    }
+
+    /// Bytes on disk for some content, counting every child of a hash seq
+    pub async fn content_size(&self, hash: Hash, format: BlobFormat) -> Result<u64> {
+        let mut size = self.get_blob_size(hash).await?.unwrap_or_default();
+        if format == BlobFormat::HashSeq && size > 0 {
+            let data = self.read_range(hash, 0, size).await?;
+            for child in HashSeq::try_from(data)?.iter() {
+                size += self.get_blob_size(child).await?.unwrap_or_default();
+            }
+        }
+        Ok(size)
+    }
+
+    /// Evict cached content, least recently used first, until it fits in
+    ///  the cache quota. Pinned, shared and pool content is never touched.
+    pub async fn enforce_retention(&self) -> Result<Vec<(HashAndFormat, u64)>> {
+        let tags = self.tags().await?;
+        let protected = tags
+            .iter()
+            .filter(|(tag, _)| tag.is_protected())
+            .map(|(_, content)| content.hash)
+            .collect::<HashSet<_>>();
+
+        let mut cached = Vec::new();
+        let mut total = 0;
+        for (tag, content) in &tags {
+            if tag.is_protected() || protected.contains(&content.hash) {
+                continue;
+            }
+            let size = self.content_size(content.hash, content.format).await?;
+            let last_access = self.access.last_access(&content.hash).await;
+            total += size;
+            cached.push((*tag, *content, size, last_access));
+        }
+
+        let mut evicted = Vec::new();
+        if total <= self.policy.cache_quota {
+            self.save_access_log().await;
+            return Ok(evicted);
+        }
+        cached.sort_by_key(|(_, _, _, last_access)| *last_access);
+        for (tag, content, size, _) in cached {
+            if total <= self.policy.cache_quota {
+                break;
+            }
+            tracing::info!(
+                "node::iroh::blobs_service: evicting cached {} ({} bytes)",
+                content.hash,
+                size
+            );
+            self.untag(tag).await?;
+            self.access.forget(&content.hash).await;
+            total -= size;
+            evicted.push((content, size));
+        }
+        self.save_access_log().await;
+        Ok(evicted)
+    }
+
+    async fn save_access_log(&self) {
+        if let Err(e) = self.access.save().await {
+            tracing::warn!(
+                "node::iroh::blobs_service: failed to save access log: {}",
+                e
+            );
+        }
+    }
+
+    /// Apply retention, then wait for GC to sweep what it let go of
+    pub async fn gc(&self) -> Result<GcReport> {
+        let evicted = self.enforce_retention().await?;
+        self.await_gc().await?;
+
+        let mut report = GcReport::default();
+        for (content, size) in evicted {
+            // something may have pulled it again in the meantime
+            if self.get_blob_size(content.hash).await?.is_none() {
+                report.bytes_reclaimed += size;
+            }
+            report.evicted.push(content.hash);
+        }
+        Ok(report)
+    }
+
+    /// Wait for a GC round that started after now to finish. The round
+    ///  in flight may have marked before our changes, so wait out two.
+    pub async fn await_gc(&self) -> Result<()> {
+        let mut rounds = self.gc_rounds.clone();
+        let target = *rounds.borrow_and_update() + 2;
+        tokio::time::timeout(GC_PERIOD * 3, rounds.wait_for(|round| *round >= target))
+            .await
+            .map_err(|_| anyhow!("timed out waiting for gc"))??;
+        Ok(())
+    }
+
+    /// Drop every tag holding `hash`, ours or not, and wait for GC to
+    ///  sweep it. `None` if we don't have it at all. Anything still
+    ///  reachable from other content -- a file in a collection we keep,
+    ///  say -- stays, and doesn't count as reclaimed.
+    pub async fn delete(&self, hash: Hash) -> Result<Option<GcReport>> {
+        let store = self.blobs.store();
+        let mut format = None;
+        for item in store.tags().await? {
+            let (name, content) = item?;
+            if content.hash == hash {
+                format.get_or_insert(content.format);
+                store.set_tag(name.clone(), None).await?;
+                if let Some(tag) = BlobTag::parse(&name) {
+                    self.tag_index.set(tag, None);
+                }
+            }
+        }
+        let format = match format {
+            Some(format) => format,
+            None if self.get_stored_size(hash).await?.is_some() => self.get_format(hash).await?,
+            None => return Ok(None),
+        };
+        self.access.forget(&hash).await;
+        self.acls.remove(&hash).await?;
+
+        // everything the content is made of, sized before it goes
+        let mut parts = vec![(hash, self.get_stored_size(hash).await?.unwrap_or_default())];
+        if format == BlobFormat::HashSeq && parts[0].1 > 0 {
+            let data = self.read_range(hash, 0, parts[0].1).await?;
+            for child in HashSeq::try_from(data)?.iter() {
+                let size = self.get_stored_size(child).await?.unwrap_or_default();
+                parts.push((child, size));
+            }
+        }
+        self.await_gc().await?;
+
+        let mut report = GcReport::default();
+        for (part, size) in parts {
+            if self.get_stored_size(part).await?.is_none() {
+                report.evicted.push(part);
+                report.bytes_reclaimed += size;
+            }
+        }
+        Ok(Some(report))
+    }
+
+    /// Which of our tags hold each piece of content
+    pub async fn tags_by_hash(&self) -> Result<HashMap<Hash, Vec<BlobTag>>> {
+        let mut by_hash: HashMap<Hash, Vec<BlobTag>> = HashMap::new();
+        for (tag, content) in self.tags().await? {
+            by_hash.entry(content.hash).or_default().push(tag);
+        }
+        Ok(by_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::iroh::encrypt_stream;
+    use crate::node::iroh::test_utils::test_blobs_service;
+
+    #[tokio::test]
+    async fn test_verify_file() {
+        let path = std::env::temp_dir().join(format!("jax-export-{}", uuid::Uuid::new_v4()));
+        tokio::fs::write(&path, b"hello").await.unwrap();
+        assert!(verify_file(&path, Hash::new(b"hello")).await.is_ok());
+        assert!(verify_file(&path, Hash::new(b"goodbye")).await.is_err());
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+
+    #[tokio::test]
+    async fn test_read_decrypted() {
+        let (blobs_service, _dir) = test_blobs_service().await;
+        let data = (0..3 * CHUNK_SIZE + 500)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<_>>();
+        let key = ContentKey::generate();
+        let stream = futures::stream::iter([Ok(Bytes::from(data.clone()))]);
+        let hash = blobs_service
+            .store_stream(encrypt_stream(key.clone(), stream))
+            .await
+            .unwrap();
+        assert_eq!(
+            blobs_service.decrypted_size(hash).await.unwrap(),
+            Some(data.len() as u64)
+        );
+
+        let read = |offset: u64, len: u64| {
+            let blobs_service = blobs_service.clone();
+            let key = key.clone();
+            async move {
+                let stream = blobs_service
+                    .read_decrypted(hash, key, offset, len)
+                    .await
+                    .unwrap();
+                let parts = futures::TryStreamExt::try_collect::<Vec<_>>(stream)
+                    .await
+                    .unwrap();
+                parts.concat()
+            }
+        };
+        assert_eq!(read(0, data.len() as u64).await, data);
+        // a range straddling chunk boundaries
+        let (offset, len) = (CHUNK_SIZE - 10, CHUNK_SIZE + 20);
+        assert_eq!(
+            read(offset, len).await,
+            data[offset as usize..(offset + len) as usize]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_complete_hashes_after() {
+        let (blobs_service, _dir) = test_blobs_service().await;
+        let mut hashes = Vec::new();
+        for i in 0..5u8 {
+            let stream = futures::stream::iter([Ok(Bytes::from(vec![i; 16]))]);
+            hashes.push(blobs_service.store_stream(stream).await.unwrap());
+        }
+        hashes.sort();
+
+        let after = |after: Option<Hash>| {
+            let blobs_service = blobs_service.clone();
+            async move {
+                blobs_service
+                    .complete_hashes_after(after)
+                    .await
+                    .unwrap()
+                    .collect::<Result<Vec<_>>>()
+                    .unwrap()
+            }
+        };
+        assert_eq!(after(None).await, hashes);
+        assert_eq!(after(Some(hashes[1])).await, hashes[2..]);
+        assert!(after(Some(hashes[4])).await.is_empty());
+    }
 }
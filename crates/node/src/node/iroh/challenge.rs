@@ -0,0 +1,377 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use iroh::endpoint::{Connecting, Connection};
+use iroh::protocol::ProtocolHandler;
+use iroh::NodeId;
+use iroh_blobs::Hash;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::blobs_service::BlobsService;
+use super::codec::{read_message, write_message};
+use super::prober::Prober;
+
+/// ALPN for the jax proof-of-storage challenge protocol
+pub const CHALLENGE_ALPN: &[u8] = b"jax/challenge/0";
+
+/// How many random chunks we ask for in a single challenge
+const CHALLENGE_CHUNKS: usize = 4;
+/// Size of a blake3 chunk, the unit we challenge on
+const CHUNK_SIZE: u64 = 1024;
+/// Challenges and responses are tiny -- refuse anything bigger than this
+const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+/// The deadline is `rtt + local_read * LOCAL_READ_FACTOR + DEADLINE_SLACK`.
+///  A node that has to fetch the chunks from somebody else pays at least
+///  another round trip plus a verified read, which should blow through it.
+const LOCAL_READ_FACTOR: u32 = 8;
+const DEADLINE_SLACK: Duration = Duration::from_millis(200);
+/// Give up waiting on a response entirely after this long
+const HARD_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeRequest {
+    pub hash: Hash,
+    pub nonce: [u8; 32],
+    pub chunks: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ChallengeResponse {
+    /// keyed blake3 of each requested chunk, in request order
+    Proofs(Vec<[u8; 32]>),
+    /// the responder doesn't hold the content
+    Absent,
+}
+
+/// Outcome of challenging a node
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ChallengeResult {
+    /// Answered correctly within the deadline
+    Passed { elapsed: Duration },
+    /// Answered correctly, but too slowly to have read it from local storage
+    Slow {
+        elapsed: Duration,
+        deadline: Duration,
+    },
+    /// Answered with proofs that don't match the content
+    Wrong,
+    /// Told us it doesn't have the content
+    Absent,
+    /// We couldn't complete the exchange at all
+    Unreachable { reason: String },
+}
+
+impl ChallengeResult {
+    pub fn is_passed(&self) -> bool {
+        matches!(self, ChallengeResult::Passed { .. })
+    }
+}
+
+fn keyed_proof(nonce: &[u8; 32], data: &[u8]) -> [u8; 32] {
+    *blake3::keyed_hash(nonce, data).as_bytes()
+}
+
+/// Compute proofs for the requested chunks out of our own store
+async fn prove(
+    blobs_service: &BlobsService,
+    request: &ChallengeRequest,
+) -> Result<ChallengeResponse> {
+    let Some(size) = blobs_service.get_blob_size(request.hash).await? else {
+        return Ok(ChallengeResponse::Absent);
+    };
+    let mut proofs = Vec::with_capacity(request.chunks.len());
+    for chunk in &request.chunks {
+        // the index is the challenger's, so it may not even fit. An empty
+        //  blob still has the one (empty) chunk to prove.
+        let offset = chunk
+            .checked_mul(CHUNK_SIZE)
+            .filter(|offset| *offset < size.max(1))
+            .ok_or_else(|| anyhow!("chunk {} out of range for {}", chunk, request.hash))?;
+        let data = if size == 0 {
+            Default::default()
+        } else {
+            blobs_service
+                .read_range(request.hash, offset, CHUNK_SIZE)
+                .await?
+        };
+        proofs.push(keyed_proof(&request.nonce, &data));
+    }
+    Ok(ChallengeResponse::Proofs(proofs))
+}
+
+/// Serving side of the challenge protocol
+#[derive(Debug, Clone)]
+pub struct ChallengeProtocol {
+    blobs_service: BlobsService,
+}
+
+impl ChallengeProtocol {
+    pub fn new(blobs_service: BlobsService) -> Self {
+        Self { blobs_service }
+    }
+
+    async fn handle(&self, connecting: Connecting) -> Result<()> {
+        let connection = connecting.await?;
+        loop {
+            let (mut send, mut recv) = match connection.accept_bi().await {
+                Ok(streams) => streams,
+                // the challenger hung up, we're done
+                Err(_) => return Ok(()),
+            };
+            let request: ChallengeRequest = read_message(&mut recv, MAX_MESSAGE_SIZE).await?;
+            if request.chunks.len() > CHALLENGE_CHUNKS {
+                return Err(anyhow!("too many chunks in challenge"));
+            }
+            let response = prove(&self.blobs_service, &request).await?;
+            write_message(&mut send, &response, MAX_MESSAGE_SIZE).await?;
+            send.finish()?;
+        }
+    }
+}
+
+impl ProtocolHandler for ChallengeProtocol {
+    fn accept(&self, conn: Connecting) -> futures::future::BoxFuture<'static, Result<()>> {
+        let this = self.clone();
+        Box::pin(async move {
+            if let Err(e) = this.handle(conn).await {
+                tracing::debug!("node::iroh::challenge: connection failed: {}", e);
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Challenge `host` to prove it holds `hash`. We have to hold `hash`
+///  ourselves in order to check the answer.
+pub async fn challenge(
+    prober: &Prober,
+    host: &NodeId,
+    blobs_service: &BlobsService,
+    hash: Hash,
+) -> Result<ChallengeResult> {
+    let prepared = prepare(blobs_service, hash).await?;
+    let connection = match prober.connect(*host, CHALLENGE_ALPN).await {
+        Ok(connection) => connection,
+        Err(e) => {
+            return Ok(ChallengeResult::Unreachable {
+                reason: e.to_string(),
+            })
+        }
+    };
+    tracing::debug!(
+        "node::iroh::challenge: challenging {} for {} chunks {:?}",
+        host,
+        hash,
+        prepared.request.chunks
+    );
+    Ok(exchange(&connection, prepared, HARD_TIMEOUT).await)
+}
+
+/// A challenge, the answer we expect and how long it took us to read
+struct Prepared {
+    request: ChallengeRequest,
+    expected: Vec<[u8; 32]>,
+    local_read: Duration,
+}
+
+async fn prepare(blobs_service: &BlobsService, hash: Hash) -> Result<Prepared> {
+    let size = blobs_service
+        .get_blob_size(hash)
+        .await?
+        .ok_or_else(|| anyhow!("cannot challenge for {} without a local copy", hash))?;
+    let total_chunks = size.div_ceil(CHUNK_SIZE).max(1);
+
+    let mut rng = rand::rngs::StdRng::from_entropy();
+    let request = ChallengeRequest {
+        hash,
+        nonce: rng.gen(),
+        chunks: (0..CHALLENGE_CHUNKS)
+            .map(|_| rng.gen_range(0..total_chunks))
+            .collect(),
+    };
+
+    // work out the answer, and how long it takes to read locally
+    let t0 = Instant::now();
+    let expected = match prove(blobs_service, &request).await? {
+        ChallengeResponse::Proofs(proofs) => proofs,
+        ChallengeResponse::Absent => return Err(anyhow!("lost local copy of {}", hash)),
+    };
+    Ok(Prepared {
+        request,
+        expected,
+        local_read: t0.elapsed(),
+    })
+}
+
+/// Put the challenge to the peer on the other end of `connection` and
+///  judge its answer. A peer that doesn't answer within `timeout` is
+///  unreachable rather than slow.
+async fn exchange(
+    connection: &Connection,
+    prepared: Prepared,
+    timeout: Duration,
+) -> ChallengeResult {
+    let Prepared {
+        request,
+        expected,
+        local_read,
+    } = prepared;
+    let deadline = connection.rtt() + local_read * LOCAL_READ_FACTOR + DEADLINE_SLACK;
+
+    let exchange = async {
+        let (mut send, mut recv) = connection.open_bi().await?;
+        let t0 = Instant::now();
+        write_message(&mut send, &request, MAX_MESSAGE_SIZE).await?;
+        send.finish()?;
+        let response: ChallengeResponse = read_message(&mut recv, MAX_MESSAGE_SIZE).await?;
+        anyhow::Ok((response, t0.elapsed()))
+    };
+    match tokio::time::timeout(timeout, exchange).await {
+        Ok(Ok((ChallengeResponse::Absent, _))) => ChallengeResult::Absent,
+        Ok(Ok((ChallengeResponse::Proofs(proofs), elapsed))) => {
+            if proofs != expected {
+                ChallengeResult::Wrong
+            } else if elapsed > deadline {
+                ChallengeResult::Slow { elapsed, deadline }
+            } else {
+                ChallengeResult::Passed { elapsed }
+            }
+        }
+        Ok(Err(e)) => ChallengeResult::Unreachable {
+            reason: e.to_string(),
+        },
+        Err(_) => ChallengeResult::Unreachable {
+            reason: format!("no answer within {:?}", timeout),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use iroh::protocol::Router;
+
+    use crate::node::iroh::test_utils::{test_blobs_service, test_endpoint};
+
+    /// How a test peer answers challenges
+    #[derive(Debug, Clone)]
+    enum Peer {
+        Honest(ChallengeProtocol),
+        /// Answers with proofs over the wrong bytes
+        Liar,
+        /// Reads the challenge and never answers
+        Silent,
+    }
+
+    impl Peer {
+        async fn handle(&self, connecting: Connecting) -> Result<()> {
+            let connection = connecting.await?;
+            let (mut send, mut recv) = connection.accept_bi().await?;
+            let request: ChallengeRequest = read_message(&mut recv, MAX_MESSAGE_SIZE).await?;
+            let proofs = request
+                .chunks
+                .iter()
+                .map(|_| keyed_proof(&request.nonce, b"not the content"))
+                .collect();
+            if let Peer::Liar = self {
+                write_message(
+                    &mut send,
+                    &ChallengeResponse::Proofs(proofs),
+                    MAX_MESSAGE_SIZE,
+                )
+                .await?;
+                send.finish()?;
+            }
+            connection.closed().await;
+            Ok(())
+        }
+    }
+
+    impl ProtocolHandler for Peer {
+        fn accept(&self, conn: Connecting) -> futures::future::BoxFuture<'static, Result<()>> {
+            let this = self.clone();
+            Box::pin(async move {
+                match this {
+                    Peer::Honest(protocol) => protocol.accept(conn).await,
+                    peer => peer.handle(conn).await,
+                }
+            })
+        }
+    }
+
+    /// Challenge a peer for `content`, with both ends sharing one store
+    async fn run(content: &[u8], peer: impl FnOnce(BlobsService) -> Peer) -> ChallengeResult {
+        let (blobs_service, _dir) = test_blobs_service().await;
+        let challenger = blobs_service.get_inner_blobs().endpoint().clone();
+        let hash = blobs_service.store_blob(content.to_vec()).await.unwrap();
+
+        let host = test_endpoint().await;
+        let router = Router::builder(host.clone())
+            .accept(CHALLENGE_ALPN, peer(blobs_service.clone()))
+            .spawn()
+            .await
+            .unwrap();
+        let addr = host.node_addr().await.unwrap();
+        let connection = challenger.connect(addr, CHALLENGE_ALPN).await.unwrap();
+
+        let prepared = prepare(&blobs_service, hash).await.unwrap();
+        let result = exchange(&connection, prepared, Duration::from_secs(1)).await;
+
+        router.shutdown().await.unwrap();
+        result
+    }
+
+    #[tokio::test]
+    async fn test_honest_peer_passes() {
+        let content = vec![7u8; 10 * CHUNK_SIZE as usize + 17];
+        let result = run(&content, |blobs| {
+            Peer::Honest(ChallengeProtocol::new(blobs))
+        })
+        .await;
+        assert!(result.is_passed(), "{:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_empty_blob_passes() {
+        let result = run(b"", |blobs| Peer::Honest(ChallengeProtocol::new(blobs))).await;
+        assert!(result.is_passed(), "{:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_out_of_range() {
+        let (blobs_service, _dir) = test_blobs_service().await;
+        let hash = blobs_service
+            .store_blob(vec![7u8; 2 * CHUNK_SIZE as usize])
+            .await
+            .unwrap();
+        // past the end, and so far past it the offset doesn't fit
+        for chunk in [2, u64::MAX / CHUNK_SIZE + 1, u64::MAX] {
+            let request = ChallengeRequest {
+                hash,
+                nonce: [0u8; 32],
+                chunks: vec![chunk],
+            };
+            assert!(prove(&blobs_service, &request).await.is_err(), "{}", chunk);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wrong_bytes() {
+        let result = run(b"the real content", |_| Peer::Liar).await;
+        assert!(matches!(result, ChallengeResult::Wrong), "{:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_is_unreachable() {
+        let result = run(b"the real content", |_| Peer::Silent).await;
+        assert!(
+            matches!(result, ChallengeResult::Unreachable { .. }),
+            "{:?}",
+            result
+        );
+    }
+}
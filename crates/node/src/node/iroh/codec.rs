@@ -0,0 +1,35 @@
+use anyhow::{bail, Result};
+use iroh::endpoint::{RecvStream, SendStream};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Write `message` as JSON behind its length, a big-endian u32
+pub(super) async fn write_message<T: Serialize>(
+    send: &mut SendStream,
+    message: &T,
+    max_size: usize,
+) -> Result<()> {
+    let data = serde_json::to_vec(message)?;
+    if data.len() > max_size {
+        bail!("message too large: {} bytes", data.len());
+    }
+    send.write_all(&(data.len() as u32).to_be_bytes()).await?;
+    send.write_all(&data).await?;
+    Ok(())
+}
+
+/// Read a message written by `write_message`, refusing one longer than
+///  `max_size` before reading any of it in
+pub(super) async fn read_message<T: DeserializeOwned>(
+    recv: &mut RecvStream,
+    max_size: usize,
+) -> Result<T> {
+    let mut len = [0u8; 4];
+    recv.read_exact(&mut len).await?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > max_size {
+        bail!("message too large: {} bytes", len);
+    }
+    let mut data = vec![0u8; len];
+    recv.read_exact(&mut data).await?;
+    Ok(serde_json::from_slice(&data)?)
+}
@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::StreamExt;
+use iroh::endpoint::{ConnectionType, DirectAddr, DirectAddrType};
+use iroh::{Endpoint, NodeId};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use super::protocol::JAX_ALPN;
+
+/// How long a discovery lookup gets before we report what we have
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long we try to reach a peer before calling it unreachable
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How our traffic to a peer gets there
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathKind {
+    Direct,
+    Relay,
+    /// Both, while a direct path is being confirmed
+    Mixed,
+    None,
+}
+
+impl fmt::Display for PathKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PathKind::Direct => "direct",
+            PathKind::Relay => "relay",
+            PathKind::Mixed => "mixed",
+            PathKind::None => "none",
+        })
+    }
+}
+
+/// How a discovery lookup for a peer went
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveryRecord {
+    /// Seconds since the epoch
+    pub at: u64,
+    /// Which discovery service answered
+    pub provenance: Option<String>,
+    pub relay_url: Option<String>,
+    pub direct_addrs: Vec<SocketAddr>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerDiagnostics {
+    pub node_id: NodeId,
+    pub reachable: bool,
+    /// Why we couldn't connect
+    pub error: Option<String>,
+    pub path: PathKind,
+    /// The address we reach the peer on directly, if we do
+    pub addr: Option<SocketAddr>,
+    pub relay_url: Option<String>,
+    pub rtt_ms: Option<f64>,
+    /// The lookup we just made -- none without discovery configured
+    pub discovery: Option<DiscoveryRecord>,
+    /// The last lookup that found the peer, which may be this one
+    pub last_resolved: Option<DiscoveryRecord>,
+}
+
+/// What kind of NAT we seem to be behind, going by the addresses the
+///  endpoint found for itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NatType {
+    /// Our public address is one we're bound to
+    None,
+    /// The router mapped a port for us
+    PortMapped,
+    /// Behind NAT, with no sign the mapping changes per destination
+    Nat,
+    /// The mapping changes per destination, so hole punching mostly fails
+    Hard,
+    /// No public address found -- no STUN, or no network
+    Unknown,
+}
+
+impl fmt::Display for NatType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NatType::None => "none",
+            NatType::PortMapped => "port_mapped",
+            NatType::Nat => "nat",
+            NatType::Hard => "hard",
+            NatType::Unknown => "unknown",
+        })
+    }
+}
+
+/// Where one of our direct addresses came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddrSource {
+    /// An interface we're bound on
+    Local,
+    /// What a STUN server saw us as
+    Stun,
+    /// A port the router mapped for us
+    Portmapped,
+    /// Our public address with our local port, guessed at when STUN sees
+    ///  the mapping change per destination
+    StunLocalPort,
+    Unknown,
+}
+
+impl From<DirectAddrType> for AddrSource {
+    fn from(typ: DirectAddrType) -> Self {
+        match typ {
+            DirectAddrType::Local => AddrSource::Local,
+            DirectAddrType::Stun => AddrSource::Stun,
+            DirectAddrType::Portmapped => AddrSource::Portmapped,
+            DirectAddrType::Stun4LocalPort => AddrSource::StunLocalPort,
+            DirectAddrType::Unknown => AddrSource::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for AddrSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AddrSource::Local => "local",
+            AddrSource::Stun => "stun",
+            AddrSource::Portmapped => "portmapped",
+            AddrSource::StunLocalPort => "stun_local_port",
+            AddrSource::Unknown => "unknown",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectAddress {
+    pub addr: SocketAddr,
+    pub source: AddrSource,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfDiagnostics {
+    pub node_id: NodeId,
+    pub bound_addrs: Vec<SocketAddr>,
+    pub direct_addrs: Vec<DirectAddress>,
+    /// Our home relay, which peers fall back to when they can't dial us
+    pub relay_url: Option<String>,
+    pub nat: NatType,
+}
+
+/// Reachability checks for us and our peers
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    endpoint: Endpoint,
+    /// The last successful lookup for each peer
+    resolved: Arc<RwLock<HashMap<NodeId, DiscoveryRecord>>>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+impl Diagnostics {
+    pub fn new(endpoint: Endpoint) -> Self {
+        Self {
+            endpoint,
+            resolved: Arc::default(),
+        }
+    }
+
+    /// Look `node_id` up through discovery, remembering it if found.
+    ///  `None` if no discovery is configured.
+    pub async fn discover(&self, node_id: NodeId) -> Option<DiscoveryRecord> {
+        let discovery = self.endpoint.discovery()?;
+        let mut record = DiscoveryRecord {
+            at: now(),
+            provenance: None,
+            relay_url: None,
+            direct_addrs: Vec::new(),
+            error: None,
+        };
+        let first = match discovery.resolve(self.endpoint.clone(), node_id) {
+            Some(mut items) => tokio::time::timeout(DISCOVERY_TIMEOUT, items.next()).await,
+            None => Ok(None),
+        };
+        match first {
+            Ok(Some(Ok(item))) => {
+                record.provenance = Some(item.provenance.to_string());
+                record.relay_url = item.node_addr.relay_url().map(ToString::to_string);
+                record.direct_addrs = item.node_addr.direct_addresses().copied().collect();
+            }
+            Ok(Some(Err(e))) => record.error = Some(e.to_string()),
+            Ok(None) => record.error = Some("no discovery service knows this node".to_string()),
+            Err(_) => record.error = Some("discovery timed out".to_string()),
+        }
+        if record.error.is_none() {
+            self.resolved.write().await.insert(node_id, record.clone());
+        }
+        Some(record)
+    }
+
+    /// Look a peer up, try to reach it, and report the path we got
+    pub async fn peer(&self, node_id: NodeId) -> PeerDiagnostics {
+        let discovery = self.discover(node_id).await;
+        let connected =
+            tokio::time::timeout(CONNECT_TIMEOUT, self.endpoint.connect(node_id, JAX_ALPN)).await;
+        let (rtt, error) = match connected {
+            Ok(Ok(connection)) => {
+                let rtt = connection.rtt();
+                connection.close(0u32.into(), b"diagnostics");
+                (Some(rtt), None)
+            }
+            Ok(Err(e)) => (None, Some(e.to_string())),
+            Err(_) => (None, Some("connection timed out".to_string())),
+        };
+
+        let info = self.endpoint.remote_info(node_id);
+        let conn_type = info
+            .as_ref()
+            .map(|info| info.conn_type.clone())
+            .unwrap_or_default();
+        let (path, addr, relay_url) = match conn_type {
+            ConnectionType::Direct(addr) => (PathKind::Direct, Some(addr), None),
+            ConnectionType::Relay(url) => (PathKind::Relay, None, Some(url.to_string())),
+            ConnectionType::Mixed(addr, url) => {
+                (PathKind::Mixed, Some(addr), Some(url.to_string()))
+            }
+            ConnectionType::None => (PathKind::None, None, None),
+        };
+        let rtt = rtt.or_else(|| info.as_ref().and_then(|info| info.latency));
+        PeerDiagnostics {
+            node_id,
+            reachable: error.is_none(),
+            error,
+            path,
+            addr,
+            relay_url: relay_url.or_else(|| {
+                info.and_then(|info| info.relay_url)
+                    .map(|relay| relay.relay_url.to_string())
+            }),
+            rtt_ms: rtt.map(|rtt| rtt.as_secs_f64() * 1000.0),
+            discovery,
+            last_resolved: self.resolved.read().await.get(&node_id).cloned(),
+        }
+    }
+
+    /// How reachable we are ourselves
+    pub fn local(&self) -> SelfDiagnostics {
+        let (bound_v4, bound_v6) = self.endpoint.bound_sockets();
+        let direct_addrs = self
+            .endpoint
+            .direct_addresses()
+            .get()
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let relay_url = self
+            .endpoint
+            .home_relay()
+            .get()
+            .ok()
+            .flatten()
+            .map(|url| url.to_string());
+        SelfDiagnostics {
+            node_id: self.endpoint.node_id(),
+            bound_addrs: std::iter::once(bound_v4).chain(bound_v6).collect(),
+            nat: nat_type(&direct_addrs),
+            direct_addrs: direct_addrs
+                .into_iter()
+                .map(|direct| DirectAddress {
+                    addr: direct.addr,
+                    source: direct.typ.into(),
+                })
+                .collect(),
+            relay_url,
+        }
+    }
+}
+
+/// Guess our NAT from where our direct addresses came from. The endpoint
+///  only adds a STUN address with our local port when the mapping varies
+///  by destination, and only if we're bound to a fixed port -- so a hard
+///  NAT on a random port looks like an ordinary one.
+fn nat_type(addrs: &[DirectAddr]) -> NatType {
+    let of = |typ| addrs.iter().filter(move |addr| addr.typ == typ);
+    if of(DirectAddrType::Stun4LocalPort).next().is_some() {
+        return NatType::Hard;
+    }
+    if of(DirectAddrType::Portmapped).next().is_some() {
+        return NatType::PortMapped;
+    }
+    let mut public = of(DirectAddrType::Stun).peekable();
+    if public.peek().is_none() {
+        return NatType::Unknown;
+    }
+    let open =
+        public.any(|stun| of(DirectAddrType::Local).any(|local| local.addr.ip() == stun.addr.ip()));
+    if open {
+        NatType::None
+    } else {
+        NatType::Nat
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(addr: &str, typ: DirectAddrType) -> DirectAddr {
+        DirectAddr {
+            addr: addr.parse().unwrap(),
+            typ,
+        }
+    }
+
+    #[test]
+    fn test_nat_type() {
+        let local = addr("192.168.1.20:3001", DirectAddrType::Local);
+        assert_eq!(nat_type(std::slice::from_ref(&local)), NatType::Unknown);
+
+        let stun = addr("203.0.113.7:51000", DirectAddrType::Stun);
+        assert_eq!(nat_type(&[local.clone(), stun.clone()]), NatType::Nat);
+
+        let hard = addr("203.0.113.7:3001", DirectAddrType::Stun4LocalPort);
+        assert_eq!(nat_type(&[local.clone(), stun, hard]), NatType::Hard);
+
+        let public_local = addr("203.0.113.9:3001", DirectAddrType::Local);
+        let public_stun = addr("203.0.113.9:3001", DirectAddrType::Stun);
+        assert_eq!(nat_type(&[public_local, public_stun]), NatType::None);
+    }
+
+    #[test]
+    fn test_display_matches_serde() {
+        fn check(value: impl Serialize + fmt::Display) {
+            assert_eq!(
+                serde_json::to_value(&value).unwrap(),
+                value.to_string().as_str()
+            );
+        }
+        for path in [
+            PathKind::Direct,
+            PathKind::Relay,
+            PathKind::Mixed,
+            PathKind::None,
+        ] {
+            check(path);
+        }
+        for nat in [
+            NatType::None,
+            NatType::PortMapped,
+            NatType::Nat,
+            NatType::Hard,
+            NatType::Unknown,
+        ] {
+            check(nat);
+        }
+        for source in [
+            DirectAddrType::Local,
+            DirectAddrType::Stun,
+            DirectAddrType::Portmapped,
+            DirectAddrType::Stun4LocalPort,
+            DirectAddrType::Unknown,
+        ] {
+            check(AddrSource::from(source));
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use bytes::{Bytes, BytesMut};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use futures::{Stream, StreamExt};
+use iroh_blobs::ticket::BlobTicket;
+use rand::RngCore;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Plaintext bytes sealed together. Each chunk is sealed on its own, so
+///  any range of the content can be decrypted by reading just the chunks
+///  it falls in.
+pub const CHUNK_SIZE: u64 = 64 * 1024;
+/// What sealing adds to every chunk
+pub const TAG_SIZE: u64 = 16;
+/// How much of the ciphertext one chunk takes up
+pub const SEALED_CHUNK_SIZE: u64 = CHUNK_SIZE + TAG_SIZE;
+
+/// The key content was encrypted with. Every piece of content gets a
+///  fresh one, so chunk nonces can simply count up from zero.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ContentKey([u8; 32]);
+
+impl ContentKey {
+    pub fn generate() -> Self {
+        let mut key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        Self(key)
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.0))
+    }
+
+    /// Seal chunk `index` of the content. The final chunk is marked in
+    ///  its nonce, so a truncated ciphertext doesn't decrypt.
+    pub fn seal(&self, index: u64, last: bool, chunk: &[u8]) -> io::Result<Bytes> {
+        self.cipher()
+            .encrypt(&nonce(index, last), chunk)
+            .map(Bytes::from)
+            .map_err(|_| io::Error::other("failed to encrypt chunk"))
+    }
+
+    /// Open chunk `index` of the content
+    pub fn open(&self, index: u64, last: bool, chunk: &[u8]) -> io::Result<Bytes> {
+        self.cipher()
+            .decrypt(&nonce(index, last), chunk)
+            .map(Bytes::from)
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("chunk {} doesn't decrypt with this key", index),
+                )
+            })
+    }
+}
+
+fn nonce(index: u64, last: bool) -> Nonce {
+    let mut nonce = [0u8; 12];
+    nonce[..8].copy_from_slice(&index.to_be_bytes());
+    nonce[8] = last as u8;
+    Nonce::from(nonce)
+}
+
+// keys end up in logs otherwise
+impl fmt::Debug for ContentKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ContentKey(..)")
+    }
+}
+
+impl fmt::Display for ContentKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl FromStr for ContentKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let bytes = hex::decode(s)?;
+        let key = bytes
+            .try_into()
+            .map_err(|_| anyhow!("a content key is 32 bytes"))?;
+        Ok(Self(key))
+    }
+}
+
+impl Serialize for ContentKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ContentKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A blob ticket for encrypted content, with the key to read it:
+///  `<ticket>:<key>`. Anyone holding one can decrypt the content, so
+///  hand it out like you would the plaintext.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedTicket {
+    pub ticket: BlobTicket,
+    pub key: ContentKey,
+}
+
+impl fmt::Display for EncryptedTicket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.ticket, self.key)
+    }
+}
+
+impl FromStr for EncryptedTicket {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (ticket, key) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow!("an encrypted ticket is <ticket>:<key>"))?;
+        Ok(Self {
+            ticket: ticket.parse()?,
+            key: key.parse()?,
+        })
+    }
+}
+
+/// Encrypt a stream chunk by chunk, as it goes
+pub fn encrypt_stream(
+    key: ContentKey,
+    stream: impl Stream<Item = io::Result<Bytes>> + Send + Unpin + 'static,
+) -> impl Stream<Item = io::Result<Bytes>> + Send + Unpin + 'static {
+    // we only know a chunk is the last once the input runs out, so one
+    //  chunk's worth is always held back
+    let state = (stream, BytesMut::new(), 0u64, false);
+    Box::pin(futures::stream::unfold(
+        state,
+        move |(mut stream, mut buffer, index, done)| {
+            let key = key.clone();
+            async move {
+                if done {
+                    return None;
+                }
+                while buffer.len() as u64 <= CHUNK_SIZE {
+                    match stream.next().await {
+                        Some(Ok(data)) => buffer.extend_from_slice(&data),
+                        Some(Err(e)) => return Some((Err(e), (stream, buffer, index, true))),
+                        None => {
+                            let sealed = key.seal(index, true, &buffer);
+                            return Some((sealed, (stream, BytesMut::new(), index + 1, true)));
+                        }
+                    }
+                }
+                let chunk = buffer.split_to(CHUNK_SIZE as usize);
+                let sealed = key.seal(index, false, &chunk);
+                Some((sealed, (stream, buffer, index + 1, false)))
+            }
+        },
+    ))
+}
+
+/// Decrypts content as its ciphertext arrives, in whatever pieces it
+///  comes in. Mirrors [`encrypt_stream`]: a chunk is only opened once
+///  more ciphertext follows it, since the last one is sealed differently.
+pub struct Decryptor {
+    key: ContentKey,
+    buffer: BytesMut,
+    index: u64,
+}
+
+impl Decryptor {
+    pub fn new(key: ContentKey) -> Self {
+        Self {
+            key,
+            buffer: BytesMut::new(),
+            index: 0,
+        }
+    }
+
+    /// Take in more ciphertext, returning whatever plaintext it completes
+    pub fn update(&mut self, data: &[u8]) -> io::Result<Vec<Bytes>> {
+        self.buffer.extend_from_slice(data);
+        let mut plaintext = Vec::new();
+        while self.buffer.len() as u64 > SEALED_CHUNK_SIZE {
+            let chunk = self.buffer.split_to(SEALED_CHUNK_SIZE as usize);
+            plaintext.push(self.key.open(self.index, false, &chunk)?);
+            self.index += 1;
+        }
+        Ok(plaintext)
+    }
+
+    /// Open the final chunk once the ciphertext has run out. Fails if
+    ///  the ciphertext was cut short.
+    pub fn finish(self) -> io::Result<Bytes> {
+        self.key.open(self.index, true, &self.buffer)
+    }
+}
+
+/// How many chunks `ciphertext_size` bytes of sealed content are
+fn chunk_count(ciphertext_size: u64) -> u64 {
+    ciphertext_size.div_ceil(SEALED_CHUNK_SIZE)
+}
+
+/// The size of the content sealed into `ciphertext_size` bytes
+pub fn plaintext_size(ciphertext_size: u64) -> Result<u64> {
+    let chunks = chunk_count(ciphertext_size);
+    let last = ciphertext_size - (chunks.max(1) - 1) * SEALED_CHUNK_SIZE;
+    if last < TAG_SIZE {
+        return Err(anyhow!("{} bytes isn't encrypted content", ciphertext_size));
+    }
+    Ok(ciphertext_size - chunks * TAG_SIZE)
+}
+
+/// The size `plaintext_size` bytes of content seal into. Even empty
+///  content is one (empty) sealed chunk.
+pub fn ciphertext_size(plaintext_size: u64) -> u64 {
+    let chunks = plaintext_size.div_ceil(CHUNK_SIZE).max(1);
+    plaintext_size + chunks * TAG_SIZE
+}
+
+/// Where chunk `index` sits in the plaintext -- offset and length --
+///  and whether it's the last. Sealing is deterministic, so this is
+///  enough to reproduce any part of the ciphertext on its own.
+pub fn plaintext_chunk(index: u64, plaintext_size: u64) -> (u64, u64, bool) {
+    let offset = index * CHUNK_SIZE;
+    let len = CHUNK_SIZE.min(plaintext_size.saturating_sub(offset));
+    let last = index + 1 == plaintext_size.div_ceil(CHUNK_SIZE).max(1);
+    (offset, len, last)
+}
+
+/// Where chunk `index` sits in the ciphertext -- offset and length --
+///  and whether it's the last
+pub fn sealed_chunk(index: u64, ciphertext_size: u64) -> (u64, u64, bool) {
+    let offset = index * SEALED_CHUNK_SIZE;
+    let len = SEALED_CHUNK_SIZE.min(ciphertext_size.saturating_sub(offset));
+    let last = index + 1 == chunk_count(ciphertext_size);
+    (offset, len, last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn encrypt(key: &ContentKey, data: &[u8]) -> Vec<u8> {
+        // deliberately ragged input chunks
+        let parts = data
+            .chunks(10_000)
+            .map(|part| Ok(Bytes::copy_from_slice(part)))
+            .collect::<Vec<_>>();
+        let mut sealed = encrypt_stream(key.clone(), futures::stream::iter(parts));
+        let mut out = Vec::new();
+        while let Some(chunk) = sealed.next().await {
+            out.extend_from_slice(&chunk.unwrap());
+        }
+        out
+    }
+
+    fn decrypt(key: &ContentKey, sealed: &[u8]) -> io::Result<Vec<u8>> {
+        let size = sealed.len() as u64;
+        let mut out = Vec::new();
+        for index in 0..chunk_count(size) {
+            let (offset, len, last) = sealed_chunk(index, size);
+            let chunk = &sealed[offset as usize..(offset + len) as usize];
+            out.extend_from_slice(&key.open(index, last, chunk)?);
+        }
+        Ok(out)
+    }
+
+    #[tokio::test]
+    async fn test_roundtrip() {
+        let key = ContentKey::generate();
+        for size in [
+            0,
+            1,
+            CHUNK_SIZE - 1,
+            CHUNK_SIZE,
+            CHUNK_SIZE + 1,
+            3 * CHUNK_SIZE + 7,
+        ] {
+            let data = (0..size).map(|i| i as u8).collect::<Vec<_>>();
+            let sealed = encrypt(&key, &data).await;
+            assert_eq!(plaintext_size(sealed.len() as u64).unwrap(), size);
+            assert_eq!(ciphertext_size(size), sealed.len() as u64);
+            assert_eq!(decrypt(&key, &sealed).unwrap(), data);
+
+            // the same ciphertext, one plaintext chunk at a time
+            let mut resealed = Vec::new();
+            for index in 0..chunk_count(sealed.len() as u64) {
+                let (offset, len, last) = plaintext_chunk(index, size);
+                let chunk = &data[offset as usize..(offset + len) as usize];
+                resealed.extend_from_slice(&key.seal(index, last, chunk).unwrap());
+            }
+            assert_eq!(resealed, sealed);
+
+            // and decrypted as it arrives, in ragged pieces
+            let mut decryptor = Decryptor::new(key.clone());
+            let mut opened = Vec::new();
+            for part in sealed.chunks(10_001) {
+                for plaintext in decryptor.update(part).unwrap() {
+                    opened.extend_from_slice(&plaintext);
+                }
+            }
+            opened.extend_from_slice(&decryptor.finish().unwrap());
+            assert_eq!(opened, data);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tampering() {
+        let key = ContentKey::generate();
+        let data = vec![7u8; 2 * CHUNK_SIZE as usize + 100];
+        let sealed = encrypt(&key, &data).await;
+
+        assert!(decrypt(&ContentKey::generate(), &sealed).is_err());
+        // dropping the last chunk leaves a chunk that isn't marked last
+        let truncated = &sealed[..2 * SEALED_CHUNK_SIZE as usize];
+        assert!(decrypt(&key, truncated).is_err());
+        let mut decryptor = Decryptor::new(key.clone());
+        assert!(decryptor.update(truncated).is_ok());
+        assert!(decryptor.finish().is_err());
+    }
+
+    #[test]
+    fn test_ticket_roundtrip() {
+        let node_id = iroh::SecretKey::from_bytes(&[1u8; 32]).public();
+        let ticket = BlobTicket::new(
+            node_id.into(),
+            iroh_blobs::Hash::new(b"sealed"),
+            iroh_blobs::BlobFormat::Raw,
+        )
+        .unwrap();
+        let encrypted = EncryptedTicket {
+            ticket,
+            key: ContentKey::generate(),
+        };
+        let parsed = encrypted.to_string().parse::<EncryptedTicket>().unwrap();
+        assert_eq!(parsed, encrypted);
+        assert!(encrypted
+            .ticket
+            .to_string()
+            .parse::<EncryptedTicket>()
+            .is_err());
+    }
+}
@@ -1,53 +1,239 @@
-use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::collections::BTreeSet;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use futures::stream::BoxStream;
+use iroh::discovery::local_swarm_discovery::LocalSwarmDiscovery;
 use iroh::discovery::pkarr::dht::DhtDiscovery;
-use iroh::{Endpoint, SecretKey};
+use iroh::discovery::pkarr::{PkarrPublisher, PkarrResolver};
+use iroh::discovery::static_provider::StaticProvider;
+use iroh::discovery::{ConcurrentDiscovery, Discovery, DiscoveryItem};
+use iroh::{Endpoint, NodeAddr, NodeId, RelayMap, RelayMode, RelayUrl, SecretKey};
 
-// spin up an ephemeral endpoint using the mainline
-//  dht as a discovery mechanism
-pub async fn create_ephemeral_endpoint() -> Endpoint {
-    // Connect to the mainline DHT as an ephemeral node
-    let addr = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0); // Let system choose port
-    let mainline_discovery = DhtDiscovery::builder()
-        .build()
-        .expect("failed to build mainline discovery");
+use crate::config::{DiscoveryMethod, RelayConfig};
 
-    // Create the endpoint with our key and discovery
+/// How our endpoints find peers, and which relays they use
+#[derive(Debug, Clone)]
+pub struct EndpointOptions {
+    pub discovery: Vec<DiscoveryMethod>,
+    pub relay: RelayConfig,
+    /// The node's mDNS service, when it uses local network discovery.
+    ///  Every instance joins the multicast group, so there's one per
+    ///  node, and ephemeral endpoints resolve through it.
+    local_network: Option<Arc<LocalSwarmDiscovery>>,
+}
+
+impl Default for EndpointOptions {
+    fn default() -> Self {
+        Self::new(vec![DiscoveryMethod::Dht], RelayConfig::Default)
+    }
+}
+
+impl EndpointOptions {
+    pub fn new(discovery: Vec<DiscoveryMethod>, relay: RelayConfig) -> Self {
+        Self {
+            discovery,
+            relay,
+            local_network: None,
+        }
+    }
+
+    /// Start local network discovery for the node `node_id`, if it's
+    ///  configured. Endpoints built from these options afterwards share it.
+    pub fn with_local_network(mut self, node_id: NodeId) -> Result<Self> {
+        if self.discovery.contains(&DiscoveryMethod::Local) {
+            let local_network = LocalSwarmDiscovery::new(node_id)
+                .context("failed to start local network discovery")?;
+            self.local_network = Some(Arc::new(local_network));
+        }
+        Ok(self)
+    }
+
+    fn relay_mode(&self) -> RelayMode {
+        match &self.relay {
+            RelayConfig::Default => RelayMode::Default,
+            RelayConfig::Custom { url } => {
+                RelayMode::Custom(RelayMap::from_url(url.clone().into()))
+            }
+            RelayConfig::Disabled => RelayMode::Disabled,
+        }
+    }
+
+    /// Build the discovery services. With a secret key we publish our
+    ///  own address as well as resolving others'.
+    fn discovery(&self, secret_key: Option<&SecretKey>) -> Result<ConcurrentDiscovery> {
+        let mut services = ConcurrentDiscovery::empty();
+        for method in &self.discovery {
+            match method {
+                DiscoveryMethod::Dht => {
+                    let mut builder = DhtDiscovery::builder();
+                    if let Some(secret_key) = secret_key {
+                        builder = builder.secret_key(secret_key.clone());
+                    }
+                    services.add(
+                        builder
+                            .build()
+                            .context("failed to build mainline discovery")?,
+                    );
+                }
+                DiscoveryMethod::Pkarr { url } => {
+                    let (publisher, resolver) = match url {
+                        Some(url) => (
+                            secret_key.map(|key| PkarrPublisher::new(key.clone(), url.clone())),
+                            PkarrResolver::new(url.clone()),
+                        ),
+                        None => (
+                            secret_key.map(|key| PkarrPublisher::n0_dns(key.clone())),
+                            PkarrResolver::n0_dns(),
+                        ),
+                    };
+                    if let Some(publisher) = publisher {
+                        services.add(publisher);
+                    }
+                    services.add(resolver);
+                }
+                DiscoveryMethod::Local => match (&self.local_network, secret_key) {
+                    (Some(local_network), _) => services.add(SharedLocalNetwork {
+                        inner: local_network.clone(),
+                        publish: secret_key.is_some(),
+                    }),
+                    (None, Some(secret_key)) => services.add(
+                        LocalSwarmDiscovery::new(secret_key.public())
+                            .context("failed to start local network discovery")?,
+                    ),
+                    // starting a service here would put another listener on
+                    //  the multicast group for every ephemeral endpoint
+                    (None, None) => {}
+                },
+                DiscoveryMethod::Static { peers } => {
+                    services.add(StaticProvider::from_node_addrs(peers.iter().map(|peer| {
+                        NodeAddr::from_parts(
+                            peer.node_id,
+                            peer.relay_url.clone().map(Into::into),
+                            peer.addrs.iter().copied(),
+                        )
+                    })))
+                }
+            }
+        }
+        Ok(services)
+    }
+}
+
+/// The node's local network discovery, as seen by one endpoint. Only
+///  the node's own endpoint publishes through it; the rest just resolve.
+#[derive(Debug, Clone)]
+struct SharedLocalNetwork {
+    inner: Arc<LocalSwarmDiscovery>,
+    publish: bool,
+}
+
+impl Discovery for SharedLocalNetwork {
+    fn publish(&self, url: Option<&RelayUrl>, addrs: &BTreeSet<SocketAddr>) {
+        if self.publish {
+            self.inner.publish(url, addrs);
+        }
+    }
+
+    fn resolve(
+        &self,
+        endpoint: Endpoint,
+        node_id: NodeId,
+    ) -> Option<BoxStream<'static, Result<DiscoveryItem>>> {
+        self.inner.resolve(endpoint, node_id)
+    }
+
+    fn subscribe(&self) -> Option<BoxStream<'static, DiscoveryItem>> {
+        if self.publish {
+            self.inner.subscribe()
+        } else {
+            None
+        }
+    }
+}
+
+// spin up an ephemeral endpoint with a random identity, which
+//  resolves peers but never publishes itself
+pub async fn create_ephemeral_endpoint(options: &EndpointOptions) -> Result<Endpoint> {
+    let addr = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0); // Let system choose port
+    let discovery = options.discovery(None)?;
 
     Endpoint::builder()
-        .discovery(Box::new(mainline_discovery))
+        .discovery(Box::new(discovery) as Box<dyn Discovery>)
+        .relay_mode(options.relay_mode())
         .bind_addr_v4(addr)
         .bind()
         .await
-        .expect("failed to bind ephemeral endpoint")
+        .context("failed to bind ephemeral endpoint")
+}
+
+/// Where to bind each address family. The configured address says
+///  where to listen for its own family, and how far to open up the
+///  other: a wildcard gets the other family's wildcard, anything else
+///  only the other family's loopback -- unless IPv6 has an address of
+///  its own.
+fn bind_addrs(
+    socket_addr: SocketAddr,
+    socket_addr_v6: Option<SocketAddrV6>,
+) -> (SocketAddrV4, SocketAddrV6) {
+    let port = socket_addr.port();
+    match socket_addr {
+        SocketAddr::V4(addr_v4) => {
+            let ip_v6 = if addr_v4.ip().is_unspecified() {
+                Ipv6Addr::UNSPECIFIED
+            } else {
+                Ipv6Addr::LOCALHOST
+            };
+            (
+                addr_v4,
+                socket_addr_v6.unwrap_or(SocketAddrV6::new(ip_v6, port, 0, 0)),
+            )
+        }
+        SocketAddr::V6(addr_v6) => {
+            let ip_v4 = if addr_v6.ip().is_unspecified() {
+                Ipv4Addr::UNSPECIFIED
+            } else {
+                Ipv4Addr::LOCALHOST
+            };
+            (
+                SocketAddrV4::new(ip_v4, port),
+                socket_addr_v6.unwrap_or(addr_v6),
+            )
+        }
+    }
 }
 
-pub async fn create_endpoint(socket_addr: SocketAddr, secret_key: SecretKey) -> Endpoint {
-    // Convert the SocketAddr to a SocketAddrV4
-    let addr = SocketAddrV4::new(
-        socket_addr
-            .ip()
-            .to_string()
-            .parse::<Ipv4Addr>()
-            .unwrap_or(Ipv4Addr::UNSPECIFIED),
-        socket_addr.port(),
-    );
-
-    let mainline_discovery = DhtDiscovery::builder()
-        .secret_key(secret_key.clone())
-        .build()
-        .expect("failed to build mainline discovery");
+pub async fn create_endpoint(
+    socket_addr: SocketAddr,
+    socket_addr_v6: Option<SocketAddrV6>,
+    secret_key: SecretKey,
+    options: &EndpointOptions,
+) -> Result<Endpoint> {
+    let (addr_v4, addr_v6) = bind_addrs(socket_addr, socket_addr_v6);
+    let discovery = options.discovery(Some(&secret_key))?;
 
     // Create the endpoint with our key and discovery
 
-    Endpoint::builder()
+    let endpoint = Endpoint::builder()
         .secret_key(secret_key)
-        .discovery(Box::new(mainline_discovery))
-        .bind_addr_v4(addr)
+        .discovery(Box::new(discovery) as Box<dyn Discovery>)
+        .relay_mode(options.relay_mode())
+        .bind_addr_v4(addr_v4)
+        .bind_addr_v6(addr_v6)
         .bind()
         .await
-        .expect("failed to bind ephemeral endpoint")
+        .with_context(|| format!("failed to bind endpoint on {} and {}", addr_v4, addr_v6))?;
+    // IPv6 is best effort -- say so if we didn't get it
+    match endpoint.bound_sockets() {
+        (v4, Some(v6)) => tracing::info!("node::iroh::endpoint: bound {} and {}", v4, v6),
+        (v4, None) => tracing::warn!(
+            "node::iroh::endpoint: bound {}, but not IPv6 on {}",
+            v4,
+            addr_v6
+        ),
+    }
+    Ok(endpoint)
 }
 
 // Helper to wait for DERP relay assignment (optional)
@@ -60,9 +246,87 @@ pub async fn await_relay_region(endpoint: Endpoint) -> Result<()> {
             break;
         }
         if t0.elapsed() > std::time::Duration::from_secs(10) {
-            panic!("failed to setup iroh endpoint against relay")
+            bail!("no relay assigned after 10s")
         }
         tokio::time::sleep(std::time::Duration::from_millis(50)).await;
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bind_addrs() {
+        let (v4, v6) = bind_addrs("0.0.0.0:3001".parse().unwrap(), None);
+        assert_eq!(v4, "0.0.0.0:3001".parse().unwrap());
+        assert_eq!(v6, "[::]:3001".parse().unwrap());
+
+        let (v4, v6) = bind_addrs("[::]:3001".parse().unwrap(), None);
+        assert_eq!(v4, "0.0.0.0:3001".parse().unwrap());
+        assert_eq!(v6, "[::]:3001".parse().unwrap());
+    }
+
+    #[test]
+    fn test_bind_addrs_loopback() {
+        let (v4, v6) = bind_addrs("127.0.0.1:3001".parse().unwrap(), None);
+        assert_eq!(v4, "127.0.0.1:3001".parse().unwrap());
+        assert_eq!(v6, "[::1]:3001".parse().unwrap());
+
+        let (v4, v6) = bind_addrs("[::1]:3001".parse().unwrap(), None);
+        assert_eq!(v4, "127.0.0.1:3001".parse().unwrap());
+        assert_eq!(v6, "[::1]:3001".parse().unwrap());
+    }
+
+    #[test]
+    fn test_bind_addrs_specific() {
+        // a specific address doesn't open the other family any wider
+        //  than loopback
+        let (v4, v6) = bind_addrs("10.0.0.2:3001".parse().unwrap(), None);
+        assert_eq!(v4, "10.0.0.2:3001".parse().unwrap());
+        assert_eq!(v6, "[::1]:3001".parse().unwrap());
+
+        let (v4, v6) = bind_addrs("[fd00::2]:3001".parse().unwrap(), None);
+        assert_eq!(v4, "127.0.0.1:3001".parse().unwrap());
+        assert_eq!(v6, "[fd00::2]:3001".parse().unwrap());
+
+        // unless IPv6 is configured separately
+        let (v4, v6) = bind_addrs(
+            "10.0.0.2:3001".parse().unwrap(),
+            Some("[fd00::2]:4001".parse().unwrap()),
+        );
+        assert_eq!(v4, "10.0.0.2:3001".parse().unwrap());
+        assert_eq!(v6, "[fd00::2]:4001".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_shared_local_network() {
+        let node_id = SecretKey::from_bytes(&[9u8; 32]).public();
+        let options = EndpointOptions::new(vec![DiscoveryMethod::Dht], RelayConfig::Disabled)
+            .with_local_network(node_id)
+            .unwrap();
+        assert!(options.local_network.is_none());
+
+        let options = EndpointOptions::new(vec![DiscoveryMethod::Local], RelayConfig::Disabled)
+            .with_local_network(node_id)
+            .unwrap();
+        let local_network = options.local_network.clone().unwrap();
+        // ephemeral endpoints come and go on the one service
+        let ephemeral = create_ephemeral_endpoint(&options).await.unwrap();
+        let cloned = options.clone();
+        assert!(Arc::ptr_eq(
+            &local_network,
+            cloned.local_network.as_ref().unwrap()
+        ));
+        assert_eq!(Arc::strong_count(&local_network), 4);
+        ephemeral.close().await;
+
+        // and never publish through it
+        let resolver = SharedLocalNetwork {
+            inner: local_network.clone(),
+            publish: false,
+        };
+        assert!(resolver.subscribe().is_none());
+    }
+}
@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy::primitives::Address;
+use anyhow::{bail, Context, Result};
+use bytes::Bytes;
+use ed25519::Signature;
+use futures::StreamExt;
+use iroh::endpoint::Connecting;
+use iroh::protocol::ProtocolHandler;
+use iroh::{Endpoint, NodeId, SecretKey};
+use iroh_blobs::Hash;
+use iroh_gossip::net::{Event, GossipReceiver, GossipSender};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+
+use super::protocol::signature_hex;
+
+pub use iroh_gossip::net::GOSSIP_ALPN;
+
+/// Announcements are small, but a probe summary names every peer we
+///  probed -- more than iroh-gossip's default allows for
+const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+/// Messages older than this are dropped rather than replayed
+const MAX_AGE: Duration = Duration::from_secs(10 * 60);
+/// How far ahead of our clock a sender's may be
+const MAX_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+/// A gossip topic -- one per pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TopicId(Hash);
+
+impl TopicId {
+    /// Every node derives the same topic from the pool's address
+    pub fn for_pool(address: Address) -> Self {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"jax/pool/");
+        hasher.update(address.as_slice());
+        Self(Hash::from(*hasher.finalize().as_bytes()))
+    }
+}
+
+impl From<TopicId> for iroh_gossip::proto::TopicId {
+    fn from(topic: TopicId) -> Self {
+        Self::from_bytes(*topic.0.as_bytes())
+    }
+}
+
+impl std::fmt::Display for TopicId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.fmt_short())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Announcement {
+    /// The sender holds the pool's content
+    Have { hash: Hash },
+    /// The sender entered the pool
+    Joined { hash: Hash },
+    /// How the sender's last round of probes of the pool went
+    ProbeSummary { results: Vec<ProbeOutcome> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProbeOutcome {
+    pub node_id: NodeId,
+    pub success: bool,
+}
+
+/// What the signature covers
+#[derive(Serialize, Deserialize)]
+struct Payload {
+    topic: TopicId,
+    from: NodeId,
+    timestamp: u64,
+    announcement: Announcement,
+}
+
+/// A payload as sent, so the signature is checked against exactly the
+///  bytes that were signed
+#[derive(Serialize, Deserialize)]
+struct SignedMessage {
+    payload: String,
+    #[serde(with = "signature_hex")]
+    signature: Signature,
+}
+
+impl SignedMessage {
+    fn sign(secret_key: &SecretKey, payload: &Payload) -> Result<Self> {
+        let payload = serde_json::to_string(payload)?;
+        let signature = secret_key.sign(payload.as_bytes());
+        Ok(Self { payload, signature })
+    }
+
+    fn verify(&self) -> Result<Payload> {
+        let payload: Payload = serde_json::from_str(&self.payload)?;
+        if payload
+            .from
+            .verify(self.payload.as_bytes(), &self.signature)
+            .is_err()
+        {
+            bail!("bad signature from {}", payload.from);
+        }
+        let now = unix_now();
+        if payload.timestamp + MAX_AGE.as_secs() < now {
+            bail!("stale message from {}", payload.from);
+        }
+        if payload.timestamp > now + MAX_CLOCK_SKEW.as_secs() {
+            bail!("message from {} is from the future", payload.from);
+        }
+        Ok(payload)
+    }
+}
+
+/// A verified announcement on one of our topics
+#[derive(Debug, Clone)]
+pub struct GossipEvent {
+    pub topic: TopicId,
+    pub from: NodeId,
+    pub announcement: Announcement,
+}
+
+/// A topic we're on: where we send to it, and the task delivering
+///  what arrives on it
+struct Subscription {
+    sender: GossipSender,
+    task: JoinHandle<()>,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        // with the receiver gone too, iroh-gossip leaves the topic
+        self.task.abort();
+    }
+}
+
+/// Signed announcements between the peers of each pool, carried by
+///  iroh-gossip. It keeps each topic's swarm connected through a few
+///  neighbours -- a bounded set, however many nodes talk on the topic --
+///  and passes each message on once. Signing on top of that says who
+///  an announcement is from, rather than just who handed it to us.
+#[derive(Clone)]
+pub struct Gossip {
+    gossip: iroh_gossip::net::Gossip,
+    secret_key: SecretKey,
+    topics: Arc<RwLock<HashMap<TopicId, Subscription>>>,
+    events: broadcast::Sender<GossipEvent>,
+}
+
+impl std::fmt::Debug for Gossip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Gossip")
+            .field("node_id", &self.secret_key.public())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Gossip {
+    /// Gossip from our node's own endpoint -- unlike probes, there's no
+    ///  point hiding who's talking
+    pub async fn new(endpoint: Endpoint, secret_key: SecretKey) -> Result<Self> {
+        let gossip = iroh_gossip::net::Gossip::builder()
+            .max_message_size(MAX_MESSAGE_SIZE)
+            .spawn(endpoint)
+            .await
+            .context("failed to start gossip")?;
+        let (events, _) = broadcast::channel(256);
+        Ok(Self {
+            gossip,
+            secret_key,
+            topics: Arc::default(),
+            events,
+        })
+    }
+
+    /// Everything we hear on any topic
+    pub fn subscribe(&self) -> broadcast::Receiver<GossipEvent> {
+        self.events.subscribe()
+    }
+
+    /// Start listening on `topic`, with whichever peers we already know of
+    pub async fn join(&self, topic: TopicId, peers: impl IntoIterator<Item = NodeId>) {
+        let me = self.secret_key.public();
+        let peers = peers
+            .into_iter()
+            .filter(|peer| *peer != me)
+            .collect::<Vec<_>>();
+        let mut topics = self.topics.write().await;
+        if let Some(subscription) = topics.get(&topic) {
+            if !peers.is_empty() {
+                if let Err(e) = subscription.sender.join_peers(peers).await {
+                    tracing::debug!("node::iroh::gossip: failed to join peers: {}", e);
+                }
+            }
+            return;
+        }
+        let (sender, receiver) = match self.gossip.subscribe(topic.into(), peers) {
+            Ok(subscription) => subscription.split(),
+            Err(e) => {
+                tracing::warn!("node::iroh::gossip: failed to join {}: {}", topic, e);
+                return;
+            }
+        };
+        let task = tokio::spawn(self.clone().deliver(topic, receiver));
+        topics.insert(topic, Subscription { sender, task });
+    }
+
+    pub async fn leave(&self, topic: TopicId) {
+        self.topics.write().await.remove(&topic);
+    }
+
+    /// Someone to connect to on `topic`, if we're on it
+    pub async fn add_neighbor(&self, topic: TopicId, node_id: NodeId) {
+        self.join(topic, [node_id]).await;
+    }
+
+    /// Sign `announcement` and send it out on `topic`
+    pub async fn broadcast(&self, topic: TopicId, announcement: Announcement) -> Result<()> {
+        let payload = Payload {
+            topic,
+            from: self.secret_key.public(),
+            timestamp: unix_now(),
+            announcement,
+        };
+        let data = serde_json::to_vec(&SignedMessage::sign(&self.secret_key, &payload)?)?;
+        let topics = self.topics.read().await;
+        let Some(subscription) = topics.get(&topic) else {
+            return Ok(());
+        };
+        subscription.sender.broadcast(Bytes::from(data)).await?;
+        Ok(())
+    }
+
+    /// Check and pass on what arrives on `topic`, until we leave it
+    async fn deliver(self, topic: TopicId, mut receiver: GossipReceiver) {
+        while let Some(event) = receiver.next().await {
+            match event {
+                Ok(Event::Gossip(iroh_gossip::net::GossipEvent::Received(message))) => {
+                    if let Err(e) = self.receive(topic, &message.content) {
+                        tracing::debug!(
+                            "node::iroh::gossip: dropped message from {}: {}",
+                            message.delivered_from,
+                            e
+                        );
+                    }
+                }
+                Ok(Event::Lagged) => {
+                    tracing::warn!("node::iroh::gossip: missed messages on {}", topic);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!("node::iroh::gossip: stopped listening on {}: {}", topic, e);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn receive(&self, topic: TopicId, data: &[u8]) -> Result<()> {
+        let message: SignedMessage = serde_json::from_slice(data)?;
+        let payload = message.verify()?;
+        // signed for another topic, and replayed on this one
+        if payload.topic != topic {
+            bail!("message for {} sent on {}", payload.topic, topic);
+        }
+        if payload.from != self.secret_key.public() {
+            let _ = self.events.send(GossipEvent {
+                topic,
+                from: payload.from,
+                announcement: payload.announcement,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl ProtocolHandler for Gossip {
+    fn accept(&self, conn: Connecting) -> futures::future::BoxFuture<'static, Result<()>> {
+        self.gossip.accept(conn)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn bind(seed: u8) -> (Gossip, Endpoint, iroh::protocol::Router) {
+        let secret_key = SecretKey::from_bytes(&[seed; 32]);
+        let endpoint = Endpoint::builder()
+            .secret_key(secret_key.clone())
+            .relay_mode(iroh::RelayMode::Disabled)
+            .bind_addr_v4("127.0.0.1:0".parse().unwrap())
+            .bind()
+            .await
+            .unwrap();
+        let gossip = Gossip::new(endpoint.clone(), secret_key).await.unwrap();
+        let router = iroh::protocol::Router::builder(endpoint.clone())
+            .accept(GOSSIP_ALPN, gossip.clone())
+            .spawn()
+            .await
+            .unwrap();
+        (gossip, endpoint, router)
+    }
+
+    fn node_addr(endpoint: &Endpoint) -> iroh::NodeAddr {
+        iroh::NodeAddr::from_parts(endpoint.node_id(), None, vec![endpoint.bound_sockets().0])
+    }
+
+    #[test]
+    fn test_signed_message() {
+        let secret_key = SecretKey::from_bytes(&[1u8; 32]);
+        let payload = Payload {
+            topic: TopicId::for_pool(Address::repeat_byte(1)),
+            from: secret_key.public(),
+            timestamp: unix_now(),
+            announcement: Announcement::Have {
+                hash: Hash::new(b"content"),
+            },
+        };
+        let message = SignedMessage::sign(&secret_key, &payload).unwrap();
+        assert!(message.verify().is_ok());
+
+        // someone else's key can't speak for the sender
+        let forged = SignedMessage {
+            payload: message.payload.clone(),
+            signature: SecretKey::from_bytes(&[2u8; 32]).sign(message.payload.as_bytes()),
+        };
+        assert!(forged.verify().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_relay_through_neighbor() {
+        // a only knows b, c only knows b -- b passes a's message on
+        let (a, endpoint_a, router_a) = bind(11).await;
+        let (b, endpoint_b, router_b) = bind(12).await;
+        let (c, endpoint_c, router_c) = bind(13).await;
+        let topic = TopicId::for_pool(Address::repeat_byte(7));
+        b.join(topic, std::iter::empty()).await;
+        for (gossip, endpoint) in [(&a, &endpoint_a), (&c, &endpoint_c)] {
+            endpoint.add_node_addr(node_addr(&endpoint_b)).unwrap();
+            gossip.join(topic, [endpoint_b.node_id()]).await;
+        }
+
+        let mut events = c.subscribe();
+        let hash = Hash::new(b"content");
+        // the swarm takes a moment to form -- keep announcing until it has
+        let event = tokio::time::timeout(Duration::from_secs(10), async {
+            let mut retry = tokio::time::interval(Duration::from_millis(500));
+            loop {
+                tokio::select! {
+                    event = events.recv() => break event.unwrap(),
+                    _ = retry.tick() => a.broadcast(topic, Announcement::Have { hash }).await.unwrap(),
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(event.topic, topic);
+        assert_eq!(event.from, endpoint_a.node_id());
+        assert_eq!(event.announcement, Announcement::Have { hash });
+
+        for router in [router_a, router_b, router_c] {
+            router.shutdown().await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replayed_on_another_topic() {
+        let (gossip, _endpoint, router) = bind(14).await;
+        let secret_key = SecretKey::from_bytes(&[15u8; 32]);
+        let topic = TopicId::for_pool(Address::repeat_byte(1));
+        let payload = Payload {
+            topic,
+            from: secret_key.public(),
+            timestamp: unix_now(),
+            announcement: Announcement::Have {
+                hash: Hash::new(b"content"),
+            },
+        };
+        let data =
+            serde_json::to_vec(&SignedMessage::sign(&secret_key, &payload).unwrap()).unwrap();
+
+        let mut events = gossip.subscribe();
+        gossip.receive(topic, &data).unwrap();
+        assert_eq!(events.try_recv().unwrap().from, secret_key.public());
+        // the signature is good, but it wasn't meant for this topic
+        let other = TopicId::for_pool(Address::repeat_byte(2));
+        assert!(gossip.receive(other, &data).is_err());
+        assert!(events.try_recv().is_err());
+
+        router.shutdown().await.unwrap();
+    }
+}
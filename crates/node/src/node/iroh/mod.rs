@@ -1,9 +1,39 @@
+mod accounting;
+mod acl;
 mod blobs_service;
+mod challenge;
+mod codec;
+mod diagnostics;
+mod encryption;
 mod endpoint;
+mod gossip;
 mod probe;
+mod prober;
+pub mod protocol;
+mod retention;
 mod router;
+mod serve;
+mod tag_index;
+#[cfg(test)]
+mod test_utils;
+mod transfers;
 
-pub use blobs_service::BlobsService;
-pub use endpoint::{await_relay_region, create_endpoint, create_ephemeral_endpoint};
-pub use probe::probe_complete;
+pub use accounting::Counters;
+pub use acl::{AclEntry, AclUpdate};
+pub use blobs_service::{BlobsService, ExportLink, FetchProgress, ImportMode, ImportProgress};
+pub use challenge::{challenge, ChallengeResult};
+pub use diagnostics::{AddrSource, Diagnostics, NatType, PathKind};
+pub use encryption::{
+    ciphertext_size, encrypt_stream, plaintext_chunk, ContentKey, Decryptor, EncryptedTicket,
+    SEALED_CHUNK_SIZE,
+};
+pub use endpoint::{
+    await_relay_region, create_endpoint, create_ephemeral_endpoint, EndpointOptions,
+};
+pub use gossip::{Announcement, Gossip, GossipEvent, ProbeOutcome, TopicId};
+pub use probe::{detect_format, probe_complete};
+pub use prober::Prober;
+pub use retention::{BlobTag, GcReport, RetentionPolicy};
 pub use router::router;
+pub use serve::ServeContext;
+pub use transfers::TransferInfo;
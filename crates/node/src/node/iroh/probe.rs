@@ -1,18 +1,27 @@
+use std::sync::Arc;
+
 use bao_tree::{ChunkNum, ChunkRanges};
-use iroh::{Endpoint, NodeId};
+use bytes::Bytes;
+use iroh::NodeId;
 use iroh_blobs::{
     get::{
         fsm::{BlobContentNext, EndBlobNext},
         Stats,
     },
+    hashseq::HashSeq,
     protocol::GetRequest,
     protocol::RangeSpecSeq,
     BlobFormat, Hash, HashAndFormat,
 };
 use rand::{Rng, SeedableRng};
 
+use super::prober::Prober;
+
+/// Refuse to parse hash seqs with more than ~3 million children
+const MAX_HASH_SEQ_SIZE: u64 = 1024 * 1024 * 100;
+
 pub async fn probe_complete(
-    endpoint: &Endpoint,
+    prober: &Prober,
     host: &NodeId,
     content: &HashAndFormat,
 ) -> anyhow::Result<Stats> {
@@ -20,7 +29,7 @@ pub async fn probe_complete(
     let HashAndFormat { hash, format } = content;
 
     // connect over the blobs protocol
-    let connection = endpoint.connect(*host, iroh_blobs::protocol::ALPN).await?;
+    let connection = prober.connect(*host, iroh_blobs::protocol::ALPN).await?;
 
     // Create a Send-compatible RNG
     let mut rng = rand::rngs::StdRng::from_entropy();
@@ -28,7 +37,9 @@ pub async fn probe_complete(
     match format {
         BlobFormat::Raw => {
             let size = get_or_insert_size(&connection, hash).await?;
-            let random_chunk = rng.gen_range(0..bao_tree::ChunkNum::chunks(size).0);
+            // an empty blob is still one (empty) chunk
+            let chunks = bao_tree::ChunkNum::chunks(size).0.max(1);
+            let random_chunk = rng.gen_range(0..chunks);
             tracing::debug!("Chunk probing {}, chunk {}", cap, random_chunk);
             let stats = chunk_probe(&connection, hash, bao_tree::ChunkNum(random_chunk)).await?;
             tracing::debug!(
@@ -39,45 +50,42 @@ pub async fn probe_complete(
             );
             Ok(stats)
         }
-        _ => {
-            tracing::debug!("Unsupported format: {}", format);
-            anyhow::bail!("unsupported format");
-        } // BlobFormat::HashSeq => {
-          //     let (hs, sizes) = get_hash_seq_and_sizes(connection, hash, 1024 * 1024 * 100).await?;
-          //     let ranges = random_hash_seq_ranges(&sizes, rng);
-          //     let text = ranges
-          //         .iter_non_empty()
-          //         .map(|(index, ranges)| {
-          //             format!("child={}, ranges={:?}", index, ranges.to_chunk_ranges())
-          //         })
-          //         .collect::<Vec<_>>()
-          //         .join(", ");
-          //     tracing::debug!("Seq probing {} using {}", cap, text);
-          //     let request = GetRequest::new(*hash, ranges);
-          //     let request = iroh_blobs::get::fsm::start(connection.clone(), request);
-          //     let connected = request.next().await?;
-          //     let iroh_blobs::get::fsm::ConnectedNext::StartChild(child) =
-          //         connected.next().await?
-          //     else {
-          //         unreachable!("request does not include root");
-          //     };
-          //     let index =
-          //         usize::try_from(child.child_offset()).expect("child offset too large");
-          //     let hash = hs.get(index).expect("request inconsistent with hash seq");
-          //     let at_blob_header = child.next(hash);
-          //     let at_end_blob = at_blob_header.drain().await?;
-          //     let EndBlobNext::Closing(closing) = at_end_blob.next() else {
-          //         unreachable!("request contains only one blob");
-          //     };
-          //     let stats = closing.next().await?;
-          //     tracing::debug!(
-          //         "Seq probed {} using {}, {:.6}s",
-          //         cap,
-          //         text,
-          //         stats.elapsed.as_secs_f64()
-          //     );
-          //     Ok(stats)
-          // }
+        BlobFormat::HashSeq => {
+            let (hs, sizes) = get_hash_seq_and_sizes(&connection, hash, MAX_HASH_SEQ_SIZE).await?;
+            let ranges = random_hash_seq_ranges(&sizes, rng)?;
+            let text = ranges
+                .iter_non_empty()
+                .map(|(index, ranges)| {
+                    format!("child={}, ranges={:?}", index, ranges.to_chunk_ranges())
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            tracing::debug!("Seq probing {} using {}", cap, text);
+            let request = GetRequest::new(*hash, ranges);
+            let request = iroh_blobs::get::fsm::start(connection.clone(), request);
+            let connected = request.next().await?;
+            let iroh_blobs::get::fsm::ConnectedNext::StartChild(child) = connected.next().await?
+            else {
+                anyhow::bail!("expected to start at a child of {}", cap);
+            };
+            let index = usize::try_from(child.child_offset())?;
+            let hash = hs
+                .get(index)
+                .ok_or_else(|| anyhow::anyhow!("request inconsistent with hash seq"))?;
+            let at_blob_header = child.next(hash);
+            let at_end_blob = at_blob_header.drain().await?;
+            let EndBlobNext::Closing(closing) = at_end_blob.next() else {
+                anyhow::bail!("expected a single child of {}", cap);
+            };
+            let stats = closing.next().await?;
+            tracing::debug!(
+                "Seq probed {} using {}, {:.6}s",
+                cap,
+                text,
+                stats.elapsed.as_secs_f64()
+            );
+            Ok(stats)
+        }
     }
 }
 
@@ -161,84 +169,177 @@ pub async fn chunk_probe(
     Ok(stats)
 }
 
-// pub async fn get_hash_seq_and_sizes(
-//     connection: &iroh::endpoint::Connection,
-//     hash: &Hash,
-//     max_size: u64,
-// ) -> anyhow::Result<(HashSeq, Arc<[u64]>)> {
-//     let content = HashAndFormat::hash_seq(*hash);
-//     tracing::debug!("Getting hash seq and children sizes of {}", content);
-//     let request = iroh_blobs::protocol::GetRequest::new(
-//         *hash,
-//         RangeSpecSeq::from_ranges_infinite([
-//             ChunkRanges::all(),
-//             ChunkRanges::from(ChunkNum(u64::MAX)..),
-//         ]),
-//     );
-//     let at_start = iroh_blobs::get::fsm::start(connection.clone(), request);
-//     let at_connected = at_start.next().await?;
-//     let iroh_blobs::get::fsm::ConnectedNext::StartRoot(start) = at_connected.next().await? else {
-//         unreachable!("query includes root");
-//     };
-//     let at_start_root = start.next();
-//     let (at_blob_content, size) = at_start_root.next().await?;
-//     // check the size to avoid parsing a maliciously large hash seq
-//     if size > max_size {
-//         anyhow::bail!("size too large");
-//     }
-//     let (mut curr, hash_seq) = at_blob_content.concatenate_into_vec().await?;
-//     let hash_seq = HashSeq::try_from(Bytes::from(hash_seq))?;
-//     let mut sizes = Vec::with_capacity(hash_seq.len());
-//     let closing = loop {
-//         match curr.next() {
-//             EndBlobNext::MoreChildren(more) => {
-//                 let hash = match hash_seq.get(sizes.len()) {
-//                     Some(hash) => hash,
-//                     None => break more.finish(),
-//                 };
-//                 let at_header = more.next(hash);
-//                 let (at_content, size) = at_header.next().await?;
-//                 let next = at_content.drain().await?;
-//                 sizes.push(size);
-//                 curr = next;
-//             }
-//             EndBlobNext::Closing(closing) => break closing,
-//         }
-//     };
-//     let _stats = closing.next().await?;
-//     tracing::debug!(
-//         "Got hash seq and children sizes of {}: {:?}",
-//         content,
-//         sizes
-//     );
-//     Ok((hash_seq, sizes.into()))
-// }
-
-// /// Given a sequence of sizes of children, generate a range spec that selects a
-// /// random chunk of a random child.
-// ///
-// /// The random chunk is chosen uniformly from the chunks of the children, so
-// /// larger children are more likely to be selected.
-// pub fn random_hash_seq_ranges(sizes: &[u64], mut rng: impl Rng + Send) -> RangeSpecSeq {
-//     let total_chunks = sizes
-//         .iter()
-//         .map(|size| ChunkNum::full_chunks(*size).0)
-//         .sum::<u64>();
-//     let random_chunk = rng.gen_range(0..total_chunks);
-//     let mut remaining = random_chunk;
-//     let mut ranges = vec![];
-//     ranges.push(ChunkRanges::empty());
-//     for size in sizes.iter() {
-//         let chunks = ChunkNum::full_chunks(*size).0;
-//         if remaining < chunks {
-//             ranges.push(ChunkRanges::from(
-//                 ChunkNum(remaining)..ChunkNum(remaining + 1),
-//             ));
-//             break;
-//         } else {
-//             remaining -= chunks;
-//             ranges.push(ChunkRanges::empty());
-//         }
-//     }
-//     RangeSpecSeq::from_ranges(ranges)
-// }
+/// Fetch a hash seq and the verified sizes of all of its children
+pub async fn get_hash_seq_and_sizes(
+    connection: &iroh::endpoint::Connection,
+    hash: &Hash,
+    max_size: u64,
+) -> anyhow::Result<(HashSeq, Arc<[u64]>)> {
+    let content = HashAndFormat::hash_seq(*hash);
+    tracing::debug!("Getting hash seq and children sizes of {}", content);
+    let request = iroh_blobs::protocol::GetRequest::new(
+        *hash,
+        RangeSpecSeq::from_ranges_infinite([
+            ChunkRanges::all(),
+            ChunkRanges::from(ChunkNum(u64::MAX)..),
+        ]),
+    );
+    let at_start = iroh_blobs::get::fsm::start(connection.clone(), request);
+    let at_connected = at_start.next().await?;
+    let iroh_blobs::get::fsm::ConnectedNext::StartRoot(start) = at_connected.next().await? else {
+        unreachable!("query includes root");
+    };
+    let at_start_root = start.next();
+    let (at_blob_content, size) = at_start_root.next().await?;
+    // check the size to avoid parsing a maliciously large hash seq
+    if size > max_size {
+        anyhow::bail!("size too large");
+    }
+    let (mut curr, hash_seq) = at_blob_content.concatenate_into_vec().await?;
+    let hash_seq = HashSeq::try_from(Bytes::from(hash_seq))?;
+    let mut sizes = Vec::with_capacity(hash_seq.len());
+    let closing = loop {
+        match curr.next() {
+            EndBlobNext::MoreChildren(more) => {
+                let hash = match hash_seq.get(sizes.len()) {
+                    Some(hash) => hash,
+                    None => break more.finish(),
+                };
+                let at_header = more.next(hash);
+                let (at_content, size) = at_header.next().await?;
+                let next = at_content.drain().await?;
+                sizes.push(size);
+                curr = next;
+            }
+            EndBlobNext::Closing(closing) => break closing,
+        }
+    };
+    let _stats = closing.next().await?;
+    tracing::debug!(
+        "Got hash seq and children sizes of {}: {:?}",
+        content,
+        sizes
+    );
+    Ok((hash_seq, sizes.into()))
+}
+
+/// Given a sequence of sizes of children, generate a range spec that selects a
+/// random chunk of a random child.
+///
+/// The random chunk is chosen uniformly from the chunks of the children, so
+/// larger children are more likely to be selected.
+pub fn random_hash_seq_ranges(
+    sizes: &[u64],
+    mut rng: impl Rng + Send,
+) -> anyhow::Result<RangeSpecSeq> {
+    // count partial chunks too, otherwise children under a chunk are never picked
+    let total_chunks = sizes
+        .iter()
+        .map(|size| ChunkNum::chunks(*size).0)
+        .sum::<u64>();
+    if total_chunks == 0 {
+        anyhow::bail!("hash seq has no content to probe");
+    }
+    let random_chunk = rng.gen_range(0..total_chunks);
+    let mut remaining = random_chunk;
+    let mut ranges = vec![];
+    ranges.push(ChunkRanges::empty());
+    for size in sizes.iter() {
+        let chunks = ChunkNum::chunks(*size).0;
+        if remaining < chunks {
+            ranges.push(ChunkRanges::from(
+                ChunkNum(remaining)..ChunkNum(remaining + 1),
+            ));
+            break;
+        } else {
+            remaining -= chunks;
+            ranges.push(ChunkRanges::empty());
+        }
+    }
+    Ok(RangeSpecSeq::from_ranges(ranges))
+}
+
+/// Work out whether `hash` is a hash seq on `host`. Anything that doesn't
+///  parse as a hash seq with children `host` can serve is treated as raw.
+pub async fn detect_format(
+    prober: &Prober,
+    host: &NodeId,
+    hash: &Hash,
+) -> anyhow::Result<BlobFormat> {
+    let connection = prober.connect(*host, iroh_blobs::protocol::ALPN).await?;
+    let (size, _) = verified_size(&connection, hash).await?;
+    if size == 0 || size % 32 != 0 || size > MAX_HASH_SEQ_SIZE {
+        return Ok(BlobFormat::Raw);
+    }
+    match get_hash_seq_and_sizes(&connection, hash, MAX_HASH_SEQ_SIZE).await {
+        Ok((hs, sizes)) if !hs.is_empty() && sizes.len() == hs.len() => Ok(BlobFormat::HashSeq),
+        _ => Ok(BlobFormat::Raw),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iroh::protocol::Router;
+
+    use super::*;
+    use crate::config::{DiscoveryMethod, RelayConfig, StaticPeer};
+    use crate::node::iroh::test_utils::test_blobs_service;
+    use crate::node::iroh::EndpointOptions;
+
+    #[test]
+    fn test_random_hash_seq_ranges_picks_one_chunk() {
+        let sizes = [10, 0, 4096, 1];
+        for seed in 0..32 {
+            let rng = rand::rngs::StdRng::seed_from_u64(seed);
+            let ranges = random_hash_seq_ranges(&sizes, rng).unwrap();
+            let non_empty = ranges.iter_non_empty().collect::<Vec<_>>();
+            assert_eq!(non_empty.len(), 1);
+            let (index, _) = non_empty[0];
+            // offset 0 is the root, and the empty child can never be chosen
+            assert!(index >= 1 && index != 2);
+        }
+    }
+
+    #[test]
+    fn test_random_hash_seq_ranges_empty() {
+        let rng = rand::rngs::StdRng::seed_from_u64(0);
+        assert!(random_hash_seq_ranges(&[0, 0], rng).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_probe_empty_blob() {
+        let (blobs_service, _dir) = test_blobs_service().await;
+        let empty = blobs_service.store_blob(Vec::new()).await.unwrap();
+        let host = blobs_service.get_inner_blobs().endpoint().clone();
+        let router = Router::builder(host.clone())
+            .accept(
+                iroh_blobs::protocol::ALPN,
+                blobs_service.get_inner_blobs().clone(),
+            )
+            .spawn()
+            .await
+            .unwrap();
+        let addrs = host
+            .node_addr()
+            .await
+            .unwrap()
+            .direct_addresses
+            .into_iter()
+            .collect();
+        let discovery = vec![DiscoveryMethod::Static {
+            peers: vec![StaticPeer {
+                node_id: host.node_id(),
+                addrs,
+                relay_url: None,
+            }],
+        }];
+        let prober = Prober::new(EndpointOptions::new(discovery, RelayConfig::Disabled));
+
+        probe_complete(&prober, &host.node_id(), &HashAndFormat::raw(empty))
+            .await
+            .unwrap();
+
+        prober.close().await;
+        router.shutdown().await.unwrap();
+    }
+}
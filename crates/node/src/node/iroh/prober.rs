@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use iroh::endpoint::Connection;
+use iroh::{Endpoint, NodeId};
+use tokio::sync::Mutex;
+
+use super::endpoint::{create_ephemeral_endpoint, EndpointOptions};
+
+/// How many probe endpoints we spread probes across
+const PROBE_ENDPOINTS: usize = 2;
+/// How long a probe endpoint keeps its identity before we replace it.
+///  Short enough that a peer can't learn to recognise our probes and
+///  answer them faster than everyone else.
+const IDENTITY_LIFETIME: Duration = Duration::from_secs(10 * 60);
+/// How long a replaced endpoint stays up for probes still using it
+const RETIRE_GRACE: Duration = Duration::from_secs(30);
+
+type ConnectionKey = (NodeId, Vec<u8>);
+
+/// An ephemeral endpoint and the connections it has open
+struct ProbeEndpoint {
+    endpoint: Endpoint,
+    created: Instant,
+    connections: Mutex<HashMap<ConnectionKey, Connection>>,
+}
+
+impl ProbeEndpoint {
+    fn is_fresh(&self) -> bool {
+        self.created.elapsed() < IDENTITY_LIFETIME
+    }
+}
+
+/// A few long-lived endpoints to probe and challenge peers from, so we
+///  don't bind a socket and start DHT lookups for every probe. None of
+///  them share our node's identity, and each is swapped for a fresh one
+///  every so often.
+#[derive(Clone)]
+pub struct Prober {
+    options: Arc<EndpointOptions>,
+    endpoints: Arc<Mutex<[Option<Arc<ProbeEndpoint>>; PROBE_ENDPOINTS]>>,
+    next: Arc<AtomicUsize>,
+}
+
+impl Prober {
+    /// Endpoints are only brought up once there's something to probe
+    pub fn new(options: EndpointOptions) -> Self {
+        Self {
+            options: Arc::new(options),
+            endpoints: Arc::default(),
+            next: Arc::default(),
+        }
+    }
+
+    /// A connection to `node_id` speaking `alpn`, reused if we already
+    ///  have one open
+    pub async fn connect(&self, node_id: NodeId, alpn: &[u8]) -> Result<Connection> {
+        let probe_endpoint = self.endpoint().await?;
+        let key = (node_id, alpn.to_vec());
+        if let Some(connection) = probe_endpoint.connections.lock().await.get(&key) {
+            if connection.close_reason().is_none() {
+                return Ok(connection.clone());
+            }
+        }
+        let connection = probe_endpoint.endpoint.connect(node_id, alpn).await?;
+        probe_endpoint
+            .connections
+            .lock()
+            .await
+            .insert(key, connection.clone());
+        Ok(connection)
+    }
+
+    /// Drop any connections to `node_id`, say after it failed to answer
+    ///  -- the next probe dials it again
+    pub async fn forget(&self, node_id: &NodeId) {
+        let endpoints = self.endpoints.lock().await.clone();
+        for probe_endpoint in endpoints.iter().flatten() {
+            probe_endpoint
+                .connections
+                .lock()
+                .await
+                .retain(|(peer, _), _| peer != node_id);
+        }
+    }
+
+    /// Close every endpoint, for shutdown
+    pub async fn close(&self) {
+        let endpoints = std::mem::take(&mut *self.endpoints.lock().await);
+        for probe_endpoint in endpoints.into_iter().flatten() {
+            probe_endpoint.endpoint.close().await;
+        }
+    }
+
+    /// The next endpoint in turn, bringing up or replacing it as needed
+    async fn endpoint(&self) -> Result<Arc<ProbeEndpoint>> {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % PROBE_ENDPOINTS;
+        if let Some(probe_endpoint) = self.current(index).await {
+            return Ok(probe_endpoint);
+        }
+
+        // bind without holding the lock, so probes through the other
+        //  slots don't wait on it
+        let fresh = Arc::new(ProbeEndpoint {
+            endpoint: create_ephemeral_endpoint(&self.options).await?,
+            created: Instant::now(),
+            connections: Mutex::new(HashMap::new()),
+        });
+        let mut endpoints = self.endpoints.lock().await;
+        let slot = &mut endpoints[index];
+        // another probe got there first while we were binding
+        if let Some(current) = slot.clone().filter(|current| current.is_fresh()) {
+            drop(endpoints);
+            fresh.endpoint.close().await;
+            return Ok(current);
+        }
+        tracing::debug!(
+            "node::iroh::prober: probing from {} in slot {}",
+            fresh.endpoint.node_id(),
+            index
+        );
+        if let Some(retired) = slot.replace(fresh.clone()) {
+            tokio::spawn(async move {
+                tokio::time::sleep(RETIRE_GRACE).await;
+                retired.endpoint.close().await;
+            });
+        }
+        Ok(fresh)
+    }
+
+    /// The endpoint in slot `index`, unless it's yet to be brought up
+    ///  or due for replacing
+    async fn current(&self, index: usize) -> Option<Arc<ProbeEndpoint>> {
+        self.endpoints.lock().await[index]
+            .clone()
+            .filter(|probe_endpoint| probe_endpoint.is_fresh())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use iroh::endpoint::Connecting;
+    use iroh::protocol::{ProtocolHandler, Router};
+
+    use super::*;
+    use crate::config::{DiscoveryMethod, RelayConfig, StaticPeer};
+    use crate::node::iroh::test_utils::test_endpoint;
+
+    const TEST_ALPN: &[u8] = b"jax/test/0";
+
+    /// Holds connections open until the other side closes them
+    #[derive(Debug, Clone)]
+    struct Hold;
+
+    impl ProtocolHandler for Hold {
+        fn accept(&self, conn: Connecting) -> futures::future::BoxFuture<'static, Result<()>> {
+            Box::pin(async move {
+                conn.await?.closed().await;
+                Ok(())
+            })
+        }
+    }
+
+    fn offline(discovery: Vec<DiscoveryMethod>) -> EndpointOptions {
+        EndpointOptions::new(discovery, RelayConfig::Disabled)
+    }
+
+    #[tokio::test]
+    async fn test_endpoints_rotate() {
+        let prober = Prober::new(offline(Vec::new()));
+        let mut endpoints = Vec::new();
+        for _ in 0..2 * PROBE_ENDPOINTS {
+            endpoints.push(prober.endpoint().await.unwrap());
+        }
+        for (i, probe_endpoint) in endpoints.iter().enumerate().skip(PROBE_ENDPOINTS) {
+            assert!(Arc::ptr_eq(probe_endpoint, &endpoints[i - PROBE_ENDPOINTS]));
+        }
+        let ids = endpoints
+            .iter()
+            .map(|probe_endpoint| probe_endpoint.endpoint.node_id())
+            .collect::<HashSet<_>>();
+        assert_eq!(ids.len(), PROBE_ENDPOINTS);
+        prober.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_endpoints() {
+        let prober = Prober::new(offline(Vec::new()));
+        // every slot is raced for, but only one endpoint per slot survives
+        let endpoints =
+            futures::future::try_join_all((0..4 * PROBE_ENDPOINTS).map(|_| prober.endpoint()))
+                .await
+                .unwrap();
+        let ids = endpoints
+            .iter()
+            .map(|probe_endpoint| probe_endpoint.endpoint.node_id())
+            .collect::<HashSet<_>>();
+        assert_eq!(ids.len(), PROBE_ENDPOINTS);
+        prober.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_connections_reused_until_forgotten() {
+        let host = test_endpoint().await;
+        let router = Router::builder(host.clone())
+            .accept(TEST_ALPN, Hold)
+            .spawn()
+            .await
+            .unwrap();
+        let addrs = host
+            .node_addr()
+            .await
+            .unwrap()
+            .direct_addresses
+            .into_iter()
+            .collect();
+        let prober = Prober::new(offline(vec![DiscoveryMethod::Static {
+            peers: vec![StaticPeer {
+                node_id: host.node_id(),
+                addrs,
+                relay_url: None,
+            }],
+        }]));
+
+        // one connection per endpoint, handed out again on the next turn
+        let mut ids = Vec::new();
+        for _ in 0..2 * PROBE_ENDPOINTS {
+            let connection = prober.connect(host.node_id(), TEST_ALPN).await.unwrap();
+            ids.push(connection.stable_id());
+        }
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(ids[..PROBE_ENDPOINTS], ids[PROBE_ENDPOINTS..]);
+
+        prober.forget(&host.node_id()).await;
+        let connection = prober.connect(host.node_id(), TEST_ALPN).await.unwrap();
+        assert!(!ids.contains(&connection.stable_id()));
+
+        prober.close().await;
+        router.shutdown().await.unwrap();
+    }
+}
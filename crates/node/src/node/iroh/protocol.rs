@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+
+use alloy::primitives::Address;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use ed25519::Signature;
+use iroh::endpoint::{Connecting, Connection, RecvStream, SendStream};
+use iroh::protocol::ProtocolHandler;
+use iroh::NodeId;
+use iroh_blobs::{BlobFormat, Hash};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::codec::{read_message, write_message};
+
+/// ALPN for node-to-node metadata. The number is the major version --
+///  anything that breaks old peers gets a new ALPN.
+pub const JAX_ALPN: &[u8] = b"jax/1";
+/// Version stamped on every frame, bumped for changes old peers can
+///  still make sense of
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// Largest frame we'll read -- a page of hashes fits comfortably
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+/// How many hashes we hand out per request
+const HASHES_PAGE_SIZE: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// The pools the node takes part in
+    Pools,
+    /// A page of the hashes the node holds in full, in order, starting
+    ///  after `after`
+    Hashes { after: Option<Hash> },
+    /// The node's local trust in each of a pool's peers
+    TrustRow { pool: Address },
+    /// The node's beneficiary, signed with its iroh key
+    Identity,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Pools {
+        pools: Vec<PoolAnnouncement>,
+    },
+    Hashes {
+        hashes: Vec<Hash>,
+        /// Where the next page starts, if there is one
+        next: Option<Hash>,
+    },
+    TrustRow {
+        pool: Address,
+        /// None if the node doesn't track the pool
+        row: Option<HashMap<NodeId, f64>>,
+    },
+    Identity(NodeIdentity),
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolAnnouncement {
+    pub address: Address,
+    pub hash: Hash,
+    pub format: BlobFormat,
+}
+
+/// Ties a node id to the eth address its rewards go to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeIdentity {
+    pub node_id: NodeId,
+    pub beneficiary: Address,
+    /// The node's signature over the beneficiary address bytes
+    #[serde(with = "signature_hex")]
+    pub signature: Signature,
+}
+
+impl NodeIdentity {
+    /// Whether the signature really is `node_id`'s, over `beneficiary`
+    pub fn verify(&self) -> bool {
+        self.node_id
+            .verify(self.beneficiary.as_slice(), &self.signature)
+            .is_ok()
+    }
+}
+
+pub(super) mod signature_hex {
+    use ed25519::Signature;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        signature: &Signature,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(signature.to_bytes()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Signature, D::Error> {
+        let bytes = hex::decode(String::deserialize(deserializer)?).map_err(D::Error::custom)?;
+        Signature::from_slice(&bytes).map_err(D::Error::custom)
+    }
+}
+
+/// What a node tells its peers about itself
+#[async_trait]
+pub trait NodeMetadata: Send + Sync + 'static {
+    async fn pools(&self) -> Result<Vec<PoolAnnouncement>>;
+    /// Up to `limit` of the hashes held in full, in order, starting
+    ///  after `after`
+    async fn hashes(&self, after: Option<Hash>, limit: usize) -> Result<Vec<Hash>>;
+    async fn trust_row(&self, pool: Address) -> Result<Option<HashMap<NodeId, f64>>>;
+    fn identity(&self) -> NodeIdentity;
+}
+
+/// Write a message behind the protocol version
+async fn write_frame<T: Serialize>(send: &mut SendStream, message: &T) -> Result<()> {
+    send.write_all(&[PROTOCOL_VERSION]).await?;
+    write_message(send, message, MAX_MESSAGE_SIZE).await
+}
+
+/// Read a frame, along with the version it was sent with
+async fn read_frame<T: DeserializeOwned>(recv: &mut RecvStream) -> Result<(u8, T)> {
+    let mut version = [0u8; 1];
+    recv.read_exact(&mut version).await?;
+    let message = read_message(recv, MAX_MESSAGE_SIZE).await;
+    if version[0] != PROTOCOL_VERSION {
+        bail!("unsupported protocol version {}", version[0]);
+    }
+    Ok((version[0], message?))
+}
+
+/// Serving side of the jax protocol
+pub struct JaxProtocol<M> {
+    metadata: std::sync::Arc<M>,
+}
+
+impl<M> Clone for JaxProtocol<M> {
+    fn clone(&self) -> Self {
+        Self {
+            metadata: self.metadata.clone(),
+        }
+    }
+}
+
+impl<M> std::fmt::Debug for JaxProtocol<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JaxProtocol").finish_non_exhaustive()
+    }
+}
+
+impl<M: NodeMetadata> JaxProtocol<M> {
+    pub fn new(metadata: M) -> Self {
+        Self {
+            metadata: std::sync::Arc::new(metadata),
+        }
+    }
+
+    async fn respond(&self, request: Request) -> Result<Response> {
+        Ok(match request {
+            Request::Pools => Response::Pools {
+                pools: self.metadata.pools().await?,
+            },
+            Request::Hashes { after } => {
+                // one past the page tells us whether there's another
+                let mut hashes = self.metadata.hashes(after, HASHES_PAGE_SIZE + 1).await?;
+                let next = if hashes.len() > HASHES_PAGE_SIZE {
+                    hashes.truncate(HASHES_PAGE_SIZE);
+                    hashes.last().copied()
+                } else {
+                    None
+                };
+                Response::Hashes { hashes, next }
+            }
+            Request::TrustRow { pool } => Response::TrustRow {
+                pool,
+                row: self.metadata.trust_row(pool).await?,
+            },
+            Request::Identity => Response::Identity(self.metadata.identity()),
+        })
+    }
+
+    async fn handle(&self, connecting: Connecting) -> Result<()> {
+        let connection = connecting.await?;
+        loop {
+            let (mut send, mut recv) = match connection.accept_bi().await {
+                Ok(streams) => streams,
+                // the peer hung up, we're done
+                Err(_) => return Ok(()),
+            };
+            // a request we can't read gets an error back rather than a
+            //  dropped stream, so newer peers learn what we speak
+            let response = match read_frame::<Request>(&mut recv).await {
+                Ok((_, request)) => {
+                    self.respond(request)
+                        .await
+                        .unwrap_or_else(|e| Response::Error {
+                            message: e.to_string(),
+                        })
+                }
+                Err(e) => Response::Error {
+                    message: e.to_string(),
+                },
+            };
+            write_frame(&mut send, &response).await?;
+            send.finish()?;
+        }
+    }
+}
+
+impl<M: NodeMetadata> ProtocolHandler for JaxProtocol<M> {
+    fn accept(&self, conn: Connecting) -> futures::future::BoxFuture<'static, Result<()>> {
+        let this = self.clone();
+        Box::pin(async move {
+            if let Err(e) = this.handle(conn).await {
+                tracing::debug!("node::iroh::protocol: connection failed: {}", e);
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Ask a peer something over a `JAX_ALPN` connection
+pub async fn request(connection: &Connection, request: &Request) -> Result<Response> {
+    let (mut send, mut recv) = connection.open_bi().await?;
+    write_frame(&mut send, request).await?;
+    send.finish()?;
+    let (_, response) = read_frame(&mut recv).await?;
+    match response {
+        Response::Error { message } => Err(anyhow!("peer error: {}", message)),
+        response => Ok(response),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_roundtrip() {
+        let secret_key = iroh::SecretKey::from_bytes(&[5u8; 32]);
+        let beneficiary = Address::repeat_byte(0x42);
+        let identity = NodeIdentity {
+            node_id: secret_key.public(),
+            beneficiary,
+            signature: secret_key.sign(beneficiary.as_slice()),
+        };
+        assert!(identity.verify());
+
+        let json = serde_json::to_string(&Response::Identity(identity.clone())).unwrap();
+        let Response::Identity(decoded) = serde_json::from_str(&json).unwrap() else {
+            panic!("expected an identity");
+        };
+        assert_eq!(decoded, identity);
+
+        let forged = NodeIdentity {
+            beneficiary: Address::repeat_byte(0x43),
+            ..identity
+        };
+        assert!(!forged.verify());
+    }
+
+    struct Fixed {
+        identity: NodeIdentity,
+        hashes: Vec<Hash>,
+    }
+
+    #[async_trait]
+    impl NodeMetadata for Fixed {
+        async fn pools(&self) -> Result<Vec<PoolAnnouncement>> {
+            Ok(Vec::new())
+        }
+
+        async fn hashes(&self, after: Option<Hash>, limit: usize) -> Result<Vec<Hash>> {
+            Ok(self
+                .hashes
+                .iter()
+                .filter(|hash| after.is_none_or(|after| **hash > after))
+                .take(limit)
+                .copied()
+                .collect())
+        }
+
+        async fn trust_row(&self, _pool: Address) -> Result<Option<HashMap<NodeId, f64>>> {
+            Ok(None)
+        }
+
+        fn identity(&self) -> NodeIdentity {
+            self.identity.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_protocol_roundtrip() {
+        let secret_key = iroh::SecretKey::from_bytes(&[6u8; 32]);
+        let beneficiary = Address::repeat_byte(0x42);
+        let identity = NodeIdentity {
+            node_id: secret_key.public(),
+            beneficiary,
+            signature: secret_key.sign(beneficiary.as_slice()),
+        };
+        let mut hashes = (0..HASHES_PAGE_SIZE as u32 + 1)
+            .map(|i| Hash::new(i.to_be_bytes()))
+            .collect::<Vec<_>>();
+        hashes.sort();
+
+        let bind = |secret_key: iroh::SecretKey| {
+            iroh::Endpoint::builder()
+                .secret_key(secret_key)
+                .relay_mode(iroh::RelayMode::Disabled)
+                .bind_addr_v4("127.0.0.1:0".parse().unwrap())
+                .bind()
+        };
+        let endpoint = bind(secret_key).await.unwrap();
+        let router = iroh::protocol::Router::builder(endpoint.clone())
+            .accept(
+                JAX_ALPN,
+                JaxProtocol::new(Fixed {
+                    identity: identity.clone(),
+                    hashes: hashes.clone(),
+                }),
+            )
+            .spawn()
+            .await
+            .unwrap();
+
+        let client = bind(iroh::SecretKey::from_bytes(&[7u8; 32])).await.unwrap();
+        let node_addr =
+            iroh::NodeAddr::from_parts(endpoint.node_id(), None, vec![endpoint.bound_sockets().0]);
+        let connection = client.connect(node_addr, JAX_ALPN).await.unwrap();
+
+        let response = request(&connection, &Request::Identity).await.unwrap();
+        assert_eq!(response, Response::Identity(identity));
+
+        // the last hash spills onto a second page
+        let Response::Hashes { hashes: page, next } =
+            request(&connection, &Request::Hashes { after: None })
+                .await
+                .unwrap()
+        else {
+            panic!("expected hashes");
+        };
+        assert_eq!(page, hashes[..HASHES_PAGE_SIZE]);
+        assert_eq!(next, Some(hashes[HASHES_PAGE_SIZE - 1]));
+        let response = request(&connection, &Request::Hashes { after: next })
+            .await
+            .unwrap();
+        assert_eq!(
+            response,
+            Response::Hashes {
+                hashes: hashes[HASHES_PAGE_SIZE..].to_vec(),
+                next: None,
+            }
+        );
+
+        // a frame from some future version gets an error, not a hang-up
+        let (mut send, mut recv) = connection.open_bi().await.unwrap();
+        let data = serde_json::to_vec(&Request::Pools).unwrap();
+        send.write_all(&[PROTOCOL_VERSION + 1]).await.unwrap();
+        send.write_all(&(data.len() as u32).to_be_bytes())
+            .await
+            .unwrap();
+        send.write_all(&data).await.unwrap();
+        send.finish().unwrap();
+        let (_, response) = read_frame::<Response>(&mut recv).await.unwrap();
+        assert!(matches!(response, Response::Error { .. }));
+
+        client.close().await;
+        router.shutdown().await.unwrap();
+    }
+}
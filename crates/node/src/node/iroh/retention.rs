@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy::primitives::Address;
+use anyhow::Result;
+use iroh_blobs::util::Tag;
+use iroh_blobs::Hash;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+/// How often iroh-blobs GC sweeps anything no tag points at
+pub const GC_PERIOD: Duration = Duration::from_secs(30);
+
+const USER_SHARED_PREFIX: &str = "user-shared/";
+const PIN_PREFIX: &str = "pin/";
+const POOL_PREFIX: &str = "pool:";
+const CACHE_PREFIX: &str = "cache/";
+
+/// Why we hold a piece of content. Each one is a named iroh-blobs tag,
+///  and GC removes whatever no tag points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum BlobTag {
+    /// Shared from this node
+    UserShared(Hash),
+    /// Pinned by the user
+    Pinned(Hash),
+    /// Backs a pool we joined
+    Pool(Address),
+    /// Pulled on demand -- evicted least recently used first
+    Cache(Hash),
+}
+
+impl BlobTag {
+    pub fn name(&self) -> Tag {
+        let name = match self {
+            BlobTag::UserShared(hash) => format!("{}{}", USER_SHARED_PREFIX, hash),
+            BlobTag::Pinned(hash) => format!("{}{}", PIN_PREFIX, hash),
+            BlobTag::Pool(address) => format!("{}{}", POOL_PREFIX, address),
+            BlobTag::Cache(hash) => format!("{}{}", CACHE_PREFIX, hash),
+        };
+        Tag::from(name)
+    }
+
+    /// Read one of our tags back. Anything else in the store -- auto tags
+    ///  from older versions, say -- is left alone.
+    pub fn parse(tag: &Tag) -> Option<Self> {
+        let name = std::str::from_utf8(&tag.0).ok()?;
+        if let Some(hash) = name.strip_prefix(USER_SHARED_PREFIX) {
+            return Hash::from_str(hash).ok().map(BlobTag::UserShared);
+        }
+        if let Some(hash) = name.strip_prefix(PIN_PREFIX) {
+            return Hash::from_str(hash).ok().map(BlobTag::Pinned);
+        }
+        if let Some(address) = name.strip_prefix(POOL_PREFIX) {
+            return Address::from_str(address).ok().map(BlobTag::Pool);
+        }
+        if let Some(hash) = name.strip_prefix(CACHE_PREFIX) {
+            return Hash::from_str(hash).ok().map(BlobTag::Cache);
+        }
+        None
+    }
+
+    /// Whether retention leaves this content alone regardless of quota
+    pub fn is_protected(&self) -> bool {
+        !matches!(self, BlobTag::Cache(_))
+    }
+}
+
+impl std::fmt::Display for BlobTag {
+    /// Short form for listings -- the hash is already on the row
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlobTag::UserShared(_) => write!(f, "user-shared"),
+            BlobTag::Pinned(_) => write!(f, "pinned"),
+            BlobTag::Pool(address) => write!(f, "{}{}", POOL_PREFIX, address),
+            BlobTag::Cache(_) => write!(f, "cache"),
+        }
+    }
+}
+
+/// What we keep and what we let go
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    /// Cached pulls are evicted, least recently used first, once they
+    ///  take up more than this many bytes
+    pub cache_quota: u64,
+}
+
+/// What a GC pass got rid of
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GcReport {
+    pub evicted: Vec<Hash>,
+    pub bytes_reclaimed: u64,
+}
+
+/// When we first got a piece of content and when it was last used,
+///  in seconds since the epoch
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AccessRecord {
+    pub created: u64,
+    pub last_access: u64,
+}
+
+/// When content was added and last pulled or served, persisted so LRU
+///  order survives a restart
+#[derive(Debug, Clone)]
+pub struct AccessLog {
+    path: PathBuf,
+    records: Arc<RwLock<HashMap<Hash, AccessRecord>>>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+impl AccessLog {
+    pub async fn load(path: PathBuf) -> Result<Self> {
+        let records = match tokio::fs::read(&path).await {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|e| {
+                tracing::warn!(
+                    "node::iroh::retention: ignoring unreadable access log {}: {}",
+                    path.display(),
+                    e
+                );
+                HashMap::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path,
+            records: Arc::new(RwLock::new(records)),
+        })
+    }
+
+    pub async fn touch(&self, hash: Hash) {
+        let now = now();
+        self.records
+            .write()
+            .await
+            .entry(hash)
+            .and_modify(|record| record.last_access = now)
+            .or_insert(AccessRecord {
+                created: now,
+                last_access: now,
+            });
+    }
+
+    /// Content added before we kept a log has no record
+    pub async fn record(&self, hash: &Hash) -> Option<AccessRecord> {
+        self.records.read().await.get(hash).copied()
+    }
+
+    /// Seconds since the epoch, or 0 for content we have never seen used
+    pub async fn last_access(&self, hash: &Hash) -> u64 {
+        self.record(hash)
+            .await
+            .map(|record| record.last_access)
+            .unwrap_or_default()
+    }
+
+    pub async fn forget(&self, hash: &Hash) {
+        self.records.write().await.remove(hash);
+    }
+
+    pub async fn save(&self) -> Result<()> {
+        let data = serde_json::to_vec(&*self.records.read().await)?;
+        let tmp_path = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, data).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_names_round_trip() {
+        let hash = Hash::new(b"hello");
+        let address = Address::repeat_byte(0xab);
+        for tag in [
+            BlobTag::UserShared(hash),
+            BlobTag::Pinned(hash),
+            BlobTag::Pool(address),
+            BlobTag::Cache(hash),
+        ] {
+            assert_eq!(BlobTag::parse(&tag.name()), Some(tag));
+        }
+        assert_eq!(BlobTag::parse(&Tag::from("auto-1700000000")), None);
+        assert!(!BlobTag::Cache(hash).is_protected());
+        assert!(BlobTag::Pool(address).is_protected());
+    }
+}
@@ -2,21 +2,36 @@ use anyhow::Result;
 use iroh::{protocol::Router, Endpoint};
 use tokio::sync::watch::Receiver as WatchReceiver;
 
+use crate::config::ServeConfig;
+
 use super::blobs_service::BlobsService;
+use super::challenge::{ChallengeProtocol, CHALLENGE_ALPN};
+use super::gossip::{Gossip, GOSSIP_ALPN};
+use super::protocol::{JaxProtocol, NodeMetadata, JAX_ALPN};
+use super::serve::{GuardedBlobs, ServeContext};
 
 const BLOBS_SERVICE_ALPN: &[u8] = iroh_blobs::ALPN;
 
-pub async fn router(
+pub async fn router<M: NodeMetadata + ServeContext + Clone>(
     endpoint: Endpoint,
     blobs_service: BlobsService,
+    metadata: M,
+    gossip: Gossip,
+    serve: ServeConfig,
     mut shutdown_rx: WatchReceiver<()>,
 ) -> Result<()> {
-    let inner_blobs = blobs_service.get_inner_blobs().clone();
+    // our blobs go out behind the serving limits and policy
+    let guarded_blobs = GuardedBlobs::new(blobs_service.clone(), serve, metadata.clone());
+    let challenge_protocol = ChallengeProtocol::new(blobs_service);
+    let jax_protocol = JaxProtocol::new(metadata);
     // Build the  router against the endpoint -> to our blobs service
     //  NOTE (amiller68): if you want to extend our iroh capabilities
     //   with more protocols and handlers, you'd do so here
     let router = Router::builder(endpoint)
-        .accept(BLOBS_SERVICE_ALPN, inner_blobs)
+        .accept(BLOBS_SERVICE_ALPN, guarded_blobs)
+        .accept(CHALLENGE_ALPN, challenge_protocol)
+        .accept(JAX_ALPN, jax_protocol)
+        .accept(GOSSIP_ALPN, gossip)
         .spawn()
         .await?;
 
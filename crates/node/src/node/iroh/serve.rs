@@ -0,0 +1,812 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::io;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use iroh::endpoint::Connecting;
+use iroh::protocol::ProtocolHandler;
+use iroh::NodeId;
+use iroh_blobs::net_protocol::Blobs;
+use iroh_blobs::provider::{CustomEventSender, Event, EventSender};
+use iroh_blobs::store::bao_tree::io::fsm::Outboard;
+use iroh_blobs::store::fs::Store;
+use iroh_blobs::store::{BaoBlobSize, Map, MapEntry};
+use iroh_blobs::{BlobFormat, Hash, HashAndFormat};
+use iroh_io::AsyncSliceReader;
+use tokio::sync::Mutex;
+
+use crate::config::{ServeConfig, ServePolicy};
+
+use super::acl::AclEntry;
+use super::blobs_service::BlobsService;
+use super::retention::BlobTag;
+
+/// Share of the global limits only peers in our pools may use
+const PRIORITY_RESERVE: f64 = 0.25;
+/// How many peers' limits we keep before forgetting the idle ones.
+///  Past that, peers we have no room for share one set of limits.
+const MAX_TRACKED_PEERS: usize = 1024;
+
+/// What the serving side needs to know about a requester
+#[async_trait]
+pub trait ServeContext: Send + Sync + 'static {
+    /// Whether `node_id` is a peer in one of the pools we've joined that
+    ///  `hash` backs
+    async fn is_pool_peer(&self, node_id: &NodeId, hash: &Hash) -> bool;
+}
+
+/// A token bucket. Takes may overdraw it -- a read is only known to be
+///  big once it's done -- and the debt is paid off before the next one.
+#[derive(Debug)]
+struct Bucket {
+    /// Tokens added per second
+    rate: f64,
+    capacity: f64,
+    /// Tokens left, and when we last topped them up
+    state: StdMutex<(f64, Instant)>,
+}
+
+impl Bucket {
+    fn new(rate: f64, capacity: f64) -> Self {
+        Self {
+            rate,
+            capacity,
+            state: StdMutex::new((capacity, Instant::now())),
+        }
+    }
+
+    /// One second's worth of burst
+    fn per_sec(rate: u64) -> Self {
+        Self::new(rate as f64, rate as f64)
+    }
+
+    /// One minute's worth of burst
+    fn per_min(rate: u32) -> Self {
+        Self::new(rate as f64 / 60.0, rate as f64)
+    }
+
+    /// Take `amount` so long as more than `reserve` of the capacity is
+    ///  left, or say how long until it will be
+    fn try_take(&self, amount: f64, reserve: f64) -> Result<(), Duration> {
+        let mut state = self.state.lock().expect("poisoned");
+        let (tokens, last) = &mut *state;
+        let now = Instant::now();
+        *tokens =
+            (*tokens + now.duration_since(*last).as_secs_f64() * self.rate).min(self.capacity);
+        *last = now;
+        let floor = reserve * self.capacity;
+        if *tokens > floor {
+            *tokens -= amount;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (floor - *tokens) / self.rate + 0.001,
+            ))
+        }
+    }
+
+    async fn take(&self, amount: f64, reserve: f64) {
+        while let Err(wait) = self.try_take(amount, reserve) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Whether the bucket has topped back up -- nothing to remember
+    fn is_idle(&self) -> bool {
+        let (tokens, last) = *self.state.lock().expect("poisoned");
+        tokens + last.elapsed().as_secs_f64() * self.rate >= self.capacity
+    }
+}
+
+/// One peer's limits, kept across its connections
+#[derive(Debug)]
+struct PeerLimits {
+    bytes: Option<Bucket>,
+    requests: Option<Bucket>,
+}
+
+impl PeerLimits {
+    fn new(config: &ServeConfig) -> Self {
+        Self {
+            bytes: config.peer_bytes_per_sec.map(Bucket::per_sec),
+            requests: config.peer_requests_per_min.map(Bucket::per_min),
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.bytes
+            .iter()
+            .chain(self.requests.iter())
+            .all(Bucket::is_idle)
+    }
+}
+
+/// Serving-side limits and policy, shared by every blobs connection
+pub struct ServeGuard {
+    config: ServeConfig,
+    global_bytes: Option<Bucket>,
+    global_requests: Option<Bucket>,
+    peers: StdMutex<HashMap<NodeId, Arc<PeerLimits>>>,
+    /// Limits for the peers that don't fit in `peers`
+    overflow: Arc<PeerLimits>,
+    blobs_service: BlobsService,
+    context: Arc<dyn ServeContext>,
+}
+
+impl std::fmt::Debug for ServeGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServeGuard")
+            .field("config", &self.config)
+            .field("global_bytes", &self.global_bytes)
+            .field("global_requests", &self.global_requests)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ServeGuard {
+    pub fn new(
+        config: ServeConfig,
+        blobs_service: BlobsService,
+        context: impl ServeContext,
+    ) -> Self {
+        Self {
+            global_bytes: config.global_bytes_per_sec.map(Bucket::per_sec),
+            global_requests: config.global_requests_per_min.map(Bucket::per_min),
+            overflow: Arc::new(PeerLimits::new(&config)),
+            config,
+            peers: StdMutex::default(),
+            blobs_service,
+            context: Arc::new(context),
+        }
+    }
+
+    /// Limits for a new connection from `peer`
+    fn connection(self: &Arc<Self>, peer: NodeId) -> ConnectionGuard {
+        let limits = {
+            let mut peers = self.peers.lock().expect("poisoned");
+            match peers.get(&peer) {
+                Some(limits) => limits.clone(),
+                None => {
+                    if peers.len() >= MAX_TRACKED_PEERS {
+                        peers.retain(|_, limits| !limits.is_idle());
+                    }
+                    // still busy -- rather than grow, or forget someone's debt
+                    if peers.len() >= MAX_TRACKED_PEERS {
+                        self.overflow.clone()
+                    } else {
+                        peers
+                            .entry(peer)
+                            .or_insert_with(|| Arc::new(PeerLimits::new(&self.config)))
+                            .clone()
+                    }
+                }
+            }
+        };
+        ConnectionGuard {
+            guard: self.clone(),
+            peer,
+            limits,
+            granted: Mutex::default(),
+            requests: StdMutex::default(),
+        }
+    }
+}
+
+/// The limits one connection is held to
+#[derive(Debug)]
+struct ConnectionGuard {
+    guard: Arc<ServeGuard>,
+    peer: NodeId,
+    limits: Arc<PeerLimits>,
+    /// Children of hash seqs we've agreed to serve this peer
+    granted: Mutex<HashSet<Hash>>,
+    /// Requests counted against the limits whose root the provider
+    ///  hasn't looked up yet, and whether they were admitted
+    requests: StdMutex<Vec<(Hash, bool)>>,
+}
+
+impl ConnectionGuard {
+    /// Count a request for `hash` against the limits as it arrives. The
+    ///  provider looks up the root and then every child of a hash seq,
+    ///  so it's only the root lookup that answers for the request.
+    async fn request(&self, hash: Hash) {
+        let admitted = self.admit(self.priority(hash).await);
+        self.requests
+            .lock()
+            .expect("poisoned")
+            .push((hash, admitted));
+    }
+
+    /// Whether the request `hash` is the root of was admitted -- None if
+    ///  it's not a root still to be looked up, but a child
+    fn take_request(&self, hash: &Hash) -> Option<bool> {
+        let mut requests = self.requests.lock().expect("poisoned");
+        let index = requests.iter().position(|(root, _)| root == hash)?;
+        Some(requests.remove(index).1)
+    }
+
+    /// Whether `hash` goes out to this peer as a fellow member of a pool
+    ///  backing it, or backing a hash seq it's part of. Those requests
+    ///  aren't held to per-peer limits, and may use the reserved share
+    ///  of the global ones.
+    async fn priority(&self, hash: Hash) -> bool {
+        let context = &self.guard.context;
+        if context.is_pool_peer(&self.peer, &hash).await {
+            return true;
+        }
+        for parent in self.guard.blobs_service.hash_seqs_containing(&hash) {
+            if context.is_pool_peer(&self.peer, &parent).await {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Count a request against the limits, or turn it away
+    fn admit(&self, priority: bool) -> bool {
+        if !priority {
+            if let Some(bucket) = &self.limits.requests {
+                if bucket.try_take(1.0, 0.0).is_err() {
+                    return false;
+                }
+            }
+        }
+        match &self.guard.global_requests {
+            Some(bucket) => bucket.try_take(1.0, reserve(priority)).is_ok(),
+            None => true,
+        }
+    }
+
+    /// Whether the policy and the content's ACL let us hand out `hash`
+    ///  to this peer. Pool content is public whatever its ACL says;
+    ///  content with an ACL goes only to the nodes on it, unless it's
+    ///  marked public. Content we only hold as part of hash seqs goes
+    ///  to whoever may have one of them.
+    async fn permits(&self, hash: Hash) -> Result<bool> {
+        if self.granted.lock().await.contains(&hash) {
+            return Ok(true);
+        }
+        let blobs_service = &self.guard.blobs_service;
+        let tags = blobs_service.tags_for(&hash);
+        let acl = blobs_service.acls().get(&hash).await;
+        let parents = match (&acl, tags.is_empty()) {
+            (None, true) => blobs_service.hash_seqs_containing(&hash),
+            _ => Vec::new(),
+        };
+        let allowed = if parents.is_empty() {
+            self.allows(&tags, acl)
+        } else {
+            let mut allowed = false;
+            for parent in parents {
+                let acl = blobs_service.acls().get(&parent).await;
+                if self.allows(&blobs_service.tags_for(&parent), acl) {
+                    allowed = true;
+                    break;
+                }
+            }
+            allowed
+        };
+        if !allowed {
+            return Ok(false);
+        }
+        // the children of a hash seq come along with it
+        let hash_seq = tags
+            .iter()
+            .any(|(_, content)| content.format == BlobFormat::HashSeq);
+        if hash_seq {
+            let children = blobs_service.hash_seq_children(hash).await?;
+            self.granted.lock().await.extend(children);
+        }
+        Ok(true)
+    }
+
+    /// Whether content held under `tags`, with `acl`, goes to this peer
+    fn allows(&self, tags: &[(BlobTag, HashAndFormat)], acl: Option<AclEntry>) -> bool {
+        let pooled = tags.iter().any(|(tag, _)| matches!(tag, BlobTag::Pool(_)));
+        match acl {
+            _ if pooled => true,
+            Some(acl) => acl.allows(&self.peer),
+            None if self.guard.config.policy == ServePolicy::All => true,
+            None => tags
+                .iter()
+                .any(|(tag, _)| matches!(tag, BlobTag::UserShared(_))),
+        }
+    }
+
+    /// Hold a read back until the limits allow `bytes` more out
+    async fn throttle(&self, bytes: usize, priority: bool) {
+        let bytes = bytes as f64;
+        if let Some(bucket) = self.limits.bytes.as_ref().filter(|_| !priority) {
+            bucket.take(bytes, 0.0).await;
+        }
+        if let Some(bucket) = &self.guard.global_bytes {
+            bucket.take(bytes, reserve(priority)).await;
+        }
+    }
+}
+
+/// How much of the global limits is off limits to a request
+fn reserve(priority: bool) -> f64 {
+    if priority {
+        0.0
+    } else {
+        PRIORITY_RESERVE
+    }
+}
+
+/// The store as one connection sees it: what the policy keeps back
+///  isn't there, and reads are held to the connection's limits
+#[derive(Debug, Clone)]
+struct GuardedStore {
+    inner: Store,
+    connection: Arc<ConnectionGuard>,
+}
+
+impl Map for GuardedStore {
+    type Entry = GuardedEntry<<Store as Map>::Entry>;
+
+    fn get(&self, hash: &Hash) -> impl Future<Output = io::Result<Option<Self::Entry>>> + Send {
+        let hash = *hash;
+        let this = self.clone();
+        async move {
+            let peer = this.connection.peer;
+            if this.connection.take_request(&hash) == Some(false) {
+                tracing::debug!("node::iroh::serve: {} is over its request limit", peer);
+                return Ok(None);
+            }
+            if !this
+                .connection
+                .permits(hash)
+                .await
+                .map_err(io::Error::other)?
+            {
+                tracing::debug!("node::iroh::serve: not serving {} to {}", hash, peer);
+                return Ok(None);
+            }
+            let priority = this.connection.priority(hash).await;
+            let entry = this.inner.get(&hash).await?;
+            Ok(entry.map(|inner| GuardedEntry {
+                inner,
+                connection: this.connection.clone(),
+                priority,
+            }))
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GuardedEntry<E> {
+    inner: E,
+    connection: Arc<ConnectionGuard>,
+    /// Whether it goes out under the pool peer allowances
+    priority: bool,
+}
+
+impl<E: MapEntry> MapEntry for GuardedEntry<E> {
+    fn hash(&self) -> Hash {
+        self.inner.hash()
+    }
+
+    fn size(&self) -> BaoBlobSize {
+        self.inner.size()
+    }
+
+    fn is_complete(&self) -> bool {
+        self.inner.is_complete()
+    }
+
+    fn outboard(&self) -> impl Future<Output = io::Result<impl Outboard>> + Send {
+        self.inner.outboard()
+    }
+
+    fn data_reader(&self) -> impl Future<Output = io::Result<impl AsyncSliceReader>> + Send {
+        let reader = self.inner.data_reader();
+        let connection = self.connection.clone();
+        let priority = self.priority;
+        async move {
+            Ok(Throttled {
+                inner: reader.await?,
+                connection,
+                priority,
+            })
+        }
+    }
+}
+
+/// Content is sent as fast as it's read, so holding reads back holds
+///  back what goes out
+struct Throttled<R> {
+    inner: R,
+    connection: Arc<ConnectionGuard>,
+    priority: bool,
+}
+
+impl<R: AsyncSliceReader> AsyncSliceReader for Throttled<R> {
+    async fn read_at(&mut self, offset: u64, len: usize) -> io::Result<Bytes> {
+        let data = self.inner.read_at(offset, len).await?;
+        self.connection.throttle(data.len(), self.priority).await;
+        Ok(data)
+    }
+
+    async fn size(&mut self) -> io::Result<u64> {
+        self.inner.size().await
+    }
+}
+
+/// Counts each request against the connection's limits as the provider
+///  announces it, ahead of any lookups, then passes the event on
+#[derive(Debug, Clone)]
+struct RequestEvents {
+    connection: Arc<ConnectionGuard>,
+    forward: EventSender,
+}
+
+impl CustomEventSender for RequestEvents {
+    fn send(&self, event: Event) -> futures::future::BoxFuture<'static, ()> {
+        let this = self.clone();
+        Box::pin(async move {
+            if let Event::GetRequestReceived { hash, .. } = &event {
+                this.connection.request(*hash).await;
+            }
+            this.forward.send(|| event).await
+        })
+    }
+
+    fn try_send(&self, event: Event) {
+        self.forward.try_send(|| event);
+    }
+}
+
+/// The blobs protocol, behind our limits and policy
+#[derive(Clone)]
+pub struct GuardedBlobs {
+    blobs: Arc<Blobs<Store>>,
+    guard: Arc<ServeGuard>,
+}
+
+impl std::fmt::Debug for GuardedBlobs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GuardedBlobs")
+            .field("guard", &self.guard)
+            .finish_non_exhaustive()
+    }
+}
+
+impl GuardedBlobs {
+    pub fn new(
+        blobs_service: BlobsService,
+        config: ServeConfig,
+        context: impl ServeContext,
+    ) -> Self {
+        Self {
+            blobs: blobs_service.get_inner_blobs().clone(),
+            guard: Arc::new(ServeGuard::new(config, blobs_service, context)),
+        }
+    }
+}
+
+impl ProtocolHandler for GuardedBlobs {
+    fn accept(&self, conn: Connecting) -> futures::future::BoxFuture<'static, Result<()>> {
+        let this = self.clone();
+        Box::pin(async move {
+            let connection = conn.await?;
+            let peer = connection.remote_node_id()?;
+            let guard = Arc::new(this.guard.connection(peer));
+            let store = GuardedStore {
+                inner: this.blobs.store().clone(),
+                connection: guard.clone(),
+            };
+            let accounting = this
+                .guard
+                .blobs_service
+                .accounting()
+                .events(peer, this.blobs.events().clone());
+            let events = RequestEvents {
+                connection: guard,
+                forward: accounting.into(),
+            };
+            iroh_blobs::provider::handle_connection(
+                connection,
+                store,
+                events.into(),
+                this.blobs.rt().clone(),
+            )
+            .await;
+            Ok(())
+        })
+    }
+
+    fn shutdown(&self) -> futures::future::BoxFuture<'static, ()> {
+        self.blobs.as_ref().shutdown()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::Address;
+
+    use iroh_blobs::ticket::BlobTicket;
+
+    use crate::node::iroh::test_utils::test_blobs_service;
+    use crate::node::iroh::AclUpdate;
+
+    /// Nobody is in our pools
+    struct NoPools;
+
+    #[async_trait]
+    impl ServeContext for NoPools {
+        async fn is_pool_peer(&self, _: &NodeId, _: &Hash) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_bucket_reserve() {
+        let bucket = Bucket::new(1.0, 100.0);
+        // overdrawing is fine once, then everyone waits out the debt
+        assert!(bucket.try_take(150.0, 0.0).is_ok());
+        assert!(bucket.try_take(1.0, 0.0).is_err());
+
+        let bucket = Bucket::new(1.0, 100.0);
+        assert!(bucket.try_take(80.0, PRIORITY_RESERVE).is_ok());
+        // the last quarter is kept back for priority takers
+        assert!(bucket.try_take(10.0, PRIORITY_RESERVE).is_err());
+        assert!(bucket.try_take(10.0, 0.0).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_tracked_peers_bounded() {
+        let (blobs_service, _dir) = test_blobs_service().await;
+        let config = ServeConfig {
+            peer_requests_per_min: Some(1),
+            ..Default::default()
+        };
+        let guard = Arc::new(ServeGuard::new(config, blobs_service, NoPools));
+        let peer = |i: u32| {
+            let mut seed = [0u8; 32];
+            seed[..4].copy_from_slice(&i.to_be_bytes());
+            iroh::SecretKey::from_bytes(&seed).public()
+        };
+        // every one of them busy, so there's nobody to forget
+        for i in 0..MAX_TRACKED_PEERS as u32 {
+            assert!(guard.connection(peer(i)).admit(false));
+        }
+        // the rest share limits
+        let limits = |i: u32| guard.connection(peer(i)).limits;
+        let max = MAX_TRACKED_PEERS as u32;
+        assert!(Arc::ptr_eq(&limits(max), &guard.overflow));
+        assert!(Arc::ptr_eq(&limits(max + 1), &guard.overflow));
+        assert_eq!(guard.peers.lock().unwrap().len(), MAX_TRACKED_PEERS);
+        // while those we track keep their own
+        assert!(Arc::ptr_eq(&limits(0), &limits(0)));
+        assert!(!Arc::ptr_eq(&limits(0), &guard.overflow));
+    }
+
+    #[tokio::test]
+    async fn test_collection_counts_once() {
+        let (blobs_service, _dir) = test_blobs_service().await;
+        let mut children = Vec::new();
+        for i in 0..4u8 {
+            let hash = blobs_service.store_blob(vec![i; 100]).await.unwrap();
+            children.push((format!("{}.txt", i), hash));
+        }
+        let collection: iroh_blobs::format::collection::Collection = children.into_iter().collect();
+        let temp_tag = collection
+            .store(blobs_service.get_inner_blobs().store())
+            .await
+            .unwrap();
+        let root = *temp_tag.hash();
+        blobs_service
+            .tag(BlobTag::UserShared(root), root, BlobFormat::HashSeq)
+            .await
+            .unwrap();
+
+        // fewer requests than the collection has blobs
+        let config = ServeConfig {
+            peer_requests_per_min: Some(2),
+            ..Default::default()
+        };
+        let guarded = GuardedBlobs::new(blobs_service.clone(), config, NoPools);
+        let host = blobs_service.get_inner_blobs().endpoint().clone();
+        let router = iroh::protocol::Router::builder(host.clone())
+            .accept(iroh_blobs::ALPN, guarded.clone())
+            .spawn()
+            .await
+            .unwrap();
+        let addr = host.node_addr().await.unwrap();
+
+        let (client, _client_dir) = test_blobs_service().await;
+        let ticket = BlobTicket::new(addr.clone(), root, BlobFormat::HashSeq).unwrap();
+        client
+            .download_blob(&ticket, BlobTag::Cache(root))
+            .await
+            .unwrap();
+        assert!(client.has_content(root, BlobFormat::HashSeq).await.unwrap());
+        // and it took one request out of the bucket, not one per blob
+        let client_id = client.get_inner_blobs().endpoint().node_id();
+        let limits = guarded.guard.peers.lock().unwrap()[&client_id].clone();
+        let (tokens, _) = *limits.requests.as_ref().unwrap().state.lock().unwrap();
+        assert!((0.5..1.5).contains(&tokens), "{}", tokens);
+
+        router.shutdown().await.unwrap();
+    }
+
+    /// One peer, in the pool for one hash
+    struct PoolOf(NodeId, Hash);
+
+    #[async_trait]
+    impl ServeContext for PoolOf {
+        async fn is_pool_peer(&self, node_id: &NodeId, hash: &Hash) -> bool {
+            *node_id == self.0 && *hash == self.1
+        }
+    }
+
+    #[tokio::test]
+    async fn test_priority_per_hash() {
+        let (blobs_service, _dir) = test_blobs_service().await;
+        let child = blobs_service.store_blob(b"child".to_vec()).await.unwrap();
+        let other = blobs_service.store_blob(b"other".to_vec()).await.unwrap();
+        let collection: iroh_blobs::format::collection::Collection =
+            [("child.txt", child)].into_iter().collect();
+        let temp_tag = collection
+            .store(blobs_service.get_inner_blobs().store())
+            .await
+            .unwrap();
+        let root = *temp_tag.hash();
+        blobs_service
+            .tag(BlobTag::UserShared(root), root, BlobFormat::HashSeq)
+            .await
+            .unwrap();
+
+        let member = iroh::SecretKey::from_bytes(&[1u8; 32]).public();
+        let stranger = iroh::SecretKey::from_bytes(&[2u8; 32]).public();
+        let guard = Arc::new(ServeGuard::new(
+            ServeConfig::default(),
+            blobs_service,
+            PoolOf(member, root),
+        ));
+        let connection = guard.connection(member);
+        assert!(connection.priority(root).await);
+        // the pool's content comes with it
+        assert!(connection.priority(child).await);
+        // but being in one pool doesn't cover everything we serve
+        assert!(!connection.priority(other).await);
+        assert!(!guard.connection(stranger).priority(root).await);
+    }
+
+    #[tokio::test]
+    async fn test_pooled_or_public_policy() {
+        let (blobs_service, _dir) = test_blobs_service().await;
+        let shared = blobs_service.store_blob(b"shared".to_vec()).await.unwrap();
+        // pulled from someone else, so only cached here
+        let cached = blobs_service.store_blob(b"cached".to_vec()).await.unwrap();
+        blobs_service
+            .tag(BlobTag::Cache(cached), cached, BlobFormat::Raw)
+            .await
+            .unwrap();
+        blobs_service
+            .untag(BlobTag::UserShared(cached))
+            .await
+            .unwrap();
+
+        let config = ServeConfig {
+            policy: ServePolicy::PooledOrPublic,
+            ..Default::default()
+        };
+        let guard = Arc::new(ServeGuard::new(config, blobs_service, NoPools));
+        let connection = guard.connection(iroh::SecretKey::from_bytes(&[1u8; 32]).public());
+        assert!(connection.permits(shared).await.unwrap());
+        assert!(!connection.permits(cached).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_acl() {
+        let (blobs_service, _dir) = test_blobs_service().await;
+        let private = blobs_service.store_blob(b"private".to_vec()).await.unwrap();
+        let pooled = blobs_service.store_blob(b"pooled".to_vec()).await.unwrap();
+        blobs_service
+            .tag(BlobTag::Pool(Address::ZERO), pooled, BlobFormat::Raw)
+            .await
+            .unwrap();
+        let friend = iroh::SecretKey::from_bytes(&[1u8; 32]).public();
+        let stranger = iroh::SecretKey::from_bytes(&[2u8; 32]).public();
+        for hash in [private, pooled] {
+            blobs_service
+                .acls()
+                .update(
+                    hash,
+                    AclUpdate {
+                        allow: vec![friend],
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+        }
+
+        let guard = Arc::new(ServeGuard::new(
+            ServeConfig::default(),
+            blobs_service.clone(),
+            NoPools,
+        ));
+        assert!(guard.connection(friend).permits(private).await.unwrap());
+        let connection = guard.connection(stranger);
+        assert!(!connection.permits(private).await.unwrap());
+        // pool content stays public
+        assert!(connection.permits(pooled).await.unwrap());
+
+        blobs_service
+            .acls()
+            .update(
+                private,
+                AclUpdate {
+                    public: Some(true),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(connection.permits(private).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_acl_covers_collection() {
+        let (blobs_service, _dir) = test_blobs_service().await;
+        let child = blobs_service.store_blob(b"child".to_vec()).await.unwrap();
+        // held only as part of the collection
+        blobs_service
+            .untag(BlobTag::UserShared(child))
+            .await
+            .unwrap();
+        let collection: iroh_blobs::format::collection::Collection =
+            [("child.txt", child)].into_iter().collect();
+        let temp_tag = collection
+            .store(blobs_service.get_inner_blobs().store())
+            .await
+            .unwrap();
+        let root = *temp_tag.hash();
+        blobs_service
+            .tag(BlobTag::UserShared(root), root, BlobFormat::HashSeq)
+            .await
+            .unwrap();
+        drop(temp_tag);
+        let friend = iroh::SecretKey::from_bytes(&[1u8; 32]).public();
+        let stranger = iroh::SecretKey::from_bytes(&[2u8; 32]).public();
+        blobs_service
+            .acls()
+            .update(
+                root,
+                AclUpdate {
+                    allow: vec![friend],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // the policy serves anything without an ACL, but the child
+        //  comes under the collection's
+        let guard = Arc::new(ServeGuard::new(
+            ServeConfig::default(),
+            blobs_service.clone(),
+            NoPools,
+        ));
+        assert_eq!(guard.config.policy, ServePolicy::All);
+        let connection = guard.connection(stranger);
+        assert!(!connection.permits(child).await.unwrap());
+        assert!(!connection.permits(root).await.unwrap());
+        assert!(guard.connection(friend).permits(child).await.unwrap());
+
+        // shared in its own right, it's served like anything else
+        blobs_service
+            .tag(BlobTag::UserShared(child), child, BlobFormat::Raw)
+            .await
+            .unwrap();
+        assert!(connection.permits(child).await.unwrap());
+    }
+}
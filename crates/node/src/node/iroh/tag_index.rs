@@ -0,0 +1,145 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use iroh_blobs::{Hash, HashAndFormat};
+
+use super::retention::BlobTag;
+
+/// Our tags by the content they hold, and the hash seqs they hold that
+///  each blob is part of. Kept in step with the store, so looking one
+///  piece of content up doesn't mean listing every tag.
+#[derive(Debug, Clone, Default)]
+pub struct TagIndex {
+    inner: Arc<RwLock<Index>>,
+}
+
+#[derive(Debug, Default)]
+struct Index {
+    tags: HashMap<BlobTag, HashAndFormat>,
+    by_hash: HashMap<Hash, HashSet<BlobTag>>,
+    /// Children of the tagged hash seqs we've read
+    children: HashMap<Hash, Vec<Hash>>,
+    /// The tagged hash seqs each child is part of
+    parents: HashMap<Hash, HashSet<Hash>>,
+}
+
+impl Index {
+    fn forget_children(&mut self, root: &Hash) {
+        for child in self.children.remove(root).into_iter().flatten() {
+            if let Some(parents) = self.parents.get_mut(&child) {
+                parents.remove(root);
+                if parents.is_empty() {
+                    self.parents.remove(&child);
+                }
+            }
+        }
+    }
+}
+
+impl TagIndex {
+    /// Point `tag` at `content`, or drop it with `None`
+    pub fn set(&self, tag: BlobTag, content: Option<HashAndFormat>) {
+        let mut index = self.inner.write().expect("poisoned");
+        if let Some(old) = index.tags.remove(&tag) {
+            if let Some(tags) = index.by_hash.get_mut(&old.hash) {
+                tags.remove(&tag);
+                if tags.is_empty() {
+                    index.by_hash.remove(&old.hash);
+                    index.forget_children(&old.hash);
+                }
+            }
+        }
+        if let Some(content) = content {
+            index.tags.insert(tag, content);
+            index.by_hash.entry(content.hash).or_default().insert(tag);
+        }
+    }
+
+    /// Whether we know what the hash seq `root` is made of
+    pub fn has_children(&self, root: &Hash) -> bool {
+        let index = self.inner.read().expect("poisoned");
+        index.children.contains_key(root)
+    }
+
+    /// Note what a tagged hash seq is made of
+    pub fn set_children(&self, root: Hash, children: Vec<Hash>) {
+        let mut index = self.inner.write().expect("poisoned");
+        // its tags went while we were reading it
+        if !index.by_hash.contains_key(&root) {
+            return;
+        }
+        index.forget_children(&root);
+        for child in &children {
+            index.parents.entry(*child).or_default().insert(root);
+        }
+        index.children.insert(root, children);
+    }
+
+    /// The tagged hash seqs `hash` is part of
+    pub fn parents(&self, hash: &Hash) -> Vec<Hash> {
+        let index = self.inner.read().expect("poisoned");
+        index
+            .parents
+            .get(hash)
+            .into_iter()
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// The tags holding `hash`
+    pub fn get(&self, hash: &Hash) -> Vec<(BlobTag, HashAndFormat)> {
+        let index = self.inner.read().expect("poisoned");
+        index
+            .by_hash
+            .get(hash)
+            .into_iter()
+            .flatten()
+            .filter_map(|tag| index.tags.get(tag).map(|content| (*tag, *content)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iroh_blobs::BlobFormat;
+
+    #[test]
+    fn test_retag() {
+        let index = TagIndex::default();
+        let (a, b) = (Hash::new(b"a"), Hash::new(b"b"));
+        index.set(BlobTag::Pinned(a), Some(HashAndFormat::raw(a)));
+        index.set(BlobTag::Cache(a), Some(HashAndFormat::raw(a)));
+        assert_eq!(index.get(&a).len(), 2);
+
+        // a tag points at one thing at a time
+        index.set(BlobTag::Cache(a), Some(HashAndFormat::hash_seq(b)));
+        assert_eq!(
+            index.get(&a),
+            vec![(BlobTag::Pinned(a), HashAndFormat::raw(a))]
+        );
+        assert_eq!(index.get(&b)[0].1.format, BlobFormat::HashSeq);
+
+        index.set(BlobTag::Pinned(a), None);
+        assert!(index.get(&a).is_empty());
+    }
+
+    #[test]
+    fn test_parents() {
+        let index = TagIndex::default();
+        let (root, child) = (Hash::new(b"root"), Hash::new(b"child"));
+        // nothing to hang children off until the root is tagged
+        index.set_children(root, vec![child]);
+        assert!(index.parents(&child).is_empty());
+
+        index.set(BlobTag::Pinned(root), Some(HashAndFormat::hash_seq(root)));
+        index.set_children(root, vec![child]);
+        assert!(index.has_children(&root));
+        assert_eq!(index.parents(&child), vec![root]);
+
+        index.set(BlobTag::Pinned(root), None);
+        assert!(!index.has_children(&root));
+        assert!(index.parents(&child).is_empty());
+    }
+}
@@ -0,0 +1,47 @@
+use std::path::{Path, PathBuf};
+
+use iroh::{Endpoint, RelayMode};
+
+use super::{BlobsService, RetentionPolicy};
+
+/// A scratch directory, removed again when dropped
+#[derive(Debug)]
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(prefix: &str) -> Self {
+        Self(std::env::temp_dir().join(format!("{}-{}", prefix, uuid::Uuid::new_v4())))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// An endpoint that only talks over loopback
+pub async fn test_endpoint() -> Endpoint {
+    Endpoint::builder()
+        .relay_mode(RelayMode::Disabled)
+        .bind_addr_v4("127.0.0.1:0".parse().unwrap())
+        .bind()
+        .await
+        .unwrap()
+}
+
+/// A store that keeps nothing it isn't told to, in a scratch directory,
+///  on a loopback endpoint
+pub async fn test_blobs_service() -> (BlobsService, TempDir) {
+    let dir = TempDir::new("jax-blobs");
+    let endpoint = test_endpoint().await;
+    let blobs_service =
+        BlobsService::load(dir.path(), endpoint, RetentionPolicy { cache_quota: 0 })
+            .await
+            .unwrap();
+    (blobs_service, dir)
+}
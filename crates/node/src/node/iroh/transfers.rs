@@ -0,0 +1,243 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use iroh::NodeId;
+use iroh_blobs::{BlobFormat, Hash};
+use serde::Serialize;
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// How many finished transfers we remember for anyone polling late
+const FINISHED_HISTORY: usize = 32;
+/// BLAKE3 hashes content in 1 KiB chunks
+const CHUNK_SIZE: u64 = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", content = "reason", rename_all = "snake_case")]
+pub enum TransferState {
+    Running,
+    Complete,
+    Failed(String),
+    Cancelled,
+}
+
+/// A download as seen from outside
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferInfo {
+    pub id: Uuid,
+    pub hash: Hash,
+    pub format: BlobFormat,
+    pub sources: Vec<NodeId>,
+    #[serde(flatten)]
+    pub state: TransferState,
+    /// Seconds since the epoch
+    pub started: u64,
+    pub bytes_done: u64,
+    /// Grows as blobs in a collection are discovered
+    pub bytes_total: u64,
+    pub chunks_done: u64,
+    pub chunks_total: u64,
+    /// Average over the transfer so far, in bytes per second
+    pub rate: f64,
+    /// Seconds left at the current rate, if we can tell
+    pub eta: Option<u64>,
+}
+
+#[derive(Debug)]
+struct Transfer {
+    info: TransferInfo,
+    started_at: Instant,
+    /// blob sizes and offsets by progress id
+    sizes: HashMap<u64, u64>,
+    offsets: HashMap<u64, u64>,
+    local: u64,
+    cancel: CancellationToken,
+}
+
+impl Transfer {
+    fn refresh(&mut self) {
+        let info = &mut self.info;
+        info.bytes_total = self.local + self.sizes.values().sum::<u64>();
+        info.bytes_done = self.local + self.offsets.values().sum::<u64>();
+        info.chunks_total = info.bytes_total.div_ceil(CHUNK_SIZE);
+        info.chunks_done = info.bytes_done.div_ceil(CHUNK_SIZE);
+        // what we had locally didn't cost anything to get
+        let fetched = info.bytes_done - self.local;
+        let elapsed = self.started_at.elapsed().as_secs_f64();
+        info.rate = if elapsed > 0.0 {
+            fetched as f64 / elapsed
+        } else {
+            0.0
+        };
+        info.eta = (info.rate > 0.0)
+            .then(|| ((info.bytes_total - info.bytes_done) as f64 / info.rate).ceil() as u64);
+    }
+}
+
+/// Live and recently finished downloads, each cancellable by id
+#[derive(Debug, Clone, Default)]
+pub struct Transfers {
+    active: Arc<RwLock<HashMap<Uuid, Transfer>>>,
+    finished: Arc<RwLock<VecDeque<TransferInfo>>>,
+}
+
+impl Transfers {
+    /// Start tracking a download. Cancelling the token is how the
+    ///  download learns it should stop.
+    pub async fn start(
+        &self,
+        hash: Hash,
+        format: BlobFormat,
+        sources: Vec<NodeId>,
+    ) -> (Uuid, CancellationToken) {
+        let id = Uuid::new_v4();
+        let cancel = CancellationToken::new();
+        let transfer = Transfer {
+            info: TransferInfo {
+                id,
+                hash,
+                format,
+                sources,
+                state: TransferState::Running,
+                started: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+                bytes_done: 0,
+                bytes_total: 0,
+                chunks_done: 0,
+                chunks_total: 0,
+                rate: 0.0,
+                eta: None,
+            },
+            started_at: Instant::now(),
+            sizes: HashMap::new(),
+            offsets: HashMap::new(),
+            local: 0,
+            cancel: cancel.clone(),
+        };
+        self.active.write().await.insert(id, transfer);
+        (id, cancel)
+    }
+
+    pub async fn found_local(&self, id: Uuid, size: u64) {
+        self.update(id, |transfer| transfer.local += size).await;
+    }
+
+    pub async fn found(&self, id: Uuid, blob: u64, size: u64) {
+        self.update(id, |transfer| {
+            transfer.sizes.insert(blob, size);
+        })
+        .await;
+    }
+
+    pub async fn progress(&self, id: Uuid, blob: u64, offset: u64) {
+        self.update(id, |transfer| {
+            transfer.offsets.insert(blob, offset);
+        })
+        .await;
+    }
+
+    pub async fn done(&self, id: Uuid, blob: u64) {
+        self.update(id, |transfer| {
+            let size = transfer.sizes.get(&blob).copied().unwrap_or_default();
+            transfer.offsets.insert(blob, size);
+        })
+        .await;
+    }
+
+    async fn update(&self, id: Uuid, f: impl FnOnce(&mut Transfer)) {
+        if let Some(transfer) = self.active.write().await.get_mut(&id) {
+            f(transfer);
+            transfer.refresh();
+        }
+    }
+
+    /// Move a transfer to the finished list
+    pub async fn finish(&self, id: Uuid, state: TransferState) {
+        let Some(mut transfer) = self.active.write().await.remove(&id) else {
+            return;
+        };
+        transfer.refresh();
+        transfer.info.state = state;
+        transfer.info.eta = None;
+        let mut finished = self.finished.write().await;
+        if finished.len() == FINISHED_HISTORY {
+            finished.pop_front();
+        }
+        finished.push_back(transfer.info);
+    }
+
+    /// Running transfers first, then the most recently finished
+    pub async fn list(&self) -> Vec<TransferInfo> {
+        let mut transfers = self
+            .active
+            .read()
+            .await
+            .values()
+            .map(|transfer| transfer.info.clone())
+            .collect::<Vec<_>>();
+        transfers.sort_by_key(|info| info.started);
+        transfers.extend(self.finished.read().await.iter().rev().cloned());
+        transfers
+    }
+
+    pub async fn get(&self, id: Uuid) -> Option<TransferInfo> {
+        if let Some(transfer) = self.active.read().await.get(&id) {
+            return Some(transfer.info.clone());
+        }
+        self.finished
+            .read()
+            .await
+            .iter()
+            .find(|info| info.id == id)
+            .cloned()
+    }
+
+    /// Ask a running transfer to stop. False if there's no such
+    ///  transfer running.
+    pub async fn cancel(&self, id: Uuid) -> bool {
+        match self.active.read().await.get(&id) {
+            Some(transfer) => {
+                transfer.cancel.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_transfer_progress() {
+        let transfers = Transfers::default();
+        let node_id = iroh::SecretKey::from_bytes(&[7u8; 32]).public();
+        let (id, cancel) = transfers
+            .start(Hash::new(b"hello"), BlobFormat::HashSeq, vec![node_id])
+            .await;
+
+        transfers.found_local(id, 1024).await;
+        transfers.found(id, 1, 4096).await;
+        transfers.progress(id, 1, 2048).await;
+        let info = transfers.get(id).await.unwrap();
+        assert_eq!(info.bytes_total, 5120);
+        assert_eq!(info.bytes_done, 3072);
+        assert_eq!(info.chunks_done, 3);
+        assert_eq!(info.state, TransferState::Running);
+
+        assert!(transfers.cancel(id).await);
+        assert!(cancel.is_cancelled());
+        transfers.finish(id, TransferState::Cancelled).await;
+        assert!(!transfers.cancel(id).await);
+        let info = transfers.get(id).await.unwrap();
+        assert_eq!(info.state, TransferState::Cancelled);
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["state"], "cancelled");
+        assert_eq!(transfers.list().await.len(), 1);
+    }
+}
@@ -15,11 +15,16 @@ mod http;
 mod iroh;
 mod state;
 mod tracker;
+mod uploads;
 mod utils;
 
 use http::http_server;
-pub use iroh::create_ephemeral_endpoint;
 use iroh::router as iroh_router;
+pub use iroh::{
+    ciphertext_size, create_ephemeral_endpoint, encrypt_stream, plaintext_chunk, AddrSource,
+    ContentKey, Decryptor, EncryptedTicket, ExportLink, ImportMode, ImportProgress, NatType,
+    PathKind, SEALED_CHUNK_SIZE,
+};
 pub use state::State;
 
 pub struct Node;
@@ -76,9 +81,21 @@ impl Node {
         // Start Iroh router
         let iroh_endpoint = state.endpoint().clone();
         let blobs_service = state.blobs_service().clone();
+        let tracker = state.tracker().clone();
+        let gossip = state.gossip().clone();
+        let serve = config.serve().clone();
         let iroh_rx = shutdown_rx.clone();
         let iroh_handle = tokio::spawn(async move {
-            if let Err(e) = iroh_router(iroh_endpoint, blobs_service, iroh_rx).await {
+            if let Err(e) = iroh_router(
+                iroh_endpoint,
+                blobs_service,
+                tracker,
+                gossip,
+                serve,
+                iroh_rx,
+            )
+            .await
+            {
                 tracing::error!("Iroh router error: {}", e);
             }
         });
@@ -86,6 +103,14 @@ impl Node {
 
         let _ = graceful_waiter.await;
 
+        // Snapshot tracker state so we don't re-bootstrap trust from zero
+        if let Err(e) = state.tracker().persist().await {
+            tracing::error!("Failed to persist tracker state: {}", e);
+        }
+        if let Err(e) = state.blobs_service().accounting().save().await {
+            tracing::error!("Failed to save accounting: {}", e);
+        }
+
         if timeout(FINAL_SHUTDOWN_TIMEOUT, join_all(handles))
             .await
             .is_err()
@@ -1,3 +1,4 @@
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use alloy::primitives::Address;
@@ -8,10 +9,14 @@ use iroh::Endpoint;
 use iroh::NodeId;
 use iroh::SecretKey;
 
-use crate::config::{Config, ConfigError};
+use crate::config::{Config, ConfigError, RelayConfig};
 
-use super::iroh::{await_relay_region, create_endpoint, BlobsService};
-use super::tracker::Tracker;
+use super::iroh::{
+    await_relay_region, create_endpoint, BlobsService, Diagnostics, EndpointOptions, Gossip,
+    Prober, RetentionPolicy,
+};
+use super::tracker::{Tracker, TrackerStore};
+use super::uploads::{UploadLimits, Uploads};
 
 use tokio::sync::watch;
 
@@ -21,7 +26,11 @@ pub struct State {
     eth_signer: PrivateKeySigner,
     endpoint: Arc<Endpoint>,
     blobs_service: BlobsService,
+    gossip: Gossip,
+    diagnostics: Diagnostics,
+    uploads: Uploads,
     tracker: Tracker,
+    export_path: Option<PathBuf>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -41,17 +50,41 @@ impl State {
         let endpoint_socket_addr = config.endpoint_listen_addr();
         let iroh_secret_key = config.iroh_secret_key()?;
         let iroh_node_id = iroh_secret_key.public();
-        let _endpoint = create_endpoint(*endpoint_socket_addr, iroh_secret_key.clone()).await;
+        let endpoint_options =
+            EndpointOptions::new(config.discovery().to_vec(), config.relay().clone())
+                .with_local_network(iroh_node_id)?;
+        let _endpoint = create_endpoint(
+            *endpoint_socket_addr,
+            config.endpoint_listen_addr_v6().copied(),
+            iroh_secret_key.clone(),
+            &endpoint_options,
+        )
+        .await?;
         let endpoint = Arc::new(_endpoint);
-        // await making sure the endpoint is setup
-        let _ = await_relay_region(endpoint.as_ref().clone()).await;
+        // give the endpoint a chance to reach a relay, but carry on without
+        //  one -- peers on the local network can still dial us direct
+        if *config.relay() != RelayConfig::Disabled {
+            if let Err(e) = await_relay_region(endpoint.as_ref().clone()).await {
+                tracing::warn!("node::state: starting without a relay: {}", e);
+            }
+        }
 
         // set up a blob service
         let blobs_path = config.blobs_path();
-        let blobs_service = BlobsService::load(blobs_path, endpoint.as_ref().clone())
+        let policy = RetentionPolicy {
+            cache_quota: config.cache_quota(),
+        };
+        let blobs_service = BlobsService::load(blobs_path, endpoint.as_ref().clone(), policy)
+            .await
+            .map_err(StateSetupError::Default)?;
+        // resumable uploads keep their partial data next to the store's own temp files
+        let uploads = Uploads::load(config.blobs_temp_path().clone(), UploadLimits::default())
             .await
             .map_err(StateSetupError::Default)?;
 
+        // announcements to our pools go out under our own identity
+        let gossip = Gossip::new(endpoint.as_ref().clone(), iroh_secret_key.clone()).await?;
+
         let beneficiary_address = config.eth_signer().expect("valid eth signer").address();
         println!("beneficiary_address: {:?}", beneficiary_address);
         let iroh_signature = iroh_secret_key.sign(beneficiary_address.into_array().as_ref());
@@ -64,17 +97,26 @@ impl State {
             config.eth_signer().expect("valid eth signer"),
             blobs_service.clone(),
             iroh_signature,
+            TrackerStore::new(config.tracker_state_path().clone()),
+            Prober::new(endpoint_options),
+            gossip.clone(),
         )
         .await
         .expect("valid tracker");
 
+        let diagnostics = Diagnostics::new(endpoint.as_ref().clone());
+
         // Create state with all components
         let state = Self {
             iroh_secret_key,
             eth_signer: config.eth_signer().expect("valid eth signer"),
             endpoint,
             blobs_service,
+            gossip,
+            diagnostics,
+            uploads,
             tracker,
+            export_path: config.export_path().cloned(),
         };
 
         Ok(state)
@@ -104,6 +146,23 @@ impl State {
         &self.blobs_service
     }
 
+    pub fn gossip(&self) -> &Gossip {
+        &self.gossip
+    }
+
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    pub fn uploads(&self) -> &Uploads {
+        &self.uploads
+    }
+
+    /// Where exports may be written, if anywhere
+    pub fn export_path(&self) -> Option<&Path> {
+        self.export_path.as_deref()
+    }
+
     pub fn tracker(&self) -> &Tracker {
         &self.tracker
     }
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use alloy::primitives::{Address, U256};
+use iroh::NodeId;
+use iroh_blobs::get::Stats;
+use iroh_blobs::Hash;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use super::{ChallengeResult, PoolKey, ProbeResult};
+
+/// How many events a slow subscriber can fall behind before it starts
+///  missing them
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Outcome of a probe, flattened for subscribers
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeStatus {
+    Success,
+    Timeout,
+    Error,
+}
+
+/// Things the tracker has observed or done, published to any subscriber
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TrackerEvent {
+    PoolDiscovered {
+        address: Address,
+        hash: Hash,
+        balance: U256,
+    },
+    PoolJoined {
+        address: Address,
+        hash: Hash,
+    },
+    PoolLeft {
+        address: Address,
+        hash: Hash,
+    },
+    PoolJoinFailed {
+        address: Address,
+        hash: Hash,
+        reason: String,
+    },
+    PeerAdded {
+        address: Address,
+        hash: Hash,
+        node_id: NodeId,
+    },
+    ProbeResult {
+        address: Address,
+        hash: Hash,
+        node_id: NodeId,
+        outcome: ProbeStatus,
+        stats: Option<Stats>,
+    },
+    ChallengeResult {
+        address: Address,
+        hash: Hash,
+        node_id: NodeId,
+        result: ChallengeResult,
+    },
+    TrustRecomputed {
+        address: Address,
+        hash: Hash,
+        trust: HashMap<NodeId, f64>,
+    },
+    DepositSeen {
+        address: Address,
+        hash: Hash,
+        balance: U256,
+    },
+    RewardsClaimed {
+        address: Address,
+        hash: Hash,
+    },
+}
+
+impl TrackerEvent {
+    pub fn probe_result(key: &PoolKey, node_id: NodeId, result: &ProbeResult) -> Self {
+        let (outcome, stats) = match result {
+            ProbeResult::Success(stats) => (ProbeStatus::Success, Some(stats.clone())),
+            ProbeResult::Timeout(_) => (ProbeStatus::Timeout, None),
+            ProbeResult::Error => (ProbeStatus::Error, None),
+        };
+        Self::ProbeResult {
+            address: key.address,
+            hash: key.hash,
+            node_id,
+            outcome,
+            stats,
+        }
+    }
+}
+
+/// Broadcast channel for tracker events -- publishing never blocks
+///  and is a no-op when nobody is listening
+#[derive(Debug, Clone)]
+pub struct TrackerEvents {
+    tx: broadcast::Sender<TrackerEvent>,
+}
+
+impl Default for TrackerEvents {
+    fn default() -> Self {
+        let (tx, _rx) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self { tx }
+    }
+}
+
+impl TrackerEvents {
+    pub fn publish(&self, event: TrackerEvent) {
+        tracing::debug!("node::tracker::events: {:?}", event);
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<TrackerEvent> {
+        self.tx.subscribe()
+    }
+}
@@ -0,0 +1,1745 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use alloy::primitives::{Address, U256};
+use alloy::signers::local::PrivateKeySigner;
+use anyhow::Result;
+use async_trait::async_trait;
+use ed25519::Signature;
+use iroh::NodeId;
+use iroh_blobs::get::Stats;
+use iroh_blobs::ticket::BlobTicket;
+use iroh_blobs::{BlobFormat, Hash, HashAndFormat};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio::sync::watch;
+use tokio::sync::Mutex;
+use tokio::sync::RwLock;
+use url::Url;
+
+use crate::node::eth::contracts::{get_peers, FactoryContract, PoolContract};
+use crate::node::iroh::{
+    Announcement, BlobTag, BlobsService, GcReport, Gossip, GossipEvent, ProbeOutcome, Prober,
+    TopicId,
+};
+
+use super::eth::get_address_balance;
+use super::iroh::protocol::{
+    self, NodeIdentity, NodeMetadata, PoolAnnouncement, Request, Response, JAX_ALPN,
+};
+use super::iroh::{challenge, detect_format, probe_complete, ChallengeResult, ServeContext};
+
+use jax_eigen_trust::{EigenTrust, TrustFetcher};
+
+mod events;
+mod store;
+
+pub use events::{TrackerEvent, TrackerEvents};
+pub use store::TrackerStore;
+use store::{Interaction, InteractionHistorySnapshot, PoolSnapshot, TrackerSnapshot};
+
+/// How often the tracker writes a snapshot of its state to disk
+const SNAPSHOT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+/// How long we wait on a peer to answer a jax protocol request
+const PEER_QUERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// How often we tell each pool's topic which of its content we hold
+const ANNOUNCE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+/// How often cached content is checked against the cache quota
+const RETENTION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+/// How long a peer that gossips it holds a pool's content gets to prove it
+const GOSSIP_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+/// How many peers a pool's gossip can bring in beside those on chain --
+///  anyone can talk on a topic, so the graph mustn't grow with them
+const MAX_GOSSIP_PEERS: usize = 256;
+
+type InteractionLog = HashMap<(NodeId, NodeId), Vec<Interaction>>;
+
+// NOTE (amiller68): prolly makes no sense to hash on both the
+//  address and hash, but im not sure what else to do here
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PoolKey {
+    pub hash: Hash,
+    pub address: Address,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PoolInfo {
+    pub key: PoolKey,
+    pub balance: U256,
+}
+
+impl PoolInfo {
+    pub fn key(&self) -> &PoolKey {
+        &self.key
+    }
+}
+
+/// A pool some content backs, as seen from this node
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolMembership {
+    pub address: Address,
+    /// Our position by global trust, 1 being the most trusted --
+    ///  none if we aren't a peer
+    pub rank: Option<usize>,
+    pub peers: usize,
+}
+
+/// Simple in-memory store for network state. Where more than one
+///  lock is held at once, take them in the order `pools`, `pool_trust`,
+///  `left_pools` -- the locks are write-preferring, so a queued writer
+///  deadlocks two readers taking them in opposite orders.
+#[derive(Clone)]
+pub struct Tracker {
+    pub iroh_signature: Signature,
+    // Track all known pools
+    pools: Arc<RwLock<HashMap<PoolKey, U256>>>,
+    // Format of pooled content -- the chain only knows the hash
+    formats: Arc<RwLock<HashMap<Hash, BlobFormat>>>,
+    // Pools we've left by deleting their content -- never rejoined
+    left_pools: Arc<RwLock<HashSet<Address>>>,
+    // Per-pool trust tracking
+    pool_trust: Arc<RwLock<HashMap<PoolKey, EigenTrust<NetworkTrustFetcher>>>>,
+    // Global trust as of the last update, so we only announce changes
+    global_trust: Arc<RwLock<HashMap<PoolKey, HashMap<NodeId, f64>>>>,
+    // Shutdown signal
+    shutdown_rx: watch::Receiver<()>,
+    // Ethereum connection details
+    eth_ws_url: Arc<Url>,
+    eth_private_key: Arc<PrivateKeySigner>,
+    // Track active pool listeners
+    // Factory contract
+    factory_contract: Arc<RwLock<FactoryContract>>,
+    // Channels for contract events
+    blobs_service: Arc<BlobsService>,
+    pub current_node_id: NodeId,
+    update_lock: Arc<Mutex<()>>,
+    // On-disk snapshots of pools, peers and trust
+    store: Arc<TrackerStore>,
+    // Live feed of what the tracker is up to
+    events: TrackerEvents,
+    // Endpoints we probe and challenge peers from
+    prober: Prober,
+    // Announcements to and from each pool's peers
+    gossip: Gossip,
+}
+
+// Simplified NetworkTrustFetcher for per-pool trust
+pub struct NetworkTrustFetcher {
+    pool_key: PoolKey,
+    peers: Arc<RwLock<HashSet<NodeId>>>,
+    // Peers we've only heard of through the pool's gossip topic, and
+    //  probed to check they hold its content
+    gossip_peers: Arc<RwLock<HashSet<NodeId>>>,
+    // Gossiping peers we're still probing
+    confirming: Arc<RwLock<HashSet<NodeId>>>,
+    // Track successful/failed interactions with timestamps
+    interactions: Arc<RwLock<InteractionLog>>,
+    eth_ws_url: Arc<Url>,
+}
+
+impl NetworkTrustFetcher {
+    pub fn new(pool_key: PoolKey, eth_ws_url: Arc<Url>) -> Self {
+        Self {
+            pool_key,
+            peers: Arc::new(RwLock::new(HashSet::new())),
+            gossip_peers: Arc::new(RwLock::new(HashSet::new())),
+            confirming: Arc::new(RwLock::new(HashSet::new())),
+            interactions: Arc::new(RwLock::new(HashMap::new())),
+            eth_ws_url,
+        }
+    }
+
+    pub async fn record_interaction(&self, from: NodeId, to: NodeId, success: bool) {
+        let mut interactions = self.interactions.write().await;
+        let records = interactions.entry((from, to)).or_insert_with(Vec::new);
+        records.push(Interaction {
+            successes: if success { 1 } else { 0 },
+            failures: if success { 0 } else { 1 },
+            timestamp: std::time::SystemTime::now(),
+        });
+    }
+
+    async fn fetch_trust(&self, i: &NodeId, j: &NodeId) -> Result<f64> {
+        let interactions = self.interactions.read().await;
+
+        if let Some(records) = interactions.get(&(*i, *j)) {
+            let now = std::time::SystemTime::now();
+            let mut weighted_successes = 0.0;
+            let mut weighted_failures = 0.0;
+
+            for record in records {
+                // More aggressive time-based decay (half-life of 10 minutes)
+                let elapsed = now.duration_since(record.timestamp).unwrap_or_default();
+                let decay = 0.5f64.powf(elapsed.as_secs_f64() / 600.0); // Changed from 3600 to 600
+
+                weighted_successes += record.successes as f64 * decay;
+                weighted_failures += record.failures as f64 * decay * 2.0; // Double weight for failures
+            }
+
+            let total = weighted_successes + weighted_failures;
+            if total > 0.0 {
+                Ok(weighted_successes / total)
+            } else {
+                Ok(0.0)
+            }
+        } else {
+            Ok(0.0)
+        }
+    }
+
+    pub async fn add_peer(&self, peer: NodeId) {
+        self.peers.write().await.insert(peer);
+    }
+
+    /// Start checking `peer`'s gossip that it holds the pool's content,
+    ///  unless we already are, already believe it, or have all the
+    ///  gossip peers we'll take
+    pub async fn start_confirming(&self, peer: NodeId) -> bool {
+        if self.is_peer(&peer).await {
+            return false;
+        }
+        let gossip_peers = self.gossip_peers.read().await;
+        let mut confirming = self.confirming.write().await;
+        if gossip_peers.contains(&peer) || gossip_peers.len() + confirming.len() >= MAX_GOSSIP_PEERS
+        {
+            return false;
+        }
+        confirming.insert(peer)
+    }
+
+    /// Count `peer` among the pool's gossip peers if it proved its claim
+    pub async fn finish_confirming(&self, peer: NodeId, confirmed: bool) {
+        self.confirming.write().await.remove(&peer);
+        if confirmed {
+            self.gossip_peers.write().await.insert(peer);
+        }
+    }
+
+    /// Whether `peer` is one of the pool's peers on chain. Anyone can
+    ///  gossip, so that alone doesn't earn a say in the pool's trust.
+    pub async fn is_peer(&self, peer: &NodeId) -> bool {
+        self.peers.read().await.contains(peer)
+    }
+
+    async fn get_peers(&self) -> Result<HashSet<NodeId>> {
+        get_peers(self.pool_key.address, &self.eth_ws_url).await
+    }
+
+    async fn snapshot_interactions(&self) -> Vec<InteractionHistorySnapshot> {
+        self.interactions
+            .read()
+            .await
+            .iter()
+            .map(|((from, to), records)| InteractionHistorySnapshot::new(*from, *to, records))
+            .collect()
+    }
+
+    async fn restore_interactions(&self, history: &[InteractionHistorySnapshot]) {
+        let mut interactions = self.interactions.write().await;
+        for entry in history {
+            interactions.insert(
+                (entry.from, entry.to),
+                entry.records.iter().map(Into::into).collect(),
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl TrustFetcher for NetworkTrustFetcher {
+    type NodeId = NodeId;
+
+    async fn fetch_trust(&self, i: &NodeId, j: &NodeId) -> Result<f64> {
+        // Use the new timestamp-based implementation
+        self.fetch_trust(i, j).await
+    }
+
+    async fn discover_peers(&self, _: &NodeId) -> Result<HashSet<NodeId>> {
+        let mut all_peers = self.peers.read().await.clone();
+        all_peers.extend(self.gossip_peers.read().await.iter().copied());
+
+        if let Ok(peers) = self.get_peers().await {
+            all_peers.extend(peers);
+        }
+
+        Ok(all_peers)
+    }
+}
+
+// Add this enum to track probe outcomes
+#[derive(Debug, Clone)]
+pub enum ProbeResult {
+    Success(Stats),
+    Timeout(std::time::Duration),
+    Error,
+}
+
+impl Tracker {
+    /// Create a new tracker service
+    #[allow(clippy::too_many_arguments)]
+    pub async fn new(
+        shutdown_rx: watch::Receiver<()>,
+        eth_ws_url: Url,
+        factory_address: &Address,
+        iroh_node_id: NodeId,
+        eth_private_key: PrivateKeySigner,
+        blobs_service: BlobsService,
+        iroh_signature: Signature,
+        store: TrackerStore,
+        prober: Prober,
+        gossip: Gossip,
+    ) -> Result<Self> {
+        let (factory_event_tx, _factory_event_rx) = mpsc::channel(100);
+
+        let factory_contract = FactoryContract::new(
+            factory_address,
+            &eth_ws_url,
+            &eth_private_key,
+            factory_event_tx.clone(),
+        )
+        .await?;
+
+        let tracker = Self {
+            pools: Arc::new(RwLock::new(HashMap::new())),
+            formats: Arc::new(RwLock::new(HashMap::new())),
+            left_pools: Arc::new(RwLock::new(HashSet::new())),
+            pool_trust: Arc::new(RwLock::new(HashMap::new())),
+            global_trust: Arc::new(RwLock::new(HashMap::new())),
+            shutdown_rx: shutdown_rx.clone(),
+            eth_ws_url: Arc::new(eth_ws_url),
+            eth_private_key: Arc::new(eth_private_key),
+            factory_contract: Arc::new(RwLock::new(factory_contract)),
+            blobs_service: Arc::new(blobs_service),
+            current_node_id: iroh_node_id,
+            iroh_signature,
+            update_lock: Arc::new(Mutex::new(())),
+            store: Arc::new(store),
+            events: TrackerEvents::default(),
+            prober,
+            gossip,
+        };
+
+        // restore whatever we knew before the last shutdown prior to
+        //  the first chain sync, so we don't start everyone at zero trust
+        match tracker.store.load().await {
+            Ok(Some(snapshot)) => tracker.restore(snapshot).await?,
+            Ok(None) => {
+                tracing::info!("node::tracker: no tracker snapshot found, starting fresh")
+            }
+            Err(e) => tracing::warn!(
+                "node::tracker: failed to load tracker snapshot from {}: {}",
+                tracker.store.path().display(),
+                e
+            ),
+        }
+
+        let tracker_clone = tracker.clone();
+        tokio::spawn(async move {
+            tracker_clone.start_background_jobs().await;
+        });
+
+        Ok(tracker)
+    }
+
+    pub async fn create_pool(
+        &self,
+        hash: Hash,
+        format: BlobFormat,
+        value: Option<U256>,
+    ) -> Result<()> {
+        tracing::info!("Creating pool {} ({})", hash, format);
+        self.set_content_format(hash, format).await;
+        let factory = self.factory_contract.read().await;
+        // TODO (amiller68): for some reason this seemed to be returning the
+        //  wrong address -- we should be updating our local knowledge of pools here
+        factory.create_pool(hash, value).await?;
+        // tracing::info!("Pool created at {}", pool_address);
+        // self.add_pool(PoolKey { hash, address: pool_address }).await?;
+        Ok(())
+    }
+
+    /// Format of some pooled content, assuming raw until we learn otherwise
+    pub async fn content_format(&self, hash: Hash) -> BlobFormat {
+        self.formats
+            .read()
+            .await
+            .get(&hash)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub async fn set_content_format(&self, hash: Hash, format: BlobFormat) {
+        self.formats.write().await.insert(hash, format);
+    }
+
+    /// Work out the format of pooled content, either from our own store
+    ///  or by asking the pool's peers
+    async fn resolve_format(&self, hash: Hash, peers: &HashSet<NodeId>) -> BlobFormat {
+        if let Some(format) = self.formats.read().await.get(&hash) {
+            return *format;
+        }
+        let format = if self
+            .blobs_service
+            .get_blob_stat(hash)
+            .await
+            .unwrap_or(false)
+        {
+            self.blobs_service.get_format(hash).await.ok()
+        } else {
+            let mut detected = None;
+            for peer in peers.iter().filter(|p| **p != self.current_node_id) {
+                match detect_format(&self.prober, peer, &hash).await {
+                    Ok(format) => {
+                        detected = Some(format);
+                        break;
+                    }
+                    Err(e) => tracing::debug!(
+                        "tracker::resolve_format: failed to detect format of {} on {}: {}",
+                        hash,
+                        peer,
+                        e
+                    ),
+                }
+            }
+            detected
+        };
+        match format {
+            Some(format) => {
+                self.set_content_format(hash, format).await;
+                format
+            }
+            // don't remember a guess, we'll try again next round
+            None => BlobFormat::Raw,
+        }
+    }
+
+    /// Subscribe to the live stream of tracker events
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<TrackerEvent> {
+        self.events.subscribe()
+    }
+
+    pub async fn set_pool_balance(&self, key: PoolKey, amount: U256) {
+        let mut pools = self.pools.write().await;
+        pools.insert(key, amount);
+    }
+
+    pub async fn add_pool_deposit(&self, key: PoolKey, amount: U256) {
+        let mut pools = self.pools.write().await;
+        let value = pools.get(&key).copied();
+        if let Some(value) = value {
+            pools.insert(key, amount + value);
+        } else {
+            tracing::warn!("node::tracker::: attempted to up deposit state of non-extant pool");
+        }
+    }
+
+    /// NOTE (amiller68): this is a janky place to put this, but it's convenient
+    ///  since the tracker has the ws url
+    pub async fn get_address_balance(&self, address: Address) -> Result<U256> {
+        let balance = get_address_balance(address, &self.eth_ws_url).await?;
+        Ok(balance)
+    }
+
+    /// NOTE (amiller68): yet another way to get the balance of a pool (from anvil)
+    pub async fn get_pool_balance_live(&self, key: PoolKey) -> Result<U256> {
+        let balance = get_address_balance(key.address, &self.eth_ws_url).await?;
+        Ok(balance)
+    }
+
+    pub async fn claim_pool_rewards(&self, key: PoolKey) -> Result<()> {
+        // make sure the pool exists
+        let pools = self.pools.read().await;
+        if !pools.contains_key(&key) {
+            return Err(anyhow::anyhow!("Pool does not exist: {}", key.address));
+        }
+        let factory = self.factory_contract.read().await;
+        let hash = key.hash;
+        factory.claim_rewards(hash).await?;
+        self.events.publish(TrackerEvent::RewardsClaimed {
+            address: key.address,
+            hash,
+        });
+        Ok(())
+    }
+
+    pub async fn deposit_into_pool(&self, key: PoolKey, amount: U256) -> Result<()> {
+        let pool_contract =
+            PoolContract::new(key.address, &self.eth_ws_url, &self.eth_private_key, self).await?;
+        pool_contract.deposit(amount).await?;
+        let address = key.address;
+        let balance = get_address_balance(address, &self.eth_ws_url).await?;
+        self.events.publish(TrackerEvent::DepositSeen {
+            address,
+            hash: key.hash,
+            balance,
+        });
+        // update local state -- this can just be incrementing the balance
+        self.add_pool_deposit(key, balance).await;
+        Ok(())
+    }
+
+    pub async fn add_pool(&self, key: PoolKey, balance: U256) -> Result<()> {
+        // check if the pool already exists
+        if self.pools.read().await.contains_key(&key) {
+            tracing::warn!("Pool already exists: {}", key.address);
+            return Ok(());
+        }
+        // joined before taking the lock, a slow join would stall every reader
+        self.gossip
+            .join(TopicId::for_pool(key.address), std::iter::empty())
+            .await;
+        {
+            let mut pools = self.pools.write().await;
+            if pools.contains_key(&key) {
+                return Ok(());
+            }
+            pools.insert(key.clone(), balance);
+        }
+        self.events.publish(TrackerEvent::PoolDiscovered {
+            address: key.address,
+            hash: key.hash,
+            balance,
+        });
+
+        // Create new EigenTrust instance for this pool
+        let network_fetcher = NetworkTrustFetcher::new(key.clone(), self.eth_ws_url.clone());
+        let pool_eigen = EigenTrust::new(network_fetcher);
+        self.pool_trust
+            .write()
+            .await
+            .insert(key.clone(), pool_eigen);
+        Ok(())
+    }
+
+    pub async fn add_pool_peer(&self, key: PoolKey, node_id: NodeId) {
+        tracing::info!(
+            "tracker::add_pool_peer: adding peer {} to pool {}",
+            node_id,
+            key.address
+        );
+        self.gossip
+            .add_neighbor(TopicId::for_pool(key.address), node_id)
+            .await;
+        if let Some(eigen) = self.pool_trust.write().await.get_mut(&key) {
+            // Add peer to the pool's trust network with zero initial trust
+            if let Some(fetcher) = eigen.get_fetcher_mut() {
+                fetcher.add_peer(node_id).await;
+                // Record initial interaction with zero trust
+                fetcher
+                    .record_interaction(self.current_node_id, node_id, false)
+                    .await;
+            }
+            // Set initial local trust to 0
+            eigen.update_local_trust(node_id, 0.0, 1.0);
+            self.events.publish(TrackerEvent::PeerAdded {
+                address: key.address,
+                hash: key.hash,
+                node_id,
+            });
+        }
+    }
+
+    /// Get global known peers for a given hash, regardless of pool
+    pub async fn get_pool_peers(&self, key: PoolKey) -> Result<Vec<NodeId>> {
+        let pool_trust = self.pool_trust.read().await;
+        if let Some(eigen) = pool_trust.get(&key) {
+            if let Some(fetcher) = eigen.get_fetcher() {
+                return Ok(fetcher.peers.read().await.iter().cloned().collect());
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    pub async fn get_pool_balance(&self, key: PoolKey) -> Result<U256> {
+        let pools = self.pools.read().await;
+        let balance = pools.get(&key);
+        if let Some(balance) = balance {
+            Ok(*balance)
+        } else {
+            Err(anyhow::anyhow!("Pool does not exist: {}", key.address))
+        }
+    }
+
+    pub async fn enter_pool(&self, key: PoolKey) -> Result<()> {
+        // Check if the pool exists
+        let pools = self.pools.read().await;
+        if !pools.contains_key(&key) {
+            return Err(anyhow::anyhow!("Pool does not exist: {}", key.address));
+        }
+
+        // Create a pool contract and enter the pool
+        let pool_contract =
+            PoolContract::new(key.address, &self.eth_ws_url, &self.eth_private_key, self).await?;
+
+        // sanity check -- we should make sure we have the hash
+        //  prior to entering the pool
+        // see if you have the hash
+        let format = self.content_format(key.hash).await;
+        let stat = self.blobs_service.has_content(key.hash, format).await?;
+        if !stat {
+            return Err(anyhow::anyhow!("You don't have the hash: {}", key.hash));
+        }
+
+        // Enter the pool
+        pool_contract.enter_pool().await?;
+
+        // hold the content for as long as we're in the pool -- only now
+        //  that we are, so a failed entry leaves nothing pinned
+        self.blobs_service
+            .tag(BlobTag::Pool(key.address), key.hash, format)
+            .await?;
+
+        // mark yourself as a peer
+        self.add_pool_peer(key.clone(), self.current_node_id).await;
+        self.announce(key.address, Announcement::Joined { hash: key.hash })
+            .await;
+
+        Ok(())
+    }
+
+    /// Pools backed by `hash` that we're a member of
+    pub async fn joined_pools(&self, hash: Hash) -> Result<Vec<PoolKey>> {
+        Ok(self
+            .all_joined_pools()
+            .await?
+            .into_iter()
+            .filter(|key| key.hash == hash)
+            .collect())
+    }
+
+    /// Every pool we hold content for, whatever the hash
+    pub async fn all_joined_pools(&self) -> Result<Vec<PoolKey>> {
+        let keys = self.pools.read().await.keys().cloned().collect::<Vec<_>>();
+        let tags = self.blobs_service.tags().await?;
+        // copied out, `get_pool_peers` below takes `pool_trust`
+        let left_pools = self.left_pools.read().await.clone();
+        let mut joined = Vec::new();
+        for key in keys {
+            if left_pools.contains(&key.address) {
+                continue;
+            }
+            let tagged = tags
+                .iter()
+                .any(|(tag, _)| *tag == BlobTag::Pool(key.address));
+            let peers = self.get_pool_peers(key.clone()).await?;
+            if tagged || peers.contains(&self.current_node_id) {
+                joined.push(key);
+            }
+        }
+        Ok(joined)
+    }
+
+    /// Stop holding content for a pool, and don't rejoin it. The
+    ///  contract has no way to leave, so we stay listed as a peer --
+    ///  we just stop answering for the content.
+    pub async fn leave_pool(&self, key: &PoolKey) -> Result<()> {
+        // nothing changes unless the content is actually let go
+        self.blobs_service.untag(BlobTag::Pool(key.address)).await?;
+        self.left_pools.write().await.insert(key.address);
+        self.finish_leaving(std::slice::from_ref(key)).await
+    }
+
+    /// Delete content, leaving every pool it backs -- joined or not, or
+    ///  the next sync would pull it back. The pools are only left once
+    ///  the content is gone; if the delete fails we stay in them. `None`
+    ///  if we don't hold the content.
+    pub async fn delete_content(&self, hash: Hash) -> Result<Option<(Vec<PoolKey>, GcReport)>> {
+        let keys = self.pools_for_hash(hash).await;
+        let report =
+            while_leaving(&self.left_pools, &keys, self.blobs_service.delete(hash)).await?;
+        let Some(report) = report else {
+            return Ok(None);
+        };
+        self.finish_leaving(&keys).await?;
+        Ok(Some((keys, report)))
+    }
+
+    /// Tell everyone about pools we've just marked as left
+    async fn finish_leaving(&self, keys: &[PoolKey]) -> Result<()> {
+        for key in keys {
+            self.gossip.leave(TopicId::for_pool(key.address)).await;
+            self.events.publish(TrackerEvent::PoolLeft {
+                address: key.address,
+                hash: key.hash,
+            });
+        }
+        self.persist().await
+    }
+
+    /// Pools backed by `hash` that we haven't left, joined or not
+    pub async fn pools_for_hash(&self, hash: Hash) -> Vec<PoolKey> {
+        let pools = self.pools.read().await;
+        let left_pools = self.left_pools.read().await;
+        pools
+            .keys()
+            .filter(|key| key.hash == hash && !left_pools.contains(&key.address))
+            .cloned()
+            .collect()
+    }
+
+    pub async fn probe_node(&self, ticket: BlobTicket) -> ProbeResult {
+        let node_id = ticket.node_addr().node_id;
+        tracing::info!("tracker::probe_node: probing node {:?}", node_id);
+        let hash_and_format = HashAndFormat {
+            hash: ticket.hash(),
+            format: ticket.format(),
+        };
+        // Add a timeout of 10 seconds
+        let probe_future = probe_complete(&self.prober, &node_id, &hash_and_format);
+        let timeout = std::time::Duration::from_secs(5);
+        let result = match tokio::time::timeout(timeout, probe_future).await {
+            Ok(Ok(result)) => {
+                tracing::info!("success probe result: {:?}", result);
+                return ProbeResult::Success(result);
+            }
+            Ok(Err(e)) => {
+                tracing::info!("error probe result: {:?}", e);
+                ProbeResult::Error
+            }
+            Err(_) => ProbeResult::Timeout(timeout),
+        };
+        // the connection may be what's broken, dial again next time
+        self.prober.forget(&node_id).await;
+        result
+    }
+
+    /// How much we trust each of a pool's peers ourselves, before
+    ///  it's folded into global trust. None if we don't track the pool.
+    pub async fn local_trust_row(&self, address: Address) -> Option<HashMap<NodeId, f64>> {
+        let pool_trust = self.pool_trust.read().await;
+        let eigen = pool_trust
+            .iter()
+            .find(|(key, _)| key.address == address)
+            .map(|(_, eigen)| eigen)?;
+        let peers = match eigen.get_fetcher() {
+            Some(fetcher) => fetcher.peers.read().await.clone(),
+            None => HashSet::new(),
+        };
+        Some(
+            peers
+                .into_iter()
+                .filter(|peer| *peer != self.current_node_id)
+                .map(|peer| (peer, eigen.get_local_trust(&peer).unwrap_or(0.0)))
+                .collect(),
+        )
+    }
+
+    /// Ask a peer about itself over the jax protocol
+    pub async fn query_peer(&self, node_id: NodeId, request: &Request) -> Result<Response> {
+        let result = async {
+            let connection = self.prober.connect(node_id, JAX_ALPN).await?;
+            protocol::request(&connection, request).await
+        };
+        let result = match tokio::time::timeout(PEER_QUERY_TIMEOUT, result).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow::anyhow!("no answer after {:?}", PEER_QUERY_TIMEOUT)),
+        };
+        if result.is_err() {
+            self.prober.forget(&node_id).await;
+        }
+        result
+    }
+
+    pub async fn get_pool_trust(&self, key: &PoolKey) -> Result<Option<HashMap<NodeId, f64>>> {
+        if let Some(eigen) = self.pool_trust.write().await.get_mut(key) {
+            Ok(Some(eigen.compute_global_trust().await?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub async fn get_hash_trust(&self, hash: &Hash) -> Result<Option<HashMap<NodeId, f64>>> {
+        // find the pool with this hash -- there should only be one
+        let pools = self.pools.read().await;
+        let pool = pools.iter().find(|(p, _)| p.hash == *hash);
+        if let Some((pool, _)) = pool {
+            match self.get_pool_trust(pool).await {
+                Ok(Some(trust_scores)) => Ok(Some(trust_scores)),
+                Ok(None) => Ok(None),
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to get trust scores for pool {}: {}",
+                        pool.address,
+                        e
+                    );
+                    Ok(None)
+                }
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub async fn list_pools_with_trust(&self) -> Result<BTreeMap<PoolInfo, HashMap<NodeId, f64>>> {
+        let mut result = BTreeMap::new();
+        let pools = self.pools.read().await;
+        let mut pool_trust = self.pool_trust.write().await;
+
+        for (key, eigen) in pool_trust.iter_mut() {
+            // NOTE (amiller68): we should be gauranteed that the pool exists
+            let balance = pools.get(key).unwrap_or(&U256::ZERO);
+            let pool_info = PoolInfo {
+                key: key.clone(),
+                balance: *balance,
+            };
+            let trust = eigen.compute_global_trust().await.unwrap_or_default();
+            result.insert(pool_info, trust);
+        }
+
+        Ok(result)
+    }
+
+    /// Pools backed by each hash, and where we rank by trust in each. Trust
+    ///  is as of the last update, rather than recomputed on every call.
+    pub async fn blob_pools(&self) -> Result<HashMap<Hash, Vec<PoolMembership>>> {
+        let keys = self.pools.read().await.keys().cloned().collect::<Vec<_>>();
+        let mut result: HashMap<Hash, Vec<PoolMembership>> = HashMap::new();
+        for key in keys {
+            let trust = self
+                .global_trust
+                .read()
+                .await
+                .get(&key)
+                .cloned()
+                .unwrap_or_default();
+            let mut ranked = trust.into_iter().collect::<Vec<_>>();
+            ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+            let rank = ranked
+                .iter()
+                .position(|(node, _)| *node == self.current_node_id)
+                .map(|position| position + 1);
+            result.entry(key.hash).or_default().push(PoolMembership {
+                address: key.address,
+                rank,
+                peers: ranked.len(),
+            });
+        }
+        Ok(result)
+    }
+
+    pub async fn update_local_trust(
+        &self,
+        key: PoolKey,
+        node_id: NodeId,
+        probe_result: ProbeResult,
+    ) -> Result<()> {
+        let success = matches!(probe_result, ProbeResult::Success(_));
+        // More aggressive trust updates
+        let trust_delta = match probe_result {
+            ProbeResult::Success(_) => 0.2,
+            ProbeResult::Error => -0.9,
+            ProbeResult::Timeout(_) => -0.8,
+        };
+        self.adjust_local_trust(key, node_id, success, trust_delta)
+            .await;
+        Ok(())
+    }
+
+    /// Record an interaction with `node_id` and nudge our local trust in it
+    async fn adjust_local_trust(
+        &self,
+        key: PoolKey,
+        node_id: NodeId,
+        success: bool,
+        trust_delta: f64,
+    ) {
+        if let Some(eigen) = self.pool_trust.write().await.get_mut(&key) {
+            if let Some(fetcher) = eigen.get_fetcher_mut() {
+                fetcher
+                    .record_interaction(self.current_node_id, node_id, success)
+                    .await;
+            }
+
+            let current_trust = eigen.get_local_trust(&node_id).unwrap_or(0.5);
+            let new_trust = (current_trust + trust_delta).clamp(0.0, 1.0);
+
+            // Higher weight for faster trust updates
+            eigen.update_local_trust(node_id, new_trust, 0.8); // Increased weight
+        }
+    }
+
+    /// Challenge a node to prove it stores the content of a pool. Unlike a
+    ///  probe, this tells apart nodes that are slow, wrong or missing the content.
+    pub async fn challenge_node(&self, key: &PoolKey, node_id: NodeId) -> Result<ChallengeResult> {
+        tracing::info!(
+            "tracker::challenge_node: challenging node {:?} | {:?}",
+            node_id,
+            key.hash
+        );
+        let result = challenge(&self.prober, &node_id, &self.blobs_service, key.hash).await;
+        if !result.as_ref().is_ok_and(ChallengeResult::is_passed) {
+            self.prober.forget(&node_id).await;
+        }
+        result
+    }
+
+    pub async fn challenge_and_update_trust(
+        &self,
+        key: PoolKey,
+        node_id: NodeId,
+    ) -> Result<ChallengeResult> {
+        let result = self.challenge_node(&key, node_id).await?;
+        tracing::info!(
+            "tracker::challenge_and_update_trust: {:?} -> {:?}",
+            node_id,
+            result
+        );
+        self.events.publish(TrackerEvent::ChallengeResult {
+            address: key.address,
+            hash: key.hash,
+            node_id,
+            result: result.clone(),
+        });
+        let trust_delta = match result {
+            ChallengeResult::Passed { .. } => 0.2,
+            // probably proxying, but at least it can get at the content
+            ChallengeResult::Slow { .. } => -0.3,
+            ChallengeResult::Absent => -0.9,
+            ChallengeResult::Unreachable { .. } => -0.8,
+            // lying about what it stores is the worst thing a peer can do
+            ChallengeResult::Wrong => -1.0,
+        };
+        self.adjust_local_trust(key, node_id, result.is_passed(), trust_delta)
+            .await;
+        Ok(result)
+    }
+
+    pub async fn probe_and_update_trust(
+        &self,
+        key: PoolKey,
+        node_id: NodeId,
+    ) -> Result<ProbeResult> {
+        tracing::info!(
+            "tracker::probe_and_update_trust: probing node {:?} | {:?}",
+            node_id,
+            key.hash
+        );
+        let format = self.content_format(key.hash).await;
+        let ticket = BlobTicket::new(node_id.into(), key.hash, format).expect("valid ticket");
+
+        // Reduce timeout threshold to 2 seconds
+        let probe_result = self.probe_node(ticket).await;
+        self.events
+            .publish(TrackerEvent::probe_result(&key, node_id, &probe_result));
+        self.update_local_trust(key.clone(), node_id, probe_result.clone())
+            .await?;
+        Ok(probe_result)
+    }
+
+    // Add this method to probe all nodes in a pool
+    pub async fn probe_pool(&self, key: PoolKey) -> Result<()> {
+        tracing::info!(
+            "tracker::probe_pool: probing pool {:?} | {:?}",
+            key.address,
+            key.hash
+        );
+        let peers = self.get_pool_peers(key.clone()).await?;
+        // we can only check challenge answers against our own copy, and
+        //  challenges only cover raw blobs -- hash seqs get probed instead
+        let have_content = self.content_format(key.hash).await == BlobFormat::Raw
+            && self.blobs_service.get_blob_stat(key.hash).await?;
+        let mut results = Vec::new();
+        for node_id in peers {
+            if node_id == self.current_node_id {
+                continue;
+            }
+            tracing::info!("tracker::probe_pool: probing node {:?}", node_id);
+            let result = if have_content {
+                self.challenge_and_update_trust(key.clone(), node_id)
+                    .await
+                    .map(|result| result.is_passed())
+            } else {
+                self.probe_and_update_trust(key.clone(), node_id)
+                    .await
+                    .map(|result| matches!(result, ProbeResult::Success(_)))
+            };
+            match result {
+                Ok(success) => results.push(ProbeOutcome { node_id, success }),
+                Err(e) => tracing::warn!(
+                    "tracker::probe_pool: failed to probe node {}: {}",
+                    node_id,
+                    e
+                ),
+            }
+        }
+        // let the rest of the pool weigh in on what we saw
+        if !results.is_empty() {
+            self.announce(key.address, Announcement::ProbeSummary { results })
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Tell a pool's topic that we hold `hash`, for each pool it backs
+    pub async fn announce_have(&self, hash: Hash) {
+        for key in self.pools_for_hash(hash).await {
+            self.announce(key.address, Announcement::Have { hash })
+                .await;
+        }
+    }
+
+    async fn announce(&self, address: Address, announcement: Announcement) {
+        if let Err(e) = self
+            .gossip
+            .broadcast(TopicId::for_pool(address), announcement)
+            .await
+        {
+            tracing::warn!(
+                "tracker::announce: failed to announce to pool {}: {}",
+                address,
+                e
+            );
+        }
+    }
+
+    /// Re-announce the content we hold, for peers that have come along
+    ///  since we last did
+    async fn announce_holdings(&self) -> Result<()> {
+        let keys = self.pools.read().await.keys().cloned().collect::<Vec<_>>();
+        let left_pools = self.left_pools.read().await.clone();
+        for key in keys {
+            if left_pools.contains(&key.address) {
+                continue;
+            }
+            let format = self.content_format(key.hash).await;
+            if self.blobs_service.has_content(key.hash, format).await? {
+                self.announce(key.address, Announcement::Have { hash: key.hash })
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Learn from what a pool's peers announce: who else holds its
+    ///  content, and how their probes of each other went
+    async fn handle_gossip(&self, event: GossipEvent) {
+        let pool_trust = self.pool_trust.read().await;
+        let Some((key, eigen)) = pool_trust
+            .iter()
+            .find(|(key, _)| TopicId::for_pool(key.address) == event.topic)
+        else {
+            return;
+        };
+        let Some(fetcher) = eigen.get_fetcher() else {
+            return;
+        };
+        match event.announcement {
+            // claims get checked before they count for anything
+            Announcement::Have { hash } | Announcement::Joined { hash }
+                if hash == key.hash && fetcher.start_confirming(event.from).await =>
+            {
+                tracing::debug!(
+                    "tracker::handle_gossip: {} says it holds content for pool {}",
+                    event.from,
+                    key.address
+                );
+                let tracker = self.clone();
+                let key = key.clone();
+                tokio::spawn(async move { tracker.confirm_gossip_peer(key, event.from).await });
+            }
+            // only peers get a say in the pool's trust
+            Announcement::ProbeSummary { results } if fetcher.is_peer(&event.from).await => {
+                for outcome in results {
+                    if outcome.node_id != event.from {
+                        fetcher
+                            .record_interaction(event.from, outcome.node_id, outcome.success)
+                            .await;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Probe a peer that gossiped it holds a pool's content, and only
+    ///  count it among the pool's peers if it does
+    async fn confirm_gossip_peer(&self, key: PoolKey, node_id: NodeId) {
+        let content = HashAndFormat {
+            hash: key.hash,
+            format: self.content_format(key.hash).await,
+        };
+        let probe = probe_complete(&self.prober, &node_id, &content);
+        let confirmed = match tokio::time::timeout(GOSSIP_PROBE_TIMEOUT, probe).await {
+            Ok(Ok(_)) => true,
+            Ok(Err(e)) => {
+                tracing::debug!(
+                    "tracker::confirm_gossip_peer: {} doesn't hold content for pool {}: {}",
+                    node_id,
+                    key.address,
+                    e
+                );
+                false
+            }
+            Err(_) => false,
+        };
+        if !confirmed {
+            self.prober.forget(&node_id).await;
+        }
+        if let Some(fetcher) = self
+            .pool_trust
+            .read()
+            .await
+            .get(&key)
+            .and_then(|eigen| eigen.get_fetcher())
+        {
+            fetcher.finish_confirming(node_id, confirmed).await;
+        }
+    }
+
+    /// Start background jobs for pool maintenance
+    pub async fn start_background_jobs(&self) {
+        let tracker = self.clone();
+
+        // Try initial bootstrap with retries
+        for i in 0..3 {
+            match tracker.update_all_pools().await {
+                Ok(_) => {
+                    tracing::info!(
+                        "tracker::start_background_jobs: successfully bootstrapped tracker"
+                    );
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "tracker::start_background_jobs: bootstrap attempt {} failed: {}",
+                        i + 1,
+                        e
+                    );
+                    if i < 2 {
+                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    }
+                }
+            }
+        }
+
+        let mut shutdown_rx = self.shutdown_rx.clone();
+        let mut gossip_rx = self.gossip.subscribe();
+
+        // Spawn background task with shorter interval
+        tokio::spawn(async move {
+            // Reduce interval to 2 seconds for more frequent updates
+            let update_interval = tokio::time::Duration::from_secs(5);
+            let mut interval = tokio::time::interval(update_interval);
+            let mut snapshot_interval = tokio::time::interval(SNAPSHOT_INTERVAL);
+            let mut retention_interval = tokio::time::interval(RETENTION_INTERVAL);
+            let mut announce_interval = tokio::time::interval(ANNOUNCE_INTERVAL);
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        if let Err(e) = tracker.update_all_pools().await {
+                            tracing::warn!("tracker::start_background_jobs: failed to update pools: {}", e);
+                        }
+                    }
+                    _ = snapshot_interval.tick() => {
+                        if let Err(e) = tracker.persist().await {
+                            tracing::warn!("tracker::start_background_jobs: failed to persist tracker state: {}", e);
+                        }
+                    }
+                    _ = retention_interval.tick() => {
+                        if let Err(e) = tracker.blobs_service.enforce_retention().await {
+                            tracing::warn!("tracker::start_background_jobs: failed to enforce retention: {}", e);
+                        }
+                    }
+                    _ = announce_interval.tick() => {
+                        if let Err(e) = tracker.announce_holdings().await {
+                            tracing::warn!("tracker::start_background_jobs: failed to announce holdings: {}", e);
+                        }
+                    }
+                    event = gossip_rx.recv() => match event {
+                        Ok(event) => tracker.handle_gossip(event).await,
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                            tracing::warn!("tracker::start_background_jobs: missed {} gossip messages", skipped);
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => {}
+                    },
+                    _ = shutdown_rx.changed() => {
+                        tracing::info!("tracker::start_background_jobs: shutting down pool maintenance jobs");
+                        tracker.prober.close().await;
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Update all pools - fetch new peers and recalculate trust scores
+    async fn update_all_pools(&self) -> Result<()> {
+        // Try to acquire the lock, return immediately if another update is in progress
+        let _lock = match self.update_lock.try_lock() {
+            Ok(guard) => guard,
+            Err(_) => {
+                tracing::debug!("tracker::update_all_pools: update already in progress, skipping");
+                return Ok(());
+            }
+        };
+
+        tracing::info!("tracker::update_all_pools: updating all pools");
+        // read the factory contract
+        let factory = self.factory_contract.read().await;
+
+        // get all pools from the factory
+        let pools = factory.get_all_pools().await?;
+
+        // add the new pools to the pool set and update the pool info
+        let mut pool_info = Vec::new();
+
+        tracing::info!("tracker::update_all_pools: found {} pools", pools.len());
+        for pool in pools {
+            let pool_contract =
+                PoolContract::new(pool, &self.eth_ws_url, &self.eth_private_key, self).await?;
+            let hash = match pool_contract.get_hash().await {
+                Ok(hash) => hash,
+                Err(e) => {
+                    tracing::warn!(
+                        "tracker::update_all_pools: failed to get hash for pool {}: {}",
+                        pool,
+                        e
+                    );
+                    continue;
+                }
+            };
+            let balance = match pool_contract.get_balance().await {
+                Ok(balance) => balance,
+                Err(e) => {
+                    tracing::warn!(
+                        "tracker::update_all_pools: failed to get balance for pool {}: {}",
+                        pool,
+                        e
+                    );
+                    continue;
+                }
+            };
+            let pi = PoolInfo {
+                key: PoolKey {
+                    hash,
+                    address: pool,
+                },
+                balance,
+            };
+            pool_info.push(pi.clone());
+            let pk = pi.key();
+            let previous = self.pools.read().await.get(pk).copied();
+            match previous {
+                None => self.add_pool(pk.clone(), balance).await?,
+                Some(previous) => {
+                    if balance > previous {
+                        self.events.publish(TrackerEvent::DepositSeen {
+                            address: pk.address,
+                            hash: pk.hash,
+                            balance,
+                        });
+                    }
+                    // update the pool balance
+                    self.pools.write().await.insert(pk.clone(), balance);
+                }
+            }
+        }
+
+        tracing::info!("tracker::update_all_pools: updating pool peers and trust scores");
+        // update the pool peers and trust scores
+        for pi in pool_info {
+            let pool_key = pi.key();
+            // get the current pool peers
+            let current_peers = self.get_pool_peers(pool_key.clone()).await?;
+            let current_peers_set: HashSet<_> = current_peers.clone().into_iter().collect();
+            // get the historical peers
+            let all_peers = get_peers(pi.key().address, &self.eth_ws_url).await?;
+            let all_peers_set: HashSet<_> = all_peers.clone().into_iter().collect();
+            // get the new peer/
+            let new_peers = all_peers_set.difference(&current_peers_set);
+            // add the new peers
+            tracing::info!("tracker::update_all_pools: new peers: {:?}", new_peers);
+            for peer in new_peers {
+                self.add_pool_peer(pool_key.clone(), *peer).await;
+            }
+
+            let format = self.resolve_format(pool_key.hash, &all_peers_set).await;
+
+            if self.left_pools.read().await.contains(&pool_key.address) {
+                tracing::debug!(
+                    "tracker::update_all_pools: left pool {}, skipping join",
+                    pool_key.address
+                );
+            } else if !all_peers_set.contains(&self.current_node_id) {
+                tracing::info!(
+                    "tracker::update_all_pools: attempting to join pool {:?}",
+                    pool_key.address
+                );
+                // check if you have the hash
+                let stat = self
+                    .blobs_service
+                    .has_content(pool_key.hash, format)
+                    .await?;
+                let mut proceed = stat;
+                if !stat {
+                    tracing::info!(
+                        "tracker::update_all_pools: attempting to download hash {:?}",
+                        pool_key.hash
+                    );
+                    // iterate through the peers and attempt to download the hash
+                    for peer in all_peers.clone() {
+                        let ticket = BlobTicket::new(peer.into(), pool_key.hash, format)
+                            .expect("valid ticket");
+                        // attempt to probe the node
+                        let probe_result =
+                            self.probe_and_update_trust(pool_key.clone(), peer).await?;
+                        if !matches!(probe_result, ProbeResult::Success(_)) {
+                            tracing::info!(
+                                "tracker::update_all_pools: failed to probe node {:?}",
+                                peer
+                            );
+                            continue;
+                        }
+                        tracing::info!(
+                            "tracker::update_all_pools: successfully probed node {:?}",
+                            peer
+                        );
+                        // cached until we're actually in the pool -- if
+                        //  entering fails it's evicted like anything else
+                        if self
+                            .blobs_service
+                            .download_blob(&ticket, BlobTag::Cache(pool_key.hash))
+                            .await
+                            .is_ok()
+                        {
+                            proceed = true;
+                            break;
+                        }
+                    }
+                }
+                if proceed {
+                    tracing::info!(
+                        "tracker::update_all_pools: successfully downloaded (or had) hash {:?}",
+                        pool_key.hash
+                    );
+                    match self.enter_pool(pool_key.clone()).await {
+                        Ok(_) => {
+                            tracing::info!(
+                                "tracker::update_all_pools: successfully joined pool {}",
+                                pool_key.address
+                            );
+                            self.events.publish(TrackerEvent::PoolJoined {
+                                address: pool_key.address,
+                                hash: pool_key.hash,
+                            });
+                        }
+                        Err(e) => {
+                            tracing::warn!(
+                                "tracker::update_all_pools: failed to join pool {}: {}",
+                                pool_key.address,
+                                e
+                            );
+                            self.events.publish(TrackerEvent::PoolJoinFailed {
+                                address: pool_key.address,
+                                hash: pool_key.hash,
+                                reason: e.to_string(),
+                            });
+                        }
+                    }
+                } else {
+                    tracing::warn!(
+                        "tracker::update_all_pools: failed to download hash for pool {}, skipping join",
+                        pool_key.address
+                    );
+                    self.events.publish(TrackerEvent::PoolJoinFailed {
+                        address: pool_key.address,
+                        hash: pool_key.hash,
+                        reason: "failed to download hash from any peer".to_string(),
+                    });
+                }
+            } else {
+                tracing::debug!(
+                    "tracker::update_all_pools: already in pool {}, skipping join",
+                    pool_key.address
+                );
+            }
+            // this both probes and updates trust
+            tracing::info!(
+                "tracker::update_all_pools: probing pool {:?}",
+                pool_key.address
+            );
+            self.probe_pool(pool_key.clone()).await?;
+        }
+
+        // Add more aggressive periodic trust decay
+        for (_key, eigen) in self.pool_trust.write().await.iter_mut() {
+            let peers = eigen.get_peers().clone();
+            for peer in peers {
+                if let Some(current_trust) = eigen.get_local_trust(&peer) {
+                    // Much stronger decay - 30% every update cycle
+                    let decayed_trust = current_trust * 0.7;
+                    eigen.update_local_trust(peer, decayed_trust, 0.9);
+                }
+            }
+        }
+
+        self.publish_trust_changes().await;
+
+        Ok(())
+    }
+
+    /// Recompute global trust for every pool and publish the pools where
+    ///  it moved since the last update
+    async fn publish_trust_changes(&self) {
+        let mut pool_trust = self.pool_trust.write().await;
+        let mut global_trust = self.global_trust.write().await;
+        for (key, eigen) in pool_trust.iter_mut() {
+            let trust = match eigen.compute_global_trust().await {
+                Ok(trust) => trust,
+                Err(e) => {
+                    tracing::warn!(
+                        "tracker::publish_trust_changes: failed to compute trust for pool {}: {}",
+                        key.address,
+                        e
+                    );
+                    continue;
+                }
+            };
+            if global_trust.get(key) == Some(&trust) {
+                continue;
+            }
+            self.events.publish(TrackerEvent::TrustRecomputed {
+                address: key.address,
+                hash: key.hash,
+                trust: trust.clone(),
+            });
+            global_trust.insert(key.clone(), trust);
+        }
+    }
+
+    /// Capture pools, balances, peers, local trust and interaction
+    ///  histories in a form we can write to disk
+    pub async fn snapshot(&self) -> TrackerSnapshot {
+        let pools = self.pools.read().await;
+        let pool_trust = self.pool_trust.read().await;
+        let left_pools = self.left_pools.read().await;
+
+        let mut snapshots = Vec::with_capacity(pools.len());
+        for (key, balance) in pools.iter() {
+            let (peers, local_trust, interactions) = match pool_trust.get(key) {
+                Some(eigen) => {
+                    let local_trust = eigen
+                        .get_peers()
+                        .iter()
+                        .filter_map(|peer| eigen.get_local_trust(peer).map(|t| (*peer, t)))
+                        .collect();
+                    match eigen.get_fetcher() {
+                        Some(fetcher) => (
+                            fetcher.peers.read().await.iter().cloned().collect(),
+                            local_trust,
+                            fetcher.snapshot_interactions().await,
+                        ),
+                        None => (Vec::new(), local_trust, Vec::new()),
+                    }
+                }
+                None => (Vec::new(), Vec::new(), Vec::new()),
+            };
+            snapshots.push(PoolSnapshot {
+                hash: key.hash,
+                format: self.content_format(key.hash).await,
+                address: key.address,
+                balance: *balance,
+                peers,
+                local_trust,
+                interactions,
+                left: left_pools.contains(&key.address),
+            });
+        }
+
+        TrackerSnapshot::new(snapshots)
+    }
+
+    /// Write the current tracker state to the tracker store
+    pub async fn persist(&self) -> Result<()> {
+        let snapshot = self.snapshot().await;
+        self.store.save(&snapshot).await?;
+        tracing::debug!(
+            "node::tracker: persisted {} pools to {}",
+            snapshot.pools.len(),
+            self.store.path().display()
+        );
+        Ok(())
+    }
+
+    /// Seed the tracker from a previously persisted snapshot
+    async fn restore(&self, snapshot: TrackerSnapshot) -> Result<()> {
+        tracing::info!(
+            "node::tracker: restoring {} pools from snapshot",
+            snapshot.pools.len()
+        );
+        for pool in snapshot.pools {
+            let key = PoolKey {
+                hash: pool.hash,
+                address: pool.address,
+            };
+            self.set_content_format(pool.hash, pool.format).await;
+            self.add_pool(key.clone(), pool.balance).await?;
+            if pool.left {
+                self.left_pools.write().await.insert(pool.address);
+                self.gossip.leave(TopicId::for_pool(pool.address)).await;
+            }
+
+            if let Some(eigen) = self.pool_trust.write().await.get_mut(&key) {
+                if let Some(fetcher) = eigen.get_fetcher_mut() {
+                    for peer in &pool.peers {
+                        fetcher.add_peer(*peer).await;
+                    }
+                    fetcher.restore_interactions(&pool.interactions).await;
+                }
+                for peer in pool.peers {
+                    eigen.add_peer(peer);
+                }
+                for (peer, trust) in pool.local_trust {
+                    eigen.add_local_trust(peer, trust);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Find first available peer with positive trust score
+    pub async fn find_peer(&self, hash: Hash) -> Option<NodeId> {
+        let pools = self.pools.read().await;
+
+        for (pool_key, _) in pools.iter() {
+            if pool_key.hash == hash {
+                if let Ok(Some(trust_scores)) = self.get_pool_trust(pool_key).await {
+                    // Return first peer with positive trust score
+                    return trust_scores
+                        .into_iter()
+                        .find(|(peer, trust)| *trust > 0.0 && *peer != self.current_node_id)
+                        .map(|(peer, _)| peer);
+                }
+            }
+        }
+        None
+    }
+
+    /// Format of content we hold -- either our own tags or what the
+    ///  pool told us about it
+    pub async fn stored_format(&self, hash: Hash) -> Result<BlobFormat> {
+        match self.content_format(hash).await {
+            BlobFormat::HashSeq => Ok(BlobFormat::HashSeq),
+            BlobFormat::Raw => self.blobs_service.get_format(hash).await,
+        }
+    }
+
+    /// Pull a blob from the network. Anything we didn't already hold
+    ///  is cached, and may be evicted under the retention policy.
+    pub async fn pull_blob(&self, hash: Hash) -> Result<()> {
+        // Check if we already have the blob
+        let format = self.content_format(hash).await;
+        let stat = self.blobs_service.has_content(hash, format).await?;
+        if !stat {
+            let peer = self
+                .find_peer(hash)
+                .await
+                .ok_or_else(|| anyhow::anyhow!("No peers available for hash {}", hash))?;
+
+            let ticket = BlobTicket::new(peer.into(), hash, format).expect("valid ticket");
+
+            self.blobs_service
+                .download_blob(&ticket, BlobTag::Cache(hash))
+                .await?;
+        } else {
+            self.blobs_service.touch(hash).await;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NodeMetadata for Tracker {
+    async fn pools(&self) -> Result<Vec<PoolAnnouncement>> {
+        let mut pools = Vec::new();
+        for key in self.all_joined_pools().await? {
+            pools.push(PoolAnnouncement {
+                address: key.address,
+                hash: key.hash,
+                format: self.content_format(key.hash).await,
+            });
+        }
+        Ok(pools)
+    }
+
+    async fn hashes(&self, after: Option<Hash>, limit: usize) -> Result<Vec<Hash>> {
+        let mut hashes = Vec::new();
+        for hash in self.blobs_service.complete_hashes_after(after).await? {
+            let hash = hash?;
+            // content behind an ACL isn't ours to advertise
+            if self
+                .blobs_service
+                .acls()
+                .get(&hash)
+                .await
+                .is_some_and(|acl| !acl.public)
+            {
+                continue;
+            }
+            hashes.push(hash);
+            if hashes.len() == limit {
+                break;
+            }
+        }
+        Ok(hashes)
+    }
+
+    async fn trust_row(&self, pool: Address) -> Result<Option<HashMap<NodeId, f64>>> {
+        Ok(self.local_trust_row(pool).await)
+    }
+
+    fn identity(&self) -> NodeIdentity {
+        NodeIdentity {
+            node_id: self.current_node_id,
+            beneficiary: self.eth_private_key.address(),
+            signature: self.iroh_signature,
+        }
+    }
+}
+
+/// Run `op` with the pools in `keys` marked as left, so the update loop
+///  doesn't rejoin them and fetch their content back in the meantime.
+///  Unless `op` comes back with something, the pools we weren't already
+///  out of are marked as joined again.
+async fn while_leaving<T>(
+    left_pools: &RwLock<HashSet<Address>>,
+    keys: &[PoolKey],
+    op: impl std::future::Future<Output = Result<Option<T>>>,
+) -> Result<Option<T>> {
+    let marked = {
+        let mut left_pools = left_pools.write().await;
+        keys.iter()
+            .filter(|key| left_pools.insert(key.address))
+            .map(|key| key.address)
+            .collect::<Vec<_>>()
+    };
+    let result = op.await;
+    if !matches!(result, Ok(Some(_))) {
+        let mut left_pools = left_pools.write().await;
+        for address in marked {
+            left_pools.remove(&address);
+        }
+    }
+    result
+}
+
+#[async_trait]
+impl ServeContext for Tracker {
+    async fn is_pool_peer(&self, node_id: &NodeId, hash: &Hash) -> bool {
+        // the pools we hold `hash` for, short of any we're leaving
+        let mut addresses = self
+            .blobs_service
+            .tags_for(hash)
+            .into_iter()
+            .filter_map(|(tag, _)| match tag {
+                BlobTag::Pool(address) => Some(address),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        {
+            let left_pools = self.left_pools.read().await;
+            addresses.retain(|address| !left_pools.contains(address));
+        }
+        if addresses.is_empty() {
+            return false;
+        }
+        for (key, eigen) in self.pool_trust.read().await.iter() {
+            if key.hash != *hash || !addresses.contains(&key.address) {
+                continue;
+            }
+            if let Some(fetcher) = eigen.get_fetcher() {
+                if fetcher.peers.read().await.contains(node_id) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys() -> Vec<PoolKey> {
+        (1..=2u8)
+            .map(|i| PoolKey {
+                hash: Hash::new([i]),
+                address: Address::repeat_byte(i),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_left_after_delete() {
+        let left_pools = RwLock::new(HashSet::new());
+        let keys = keys();
+        let result = while_leaving(&left_pools, &keys, async {
+            // no rejoining while the delete runs
+            let left_pools = left_pools.read().await;
+            assert!(keys.iter().all(|key| left_pools.contains(&key.address)));
+            Ok(Some(()))
+        })
+        .await;
+        assert!(matches!(result, Ok(Some(()))));
+        let left_pools = left_pools.read().await;
+        assert!(keys.iter().all(|key| left_pools.contains(&key.address)));
+    }
+
+    #[tokio::test]
+    async fn test_still_joined_when_delete_fails() {
+        let keys = keys();
+        // already left one of them before
+        let left_pools = RwLock::new(HashSet::from([keys[0].address]));
+
+        let failed = while_leaving(&left_pools, &keys, async {
+            assert!(left_pools.read().await.contains(&keys[1].address));
+            Err::<Option<()>, _>(anyhow::anyhow!("store is gone"))
+        })
+        .await;
+        assert!(failed.is_err());
+        assert_eq!(*left_pools.read().await, HashSet::from([keys[0].address]));
+
+        // nothing to delete is no reason to leave either
+        let missing = while_leaving(&left_pools, &keys, async { Ok(None::<()>) }).await;
+        assert!(matches!(missing, Ok(None)));
+        assert_eq!(*left_pools.read().await, HashSet::from([keys[0].address]));
+    }
+
+    #[tokio::test]
+    async fn test_gossip_peers_confirmed() {
+        let fetcher = NetworkTrustFetcher::new(
+            keys()[0].clone(),
+            Arc::new("ws://localhost:8545".parse().unwrap()),
+        );
+        let peer = iroh::SecretKey::from_bytes(&[1u8; 32]).public();
+        let stranger = iroh::SecretKey::from_bytes(&[2u8; 32]).public();
+        fetcher.add_peer(peer).await;
+        // peers on chain need no probing
+        assert!(!fetcher.start_confirming(peer).await);
+
+        // gossip alone doesn't make a peer, nor does a failed probe
+        assert!(fetcher.start_confirming(stranger).await);
+        assert!(!fetcher.start_confirming(stranger).await);
+        fetcher.finish_confirming(stranger, false).await;
+        assert!(!fetcher.gossip_peers.read().await.contains(&stranger));
+        assert!(fetcher.start_confirming(stranger).await);
+        fetcher.finish_confirming(stranger, true).await;
+        assert!(fetcher.gossip_peers.read().await.contains(&stranger));
+        assert!(!fetcher.is_peer(&stranger).await);
+
+        // and there's only so much room for gossip peers
+        for i in 0..MAX_GOSSIP_PEERS as u32 {
+            let mut seed = [3u8; 32];
+            seed[..4].copy_from_slice(&i.to_be_bytes());
+            fetcher
+                .start_confirming(iroh::SecretKey::from_bytes(&seed).public())
+                .await;
+        }
+        assert_eq!(
+            fetcher.gossip_peers.read().await.len() + fetcher.confirming.read().await.len(),
+            MAX_GOSSIP_PEERS
+        );
+    }
+}
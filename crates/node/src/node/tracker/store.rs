@@ -0,0 +1,244 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy::primitives::{Address, U256};
+use anyhow::{anyhow, Result};
+use iroh::NodeId;
+use iroh_blobs::{BlobFormat, Hash};
+use serde::{Deserialize, Serialize};
+
+/// Bump this whenever the snapshot layout changes in a way
+///  older nodes can't read -- mismatched snapshots are ignored
+pub const TRACKER_SNAPSHOT_VERSION: u32 = 1;
+
+/// How many interaction records we keep per (from, to) pair
+///  when writing a snapshot. With a 10 minute half-life anything
+///  past this is noise anyways.
+const MAX_INTERACTIONS_PER_PAIR: usize = 256;
+
+/// A single probe outcome between two nodes
+#[derive(Debug, Clone, Copy)]
+pub struct Interaction {
+    pub successes: u64,
+    pub failures: u64,
+    pub timestamp: SystemTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractionSnapshot {
+    pub successes: u64,
+    pub failures: u64,
+    /// seconds since the unix epoch
+    pub timestamp: u64,
+}
+
+impl From<&Interaction> for InteractionSnapshot {
+    fn from(interaction: &Interaction) -> Self {
+        Self {
+            successes: interaction.successes,
+            failures: interaction.failures,
+            timestamp: interaction
+                .timestamp
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+}
+
+impl From<&InteractionSnapshot> for Interaction {
+    fn from(snapshot: &InteractionSnapshot) -> Self {
+        Self {
+            successes: snapshot.successes,
+            failures: snapshot.failures,
+            timestamp: UNIX_EPOCH + Duration::from_secs(snapshot.timestamp),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractionHistorySnapshot {
+    pub from: NodeId,
+    pub to: NodeId,
+    pub records: Vec<InteractionSnapshot>,
+}
+
+impl InteractionHistorySnapshot {
+    pub fn new(from: NodeId, to: NodeId, records: &[Interaction]) -> Self {
+        let skip = records.len().saturating_sub(MAX_INTERACTIONS_PER_PAIR);
+        Self {
+            from,
+            to,
+            records: records.iter().skip(skip).map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolSnapshot {
+    pub hash: Hash,
+    #[serde(default)]
+    pub format: BlobFormat,
+    pub address: Address,
+    pub balance: U256,
+    pub peers: Vec<NodeId>,
+    pub local_trust: Vec<(NodeId, f64)>,
+    pub interactions: Vec<InteractionHistorySnapshot>,
+    /// We deleted the content, so stay out of the pool
+    #[serde(default)]
+    pub left: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackerSnapshot {
+    pub version: u32,
+    /// seconds since the unix epoch
+    pub saved_at: u64,
+    pub pools: Vec<PoolSnapshot>,
+}
+
+impl TrackerSnapshot {
+    pub fn new(pools: Vec<PoolSnapshot>) -> Self {
+        Self {
+            version: TRACKER_SNAPSHOT_VERSION,
+            saved_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            pools,
+        }
+    }
+
+    /// Trust values go straight into eigentrust, which only takes finite,
+    ///  non-negative ones -- reject anything else rather than panic later
+    pub fn validate(&self) -> Result<()> {
+        for pool in &self.pools {
+            for (peer, trust) in &pool.local_trust {
+                if !trust.is_finite() || *trust < 0.0 {
+                    return Err(anyhow!(
+                        "invalid local trust {} for {} in pool {}",
+                        trust,
+                        peer,
+                        pool.address
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Versioned JSON snapshots of tracker state on disk
+#[derive(Debug, Clone)]
+pub struct TrackerStore {
+    path: PathBuf,
+}
+
+impl TrackerStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load the last snapshot, if there is one we can read
+    pub async fn load(&self) -> Result<Option<TrackerSnapshot>> {
+        let data = match tokio::fs::read(&self.path).await {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let snapshot: TrackerSnapshot = serde_json::from_slice(&data)?;
+        if snapshot.version != TRACKER_SNAPSHOT_VERSION {
+            tracing::warn!(
+                "node::tracker::store: ignoring snapshot with version {} (expected {})",
+                snapshot.version,
+                TRACKER_SNAPSHOT_VERSION
+            );
+            return Ok(None);
+        }
+        snapshot.validate()?;
+        Ok(Some(snapshot))
+    }
+
+    /// Write a snapshot, replacing the previous one atomically
+    pub async fn save(&self, snapshot: &TrackerSnapshot) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let data = serde_json::to_vec_pretty(snapshot)?;
+        let tmp_path = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, data).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip() {
+        let dir = std::env::temp_dir().join(format!("jax-tracker-{}", uuid::Uuid::new_v4()));
+        let store = TrackerStore::new(dir.join("tracker.json"));
+        assert!(store.load().await.unwrap().is_none());
+
+        let node_id = iroh::SecretKey::from_bytes(&[7u8; 32]).public();
+        let interaction = Interaction {
+            successes: 1,
+            failures: 0,
+            timestamp: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        };
+        let snapshot = TrackerSnapshot::new(vec![PoolSnapshot {
+            hash: Hash::new(b"hello"),
+            format: BlobFormat::HashSeq,
+            address: Address::ZERO,
+            balance: U256::from(42),
+            peers: vec![node_id],
+            local_trust: vec![(node_id, 0.5)],
+            interactions: vec![InteractionHistorySnapshot::new(
+                node_id,
+                node_id,
+                &[interaction],
+            )],
+            left: true,
+        }]);
+        store.save(&snapshot).await.unwrap();
+
+        let loaded = store.load().await.unwrap().expect("snapshot");
+        assert_eq!(loaded.pools.len(), 1);
+        let pool = &loaded.pools[0];
+        assert_eq!(pool.hash, Hash::new(b"hello"));
+        assert_eq!(pool.format, BlobFormat::HashSeq);
+        assert_eq!(pool.balance, U256::from(42));
+        assert_eq!(pool.peers, vec![node_id]);
+        assert_eq!(pool.interactions[0].records[0].timestamp, 1_700_000_000);
+        assert!(pool.left);
+
+        let _ = tokio::fs::remove_dir_all(dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_invalid_trust_rejected() {
+        let dir = std::env::temp_dir().join(format!("jax-tracker-{}", uuid::Uuid::new_v4()));
+        let store = TrackerStore::new(dir.join("tracker.json"));
+        let node_id = iroh::SecretKey::from_bytes(&[7u8; 32]).public();
+        let snapshot = TrackerSnapshot::new(vec![PoolSnapshot {
+            hash: Hash::new(b"hello"),
+            format: BlobFormat::Raw,
+            address: Address::ZERO,
+            balance: U256::ZERO,
+            peers: vec![node_id],
+            local_trust: vec![(node_id, -1.0)],
+            interactions: Vec::new(),
+            left: false,
+        }]);
+        store.save(&snapshot).await.unwrap();
+        assert!(store.load().await.is_err());
+
+        let _ = tokio::fs::remove_dir_all(dir).await;
+    }
+}
@@ -0,0 +1,495 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use iroh_blobs::{BlobFormat, Hash};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::{Mutex, RwLock};
+use uuid::Uuid;
+
+use super::iroh::{BlobsService, ImportMode};
+
+/// Sessions nobody has written to in this long are dropped
+const UPLOAD_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// How often we look for expired sessions while running
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How much anybody can have in flight at once. Sessions are open to
+///  any caller of the API, so these keep them from filling the disk.
+#[derive(Debug, Clone, Copy)]
+pub struct UploadLimits {
+    /// Open sessions at any one time
+    pub max_sessions: usize,
+    /// Bytes declared across all open sessions
+    pub max_pending_bytes: u64,
+}
+
+impl Default for UploadLimits {
+    fn default() -> Self {
+        Self {
+            max_sessions: 16,
+            max_pending_bytes: 64 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+/// Where an upload session stands
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct UploadInfo {
+    pub id: Uuid,
+    /// bytes received so far -- the next PATCH has to start here
+    pub offset: u64,
+    /// total bytes declared when the session was created
+    pub length: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct UploadMeta {
+    id: Uuid,
+    length: u64,
+    /// seconds since the unix epoch
+    created_at: u64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UploadError {
+    #[error("no such upload: {0}")]
+    NotFound(Uuid),
+    #[error("upload is at offset {expected}, not {got}")]
+    OffsetMismatch { expected: u64, got: u64 },
+    #[error("data runs past the declared length of {0} bytes")]
+    TooLong(u64),
+    #[error("upload is incomplete: have {offset} of {length} bytes")]
+    Incomplete { offset: u64, length: u64 },
+    #[error("upload is being written to by another request")]
+    Busy,
+    #[error("too many uploads in progress (at most {0})")]
+    TooManySessions(usize),
+    #[error("{requested} bytes would take uploads in progress past {limit} bytes")]
+    OverQuota { requested: u64, limit: u64 },
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Default(#[from] anyhow::Error),
+}
+
+#[derive(Debug)]
+struct Session {
+    meta: UploadMeta,
+    offset: AtomicU64,
+    /// seconds since the unix epoch of the last write, so a slow upload
+    ///  that's still going isn't dropped
+    active_at: AtomicU64,
+    /// held for as long as a request is writing to or finalizing the upload
+    lock: Mutex<()>,
+}
+
+impl Session {
+    fn expired(&self) -> bool {
+        now().saturating_sub(self.active_at.load(Ordering::SeqCst)) > UPLOAD_TTL.as_secs()
+    }
+
+    fn touch(&self) {
+        self.active_at.store(now(), Ordering::SeqCst);
+    }
+
+    fn info(&self) -> UploadInfo {
+        UploadInfo {
+            id: self.meta.id,
+            offset: self.offset.load(Ordering::SeqCst),
+            length: self.meta.length,
+        }
+    }
+}
+
+/// Resumable upload sessions. Partial data lives in the blobs temp dir
+///  alongside a small metadata file, so sessions survive a restart.
+#[derive(Debug, Clone)]
+pub struct Uploads {
+    dir: PathBuf,
+    limits: UploadLimits,
+    sessions: Arc<RwLock<HashMap<Uuid, Arc<Session>>>>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn part_path(dir: &Path, id: Uuid) -> PathBuf {
+    dir.join(format!("upload-{}.part", id))
+}
+
+fn meta_path(dir: &Path, id: Uuid) -> PathBuf {
+    dir.join(format!("upload-{}.json", id))
+}
+
+impl Uploads {
+    /// Pick up any sessions left in `dir`, dropping expired ones then
+    ///  and every so often from then on
+    pub async fn load(dir: PathBuf, limits: UploadLimits) -> Result<Self> {
+        tokio::fs::create_dir_all(&dir).await?;
+        let mut sessions = HashMap::new();
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let is_meta = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("upload-") && name.ends_with(".json"));
+            if !is_meta {
+                continue;
+            }
+            let meta: UploadMeta = match serde_json::from_slice(&tokio::fs::read(&path).await?) {
+                Ok(meta) => meta,
+                Err(e) => {
+                    tracing::warn!(
+                        "node::uploads::load: ignoring unreadable session {}: {}",
+                        path.display(),
+                        e
+                    );
+                    continue;
+                }
+            };
+            // the data's last write is as close as we get to the last activity
+            let (offset, active_at) = match tokio::fs::metadata(part_path(&dir, meta.id)).await {
+                Ok(metadata) => {
+                    let modified = metadata
+                        .modified()
+                        .ok()
+                        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                        .map_or(meta.created_at, |since| since.as_secs());
+                    (metadata.len().min(meta.length), modified)
+                }
+                Err(_) => (0, meta.created_at),
+            };
+            sessions.insert(
+                meta.id,
+                Arc::new(Session {
+                    meta,
+                    offset: AtomicU64::new(offset),
+                    active_at: AtomicU64::new(active_at),
+                    lock: Mutex::new(()),
+                }),
+            );
+        }
+        let uploads = Self {
+            dir,
+            limits,
+            sessions: Arc::new(RwLock::new(sessions)),
+        };
+        uploads.prune().await;
+
+        tokio::spawn({
+            let uploads = uploads.clone();
+            async move {
+                let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+                // the first tick is immediate, and we just pruned
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+                    uploads.prune().await;
+                }
+            }
+        });
+        Ok(uploads)
+    }
+
+    /// Drop sessions gone idle, along with their data.
+    ///  Sessions a request is busy with are left for next time.
+    pub async fn prune(&self) {
+        let expired = self
+            .sessions
+            .read()
+            .await
+            .values()
+            .filter(|session| session.expired())
+            .cloned()
+            .collect::<Vec<_>>();
+        for session in expired {
+            let Ok(_guard) = session.lock.try_lock() else {
+                continue;
+            };
+            tracing::info!(
+                "node::uploads::prune: dropping expired upload {}",
+                session.meta.id
+            );
+            if let Err(e) = self.discard(session.meta.id).await {
+                tracing::warn!(
+                    "node::uploads::prune: failed to drop upload {}: {}",
+                    session.meta.id,
+                    e
+                );
+            }
+        }
+    }
+
+    async fn session(&self, id: Uuid) -> Result<Arc<Session>, UploadError> {
+        self.sessions
+            .read()
+            .await
+            .get(&id)
+            .cloned()
+            .ok_or(UploadError::NotFound(id))
+    }
+
+    /// Start a new session for `length` bytes, if that stays within
+    ///  our limits
+    pub async fn create(&self, length: u64) -> Result<UploadInfo, UploadError> {
+        // held until the session is in, so concurrent creates can't both
+        //  squeeze under the limits
+        let mut sessions = self.sessions.write().await;
+        if sessions.len() >= self.limits.max_sessions {
+            return Err(UploadError::TooManySessions(self.limits.max_sessions));
+        }
+        let pending = sessions
+            .values()
+            .map(|session| session.meta.length)
+            .sum::<u64>();
+        if pending.saturating_add(length) > self.limits.max_pending_bytes {
+            return Err(UploadError::OverQuota {
+                requested: length,
+                limit: self.limits.max_pending_bytes,
+            });
+        }
+
+        let meta = UploadMeta {
+            id: Uuid::new_v4(),
+            length,
+            created_at: now(),
+        };
+        tokio::fs::File::create(part_path(&self.dir, meta.id)).await?;
+        tokio::fs::write(
+            meta_path(&self.dir, meta.id),
+            serde_json::to_vec(&meta).map_err(anyhow::Error::from)?,
+        )
+        .await?;
+        let session = Arc::new(Session {
+            active_at: AtomicU64::new(meta.created_at),
+            meta,
+            offset: AtomicU64::new(0),
+            lock: Mutex::new(()),
+        });
+        let info = session.info();
+        sessions.insert(info.id, session);
+        Ok(info)
+    }
+
+    pub async fn info(&self, id: Uuid) -> Result<UploadInfo, UploadError> {
+        Ok(self.session(id).await?.info())
+    }
+
+    /// Append `data` to the upload, which must currently be at `offset`.
+    ///  Whatever arrives before the stream fails is kept, so the client
+    ///  can pick up from the new offset.
+    pub async fn append<E: Display>(
+        &self,
+        id: Uuid,
+        offset: u64,
+        mut data: impl Stream<Item = Result<Bytes, E>> + Unpin,
+    ) -> Result<UploadInfo, UploadError> {
+        let session = self.session(id).await?;
+        let _guard = session.lock.try_lock().map_err(|_| UploadError::Busy)?;
+        session.touch();
+        let expected = session.offset.load(Ordering::SeqCst);
+        if offset != expected {
+            return Err(UploadError::OffsetMismatch {
+                expected,
+                got: offset,
+            });
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(part_path(&self.dir, id))
+            .await?;
+        // drop anything past the offset we last acknowledged
+        file.set_len(offset).await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+
+        let mut written = offset;
+        let result = loop {
+            let chunk = match data.next().await {
+                Some(Ok(chunk)) => chunk,
+                Some(Err(e)) => break Err(UploadError::Io(std::io::Error::other(e.to_string()))),
+                None => break Ok(()),
+            };
+            if written + chunk.len() as u64 > session.meta.length {
+                break Err(UploadError::TooLong(session.meta.length));
+            }
+            if let Err(e) = file.write_all(&chunk).await {
+                break Err(e.into());
+            }
+            written += chunk.len() as u64;
+        };
+        file.flush().await?;
+        file.sync_data().await?;
+        session.offset.store(written, Ordering::SeqCst);
+        session.touch();
+
+        result.map(|_| session.info())
+    }
+
+    /// Import a complete upload into the blob store and end the session
+    pub async fn finalize(
+        &self,
+        id: Uuid,
+        blobs_service: &BlobsService,
+    ) -> Result<(Hash, BlobFormat), UploadError> {
+        let session = self.session(id).await?;
+        let _guard = session.lock.try_lock().map_err(|_| UploadError::Busy)?;
+        let info = session.info();
+        if info.offset != info.length {
+            return Err(UploadError::Incomplete {
+                offset: info.offset,
+                length: info.length,
+            });
+        }
+        let imported = blobs_service
+            .import_path(part_path(&self.dir, id), ImportMode::Copy, None)
+            .await?;
+        self.discard(id).await?;
+        Ok(imported)
+    }
+
+    /// End a session without importing anything
+    pub async fn remove(&self, id: Uuid) -> Result<(), UploadError> {
+        let session = self.session(id).await?;
+        let _guard = session.lock.try_lock().map_err(|_| UploadError::Busy)?;
+        self.discard(id).await
+    }
+
+    async fn discard(&self, id: Uuid) -> Result<(), UploadError> {
+        self.sessions.write().await.remove(&id);
+        let _ = tokio::fs::remove_file(part_path(&self.dir, id)).await;
+        tokio::fs::remove_file(meta_path(&self.dir, id)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(data: &[&'static [u8]]) -> impl Stream<Item = Result<Bytes, String>> + Unpin {
+        futures::stream::iter(
+            data.iter()
+                .map(|chunk| Ok(Bytes::from_static(chunk)))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_append_resume_across_reload() {
+        let dir = std::env::temp_dir().join(format!("jax-uploads-{}", Uuid::new_v4()));
+        let uploads = Uploads::load(dir.clone(), UploadLimits::default())
+            .await
+            .unwrap();
+
+        let info = uploads.create(10).await.unwrap();
+        let info = uploads
+            .append(info.id, 0, chunks(&[b"hello"]))
+            .await
+            .unwrap();
+        assert_eq!(info.offset, 5);
+
+        // wrong offset is refused, and so is running past the length
+        assert!(matches!(
+            uploads.append(info.id, 0, chunks(&[b"x"])).await,
+            Err(UploadError::OffsetMismatch { expected: 5, .. })
+        ));
+        assert!(matches!(
+            uploads.append(info.id, 5, chunks(&[b"world!"])).await,
+            Err(UploadError::TooLong(10))
+        ));
+
+        // a fresh load sees the same session at the same offset
+        let uploads = Uploads::load(dir.clone(), UploadLimits::default())
+            .await
+            .unwrap();
+        let info = uploads.info(info.id).await.unwrap();
+        assert_eq!((info.offset, info.length), (5, 10));
+
+        let info = uploads
+            .append(info.id, 5, chunks(&[b"wor", b"ld"]))
+            .await
+            .unwrap();
+        assert_eq!(info.offset, 10);
+        let data = tokio::fs::read(part_path(&dir, info.id)).await.unwrap();
+        assert_eq!(data, b"helloworld");
+
+        uploads.remove(info.id).await.unwrap();
+        assert!(matches!(
+            uploads.info(info.id).await,
+            Err(UploadError::NotFound(_))
+        ));
+
+        let _ = tokio::fs::remove_dir_all(dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_limits_and_prune() {
+        let dir = std::env::temp_dir().join(format!("jax-uploads-{}", Uuid::new_v4()));
+        let limits = UploadLimits {
+            max_sessions: 2,
+            max_pending_bytes: 100,
+        };
+        let uploads = Uploads::load(dir.clone(), limits).await.unwrap();
+
+        let first = uploads.create(60).await.unwrap();
+        assert!(matches!(
+            uploads.create(50).await,
+            Err(UploadError::OverQuota { .. })
+        ));
+        uploads.create(40).await.unwrap();
+        assert!(matches!(
+            uploads.create(0).await,
+            Err(UploadError::TooManySessions(2))
+        ));
+
+        // an old session that's still being written to stays
+        let session = uploads.session(first.id).await.unwrap();
+        let stale = Arc::new(Session {
+            meta: UploadMeta {
+                id: first.id,
+                length: first.length,
+                created_at: now() - UPLOAD_TTL.as_secs() - 1,
+            },
+            offset: AtomicU64::new(session.offset.load(Ordering::SeqCst)),
+            active_at: AtomicU64::new(now() - UPLOAD_TTL.as_secs() - 1),
+            lock: Mutex::new(()),
+        });
+        uploads.sessions.write().await.insert(first.id, stale);
+        uploads
+            .append(first.id, 0, chunks(&[b"hello"]))
+            .await
+            .unwrap();
+        uploads.prune().await;
+        assert_eq!(uploads.info(first.id).await.unwrap().offset, 5);
+
+        // and goes once it's been idle past the TTL
+        let session = uploads.session(first.id).await.unwrap();
+        session
+            .active_at
+            .store(now() - UPLOAD_TTL.as_secs() - 1, Ordering::SeqCst);
+        uploads.prune().await;
+        assert!(matches!(
+            uploads.info(first.id).await,
+            Err(UploadError::NotFound(_))
+        ));
+        assert!(!part_path(&dir, first.id).exists());
+        uploads.create(60).await.unwrap();
+
+        let _ = tokio::fs::remove_dir_all(dir).await;
+    }
+}